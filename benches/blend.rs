@@ -0,0 +1,60 @@
+//! Simple timing benchmarks. Run with `cargo bench`.
+use std::{hint::black_box, time::Instant};
+
+use image::{DynamicImage, RgbaImage};
+use image_blend::{
+    pixelops::{pixel_mult, pixel_normal},
+    BlendMode, BufferBlend, DynamicChops,
+};
+
+fn layer(seed: u32) -> DynamicImage {
+    DynamicImage::ImageRgba8(RgbaImage::from_fn(2048, 2048, |x, y| {
+        let v = ((x ^ y).wrapping_mul(seed) % 256) as u8;
+        // Mostly opaque with transparent and partially transparent regions, like a typical layer
+        let alpha = match (x / 256 + y / 256) % 4 {
+            0 => 0,
+            1 => 128,
+            _ => 255,
+        };
+        image::Rgba([v, v.wrapping_add(85), v.wrapping_add(170), alpha])
+    }))
+}
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    f();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    println!("{name}: {:?} per iteration", start.elapsed() / iterations);
+}
+
+fn main() {
+    let base = layer(7);
+    let top = layer(13);
+    bench("paste rgba8 (float path)", 10, || {
+        let mut img = base.clone();
+        img.blend(black_box(&top), pixel_normal, true, true).unwrap();
+        black_box(img);
+    });
+    bench("paste rgba8 (BlendMode::Normal fast path)", 10, || {
+        let mut img = base.clone();
+        img.blend_mode(black_box(&top), BlendMode::Normal, true, true).unwrap();
+        black_box(img);
+    });
+    let base_rgb = base.to_rgb8();
+    let top_rgb = top.to_rgb8();
+    bench("multiply rgb8 buffer (generic, inlined)", 10, || {
+        let mut img = base_rgb.clone();
+        img.blend(black_box(&top_rgb), pixel_mult, true, false).unwrap();
+        black_box(img);
+    });
+    // `DynamicChops` calls `op` through a `&dyn Fn`, as with a mode chosen at runtime
+    let base_rgb = DynamicImage::ImageRgb8(base_rgb);
+    let top_rgb = DynamicImage::ImageRgb8(top_rgb);
+    bench("multiply rgb8 dynamic (dyn Fn)", 10, || {
+        let mut img = base_rgb.clone();
+        img.blend(black_box(&top_rgb), pixel_mult, true, false).unwrap();
+        black_box(img);
+    });
+}
@@ -0,0 +1,92 @@
+//! A contact sheet of every `BlendMode` applied to the same generated pair of images. Run with `cargo run --example contact_sheet`.
+//!
+//! Each tile is labelled with its mode, so changes to a mode can be checked by eye against a previous run.
+use image::{imageops, ColorType, DynamicImage, Rgba, RgbaImage};
+use image_blend::{
+    checkerboard, gradient,
+    pixelops::{pixel_normal, pixel_overlay},
+    BlendMode, Direction, DynamicChops,
+};
+
+const TILE: u32 = 48;
+const LABEL_HEIGHT: u32 = 9;
+const GAP: u32 = 4;
+const COLUMNS: u32 = 6;
+/// 3x5 glyphs for A-Z, one octal digit per row from the top, where 4 is the leftmost pixel.
+const GLYPHS: [u32; 26] = [
+    0o25755, 0o65656, 0o34443, 0o65556, 0o74647, 0o74644, 0o34553, 0o55755, 0o72227, 0o11152, 0o55655, 0o44447, 0o57755,
+    0o65555, 0o25552, 0o65644, 0o25563, 0o65655, 0o34216, 0o72222, 0o55557, 0o55552, 0o55775, 0o55255, 0o55222, 0o71247,
+];
+
+const MODES: [BlendMode; 25] = [
+    BlendMode::Normal,
+    BlendMode::Add,
+    BlendMode::Subtract,
+    BlendMode::Divide,
+    BlendMode::Darker,
+    BlendMode::Lighter,
+    BlendMode::Difference,
+    BlendMode::Multiply,
+    BlendMode::Screen,
+    BlendMode::Overlay,
+    BlendMode::HardLight,
+    BlendMode::SoftLight,
+    BlendMode::GrainExtract,
+    BlendMode::GrainMerge,
+    BlendMode::BipolarAdd,
+    BlendMode::Phoenix,
+    BlendMode::LinearBurn,
+    BlendMode::LinearDodge,
+    BlendMode::LinearLight,
+    BlendMode::VividLight,
+    BlendMode::PinLight,
+    BlendMode::HardMix,
+    BlendMode::Average,
+    BlendMode::Dissolve(7),
+    BlendMode::Custom(pixel_normal),
+];
+
+/// The mode's name without any payload, e.g. `DISSOLVE` for `Dissolve(7)`.
+fn label(mode: BlendMode) -> String {
+    let name = format!("{mode:?}");
+    name.split('(').next().unwrap().to_uppercase()
+}
+
+/// Draw `text` in black with its top left corner at `x`, `y`. Anything but A-Z is left as a space.
+fn draw_text(sheet: &mut RgbaImage, x: u32, y: u32, text: &str) {
+    for (i, c) in (0u32..).zip(text.chars()) {
+        let Some(glyph) = c.is_ascii_uppercase().then(|| GLYPHS[(c as u8 - b'A') as usize]) else {
+            continue;
+        };
+        for row in 0..5 {
+            for col in 0..3 {
+                if glyph >> ((4 - row) * 3 + (2 - col)) & 1 == 1 {
+                    sheet.put_pixel(x + i * 4 + col, y + row, Rgba([0, 0, 0, 255]));
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    let base = gradient(TILE, TILE, Direction::Horizontal, ColorType::Rgba8, &[0.1, 0.2, 0.8, 1.], &[1., 0.8, 0.1, 1.]).unwrap();
+    let mut top = gradient(TILE, TILE, Direction::Vertical, ColorType::Rgba8, &[1., 0.4, 0.2, 1.], &[0.2, 0.9, 0.6, 0.25]).unwrap();
+    let checker = checkerboard(TILE, TILE, 8, ColorType::Rgba8, &[0.25, 0.25, 0.25, 1.], &[0.75, 0.75, 0.75, 1.]).unwrap();
+    top.blend(&checker, pixel_overlay, true, false).unwrap();
+
+    let rows = u32::try_from(MODES.len()).unwrap().div_ceil(COLUMNS);
+    let (cell_width, cell_height) = (TILE + GAP, TILE + LABEL_HEIGHT);
+    let mut sheet = RgbaImage::from_pixel(COLUMNS * cell_width, rows * cell_height, Rgba([255, 255, 255, 255]));
+    for (i, mode) in (0u32..).zip(MODES) {
+        let label = label(mode);
+        assert!(u32::try_from(label.len()).unwrap() * 4 <= cell_width, "{label} is wider than its tile");
+        let (x, y) = ((i % COLUMNS) * cell_width, (i / COLUMNS) * cell_height);
+        let mut tile: DynamicImage = base.clone();
+        tile.blend_mode(&top, mode, true, false).unwrap();
+        imageops::replace(&mut sheet, &tile.to_rgba8(), i64::from(x), i64::from(y));
+        draw_text(&mut sheet, x + 1, y + TILE + 2, &label);
+    }
+    std::fs::create_dir_all("tests_out").unwrap();
+    sheet.save("tests_out/contact_sheet.png").unwrap();
+    println!("saved tests_out/contact_sheet.png");
+}
@@ -0,0 +1,113 @@
+use std::ops::{Deref, DerefMut};
+
+use image::{ImageBuffer, Luma, LumaA, Pixel, Rgb, Rgba};
+use num_traits::NumCast;
+
+use crate::{
+    blend_ops::{blend_impl, denormalize, type_max},
+    error::Error,
+    options::BlendOptions,
+    pixelops::pixel_normal,
+};
+
+/**
+Blends into an image through a 64 bit float scratch copy of it, quantizing back to the image's type only once, in `finish`.
+
+Each blend into an integer image rounds every subpixel it changes, so a long chain of blends compounds that rounding error, and blends smaller than one level are lost entirely. Accumulating them in float keeps the chain as accurate as a single blend. The scratch has the same channels as the image, so blends behave exactly as `BufferBlend::blend` on it.
+
+The image is not modified until `finish` is called. Dropping the accumulator without calling it discards the blends.
+
+# Examples
+
+```
+use image::{ImageBuffer, Luma};
+use image_blend::BlendAccumulator;
+use image_blend::pixelops::pixel_add;
+
+let mut img1_buffer: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::from_pixel(4, 4, Luma([100]));
+let img2_buffer: ImageBuffer<Luma<f32>, Vec<f32>> = ImageBuffer::from_pixel(4, 4, Luma([0.001]));
+
+// Each step is a quarter of a level, which blending in place would round away every time
+let mut accumulator = BlendAccumulator::new(&mut img1_buffer);
+for _ in 0..20 {
+    accumulator.blend(&img2_buffer, pixel_add, true, false).unwrap();
+}
+accumulator.finish();
+assert_eq!(img1_buffer.get_pixel(0, 0).0, [105]);
+```
+*/
+pub struct BlendAccumulator<'a, Pmut, ContainerMut>
+where
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>,
+{
+    target: &'a mut ImageBuffer<Pmut, ContainerMut>,
+    /// The subpixels of `target`, normalized to 0.0..1.0.
+    scratch: Vec<f64>,
+}
+impl<'a, Pmut, ContainerMut> BlendAccumulator<'a, Pmut, ContainerMut>
+where
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    /// Start accumulating blends into `target`, copying it into the scratch buffer.
+    #[must_use]
+    // Every subpixel type converts to f64, so this can't panic
+    #[allow(clippy::missing_panics_doc)]
+    pub fn new(target: &'a mut ImageBuffer<Pmut, ContainerMut>) -> Self {
+        let max = type_max::<Pmut>();
+        let scratch = target.iter().map(|v| <f64 as NumCast>::from(*v).unwrap() / max).collect();
+        Self { target, scratch }
+    }
+    /**
+    Blend `other` into the scratch buffer using the function `op`, where arg 0 is self and 1 is other, as `BufferBlend::blend` would blend it into the image.
+
+    # Errors
+
+    `DimensionMismatch`: the image and `other` have different dimensions
+
+    `UnsupportedType`: the image or `other` is of an unsupported color type
+
+    `UnsupportedBlend`: the image is a luma image and `other` is an rgb image
+    */
+    // The scratch is always the size of the image, so this can't panic
+    #[allow(clippy::missing_panics_doc)]
+    pub fn blend<P, Container>(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>
+    where
+        P: Pixel,
+        Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    {
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        let op = |_, a, b| op(a, b);
+        let (width, height) = self.target.dimensions();
+        let scratch = &mut self.scratch[..];
+        match Pmut::CHANNEL_COUNT {
+            1 => blend_impl(&mut ImageBuffer::<Luma<f64>, _>::from_raw(width, height, scratch).unwrap(), other, op, &options),
+            2 => blend_impl(&mut ImageBuffer::<LumaA<f64>, _>::from_raw(width, height, scratch).unwrap(), other, op, &options),
+            3 => blend_impl(&mut ImageBuffer::<Rgb<f64>, _>::from_raw(width, height, scratch).unwrap(), other, op, &options),
+            4 => blend_impl(&mut ImageBuffer::<Rgba<f64>, _>::from_raw(width, height, scratch).unwrap(), other, op, &options),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    /// Write the accumulated blends back to the image, rounding each subpixel to the nearest value of its type once.
+    ///
+    /// # Panics
+    ///
+    /// If an `op` returned NaN and the image has integer subpixels, as blending in place would.
+    pub fn finish(self) {
+        let max = type_max::<Pmut>();
+        self.target.iter_mut().zip(self.scratch).for_each(|(subpixel, value)| {
+            *subpixel = denormalize::<Pmut>(value, max);
+        });
+    }
+}
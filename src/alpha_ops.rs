@@ -1,9 +1,9 @@
 use std::{iter::zip, ops::{Deref, DerefMut}};
 
-use image::{ImageBuffer, Pixel};
-use num_traits::{Bounded, NumCast};
+use image::{imageops::{self, FilterType}, ImageBuffer, Pixel};
+use num_traits::NumCast;
 
-use crate::{blend_ops::{dims_match, type_max}, enums::ColorStructure, error::Error};
+use crate::{blend_ops::{denormalize, dims_match, type_max}, color::luminance, enums::ColorStructure, error::Error, pixelops::pixel_mult};
 
 pub trait BufferGetAlpha<P, Container>
 where
@@ -41,6 +41,25 @@ where
     fn get_alpha(
         &self
     ) -> Option<Self> where Self: std::marker::Sized;
+    /**
+    Get the alpha channel of this image as a grayscale, like `get_alpha`, but return a fully opaque (all channels at the maximum value of the input type) image if there is no alpha channel.
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferGetAlpha;
+
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img1_buffer = img1_dynamic.to_rgb8();
+    let mask = img1_buffer.get_alpha_or_opaque();
+    assert!(mask.pixels().all(|px| px.0 == [255, 255, 255]));
+    ```
+    */
+    #[must_use]
+    fn get_alpha_or_opaque(
+        &self
+    ) -> Self where Self: std::marker::Sized;
 }
 impl<P, Container> BufferGetAlpha<P, Container> for ImageBuffer<P, Container>
 where
@@ -74,6 +93,16 @@ where
         });
         Some(alpha)
     }
+    fn get_alpha_or_opaque(
+        &self,
+    ) -> Self {
+        self.get_alpha().unwrap_or_else(|| {
+            let mut opaque = self.clone();
+            let max: <P as Pixel>::Subpixel = NumCast::from(type_max::<P>()).unwrap();
+            opaque.pixels_mut().for_each(|px| px.channels_mut().fill(max));
+            opaque
+        })
+    }
 }
 pub trait BufferSetAlpha<P, Container>
 where
@@ -150,6 +179,146 @@ where
         &mut self,
         other: &ImageBuffer<P, Container>
     ) -> Result<(), Error>;
+
+    /**
+    Multiply this image's alpha channel by another image's alpha channel, keeping only the coverage the two have in common.
+
+    Handles type conversion and alpha channel placement automatically.
+
+    # Errors
+    `NoAlphaChannel`: `self` or `other` does not have an alpha channel
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferSetAlpha;
+
+    // Load an image that has an alpha channel
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img1_buffer = img1_dynamic.as_rgba8().unwrap();
+
+    // Load another image and mask it by the first image's alpha channel.
+    let mut img2_dynamic = open("test_data/2.png").unwrap();
+    let mut img2_buffer = img2_dynamic.to_rgba16();
+    img2_buffer.multiply_alpha(&img1_buffer).unwrap();
+    img2_buffer.save("tests_out/doctest_buffer_multiplyalpha_result.png").unwrap();
+    ```
+    */
+    fn multiply_alpha(
+        &mut self,
+        other: &ImageBuffer<P, Container>
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other`'s alpha channel into this image's alpha channel using the function `op`, where arg 0 is self's alpha and 1 is other's. Color is never read or written.
+
+    Unlike `blend` with only `apply_to_alpha`, the color channels of the two images do not need to be compatible, so e.g. an rgba mask can be intersected with the alpha of a luma alpha image.
+
+    # Errors
+    `NoAlphaChannel`: `self` or `other` does not have an alpha channel
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferSetAlpha;
+    use image_blend::pixelops::pixel_mult;
+
+    // Load an image that has an alpha channel
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img1_buffer = img1_dynamic.as_rgba8().unwrap();
+
+    // Load another image as luma alpha, whose color is incompatible with the first
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let mut img2_buffer = img2_dynamic.into_luma_alpha8();
+
+    // Intersect its alpha with the first image's
+    img2_buffer.blend_alpha_only(&img1_buffer, pixel_mult).unwrap();
+    img2_buffer.save("tests_out/doctest_buffer_blend_alpha_only_result.png").unwrap();
+    ```
+    */
+    fn blend_alpha_only(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+    ) -> Result<(), Error>;
+
+    /**
+    Set an image's alpha channel from another image's alpha channel, passed through the transfer curve `alpha.powf(gamma)`.
+
+    Useful when moving alpha between sources with different encodings. A `gamma` above 1.0 lowers partially transparent alpha values and below 1.0 raises them, while fully transparent and fully opaque pixels are unchanged. A `gamma` of 1.0 is the same as `transplant_alpha`.
+
+    # Errors
+    `NoAlphaChannel`: `self` or `other` does not have an alpha channel
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `InvalidArgument`: `gamma` is not a positive, finite number
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferSetAlpha;
+
+    // Load an image that has an alpha channel
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img1_buffer = img1_dynamic.as_rgba8().unwrap();
+
+    // Load another image and set its alpha channel to the first image's, with the partially transparent edges tightened.
+    let mut img2_dynamic = open("test_data/2.png").unwrap();
+    let mut img2_buffer = img2_dynamic.to_rgba16();
+    img2_buffer.transplant_alpha_curve(&img1_buffer, 2.).unwrap();
+    img2_buffer.save("tests_out/doctest_buffer_transplant_alpha_curve_result.png").unwrap();
+    ```
+    */
+    fn transplant_alpha_curve(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        gamma: f64,
+    ) -> Result<(), Error>;
+
+    /**
+    Set an image's alpha channel from another image's alpha channel, resizing `other` to the size of `self` with `filter` first.
+
+    For masks authored at a different resolution to the image they are applied to. Images of the same size are transplanted as by `transplant_alpha`, without resampling.
+
+    # Errors
+    `NoAlphaChannel`: `self` or `other` does not have an alpha channel
+
+    `DimensionMismatch`: `other` is empty, so there is nothing to resize
+
+    # Examples
+
+    ```
+    use image::imageops::FilterType;
+    use image::open;
+    use image_blend::BufferSetAlpha;
+
+    // Load an image that has an alpha channel, at half size
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img1_buffer = img1_dynamic.thumbnail(img1_dynamic.width() / 2, img1_dynamic.height() / 2).to_rgba8();
+
+    // Load another image and set its alpha channel to the first image's, scaled back up.
+    let mut img2_dynamic = open("test_data/2.png").unwrap();
+    let mut img2_buffer = img2_dynamic.to_rgba16();
+    img2_buffer.transplant_alpha_scaled(&img1_buffer, FilterType::Triangle).unwrap();
+    img2_buffer.save("tests_out/doctest_buffer_transplant_alpha_scaled_result.png").unwrap();
+    ```
+    */
+    fn transplant_alpha_scaled(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        filter: FilterType,
+    ) -> Result<(), Error>
+    where
+        P: 'static;
 }
 impl<P, Pmut, Container, ContainerMut> BufferSetAlpha<P, Container> for ImageBuffer<Pmut, ContainerMut>
 where
@@ -174,7 +343,7 @@ where
         zip(self.pixels_mut(), other.pixels()).for_each(|(px, px_luma)| {
             // Need to cast here because there is no guarantee P and Pmut are the same type
             let px_luma_64: f64 = <f64 as NumCast>::from(px_luma.channels()[0]).unwrap() / b_max;
-            let alpha: <Pmut as Pixel>::Subpixel = NumCast::from(px_luma_64 * a_max).unwrap();
+            let alpha: <Pmut as Pixel>::Subpixel = NumCast::from(px_luma_64.clamp(0., 1.) * a_max).unwrap();
             px.channels_mut()[alpha_channel] = alpha;
         });
         Ok(())
@@ -196,11 +365,72 @@ where
         zip(self.pixels_mut(), other.pixels()).for_each(|(pxa, pxb)| {
             // Need to cast here because there is no guarantee P and Pmut are the same type
             let float_b: f64 = <f64 as NumCast>::from(pxb.channels()[alpha_b]).unwrap() / b_max;
-            let alpha: <Pmut as Pixel>::Subpixel = NumCast::from(float_b * a_max).unwrap();
+            let alpha: <Pmut as Pixel>::Subpixel = NumCast::from(float_b.clamp(0., 1.) * a_max).unwrap();
             pxa.channels_mut()[alpha_a] = alpha;
         });
         Ok(())
     }
+    fn multiply_alpha(
+        &mut self,
+        other: &ImageBuffer<P, Container>
+    ) -> Result<(), Error> {
+        self.blend_alpha_only(other, pixel_mult)
+    }
+    fn blend_alpha_only(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        let structure_a: ColorStructure = self.sample_layout().try_into()?;
+        let structure_b: ColorStructure = other.sample_layout().try_into()?;
+
+        let alpha_a = structure_a.alpha_channel().ok_or(Error::NoAlphaChannel)?;
+        let alpha_b = structure_b.alpha_channel().ok_or(Error::NoAlphaChannel)?;
+
+        let a_max = type_max::<Pmut>();
+        let b_max = type_max::<P>();
+
+        zip(self.pixels_mut(), other.pixels()).for_each(|(pxa, pxb)| {
+            // Need to cast here because there is no guarantee P and Pmut are the same type
+            let float_a: f64 = <f64 as NumCast>::from(pxa.channels()[alpha_a]).unwrap() / a_max;
+            let float_b: f64 = <f64 as NumCast>::from(pxb.channels()[alpha_b]).unwrap() / b_max;
+            pxa.channels_mut()[alpha_a] = denormalize::<Pmut>(op(float_a, float_b), a_max);
+        });
+        Ok(())
+    }
+    fn transplant_alpha_curve(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        gamma: f64,
+    ) -> Result<(), Error> {
+        if !(gamma.is_finite() && gamma > 0.) {
+            return Err(Error::InvalidArgument("gamma must be positive and finite"));
+        }
+        self.blend_alpha_only(other, |_, b| b.clamp(0., 1.).powf(gamma))
+    }
+    fn transplant_alpha_scaled(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        filter: FilterType,
+    ) -> Result<(), Error>
+    where
+        P: 'static,
+    {
+        if self.dimensions() == other.dimensions() {
+            return self.transplant_alpha(other);
+        }
+        let structure_a: ColorStructure = self.sample_layout().try_into()?;
+        let structure_b: ColorStructure = other.sample_layout().try_into()?;
+        if !(structure_a.alpha() && structure_b.alpha()) {
+            return Err(Error::NoAlphaChannel);
+        }
+        if other.width() == 0 || other.height() == 0 {
+            return Err(Error::DimensionMismatch);
+        }
+        let scaled = imageops::resize(other, self.width(), self.height(), filter);
+        self.transplant_alpha(&scaled)
+    }
 }
 pub trait BufferStripAlpha<Pmut, ContainerMut> 
 where 
@@ -247,10 +477,288 @@ where
     ) -> Result<(), Error> {
         let structure: ColorStructure = self.sample_layout().try_into()?;
         let alpha_channel = structure.alpha_channel().ok_or(Error::NoAlphaChannel)?;
-        let max = <Pmut as Pixel>::Subpixel::max_value();
+        // Use type_max rather than max_value so float images get an alpha of 1.0, not f32::MAX
+        let max: <Pmut as Pixel>::Subpixel = NumCast::from(type_max::<Pmut>()).unwrap();
         self.pixels_mut().for_each(|px| {
             px.channels_mut()[alpha_channel] = max;
         });
         Ok(())
     }
 }
+pub trait BufferAdjustAlpha<Pmut, ContainerMut>
+where
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+        + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    /**
+    Clamp this image's alpha channel to the range `min..=max`, where `0.` is fully transparent and `1.` fully opaque.
+
+    Bounds are rounded inwards to the nearest value of the image's type, so no pixel ends up outside the range. If the range is too narrow to contain any value of the type, e.g. `min == max == 0.3` on an 8 bit image, every alpha is set to the value nearest to it instead.
+
+    # Errors
+    `NoAlphaChannel`: `self` does not have an alpha channel
+
+    `InvalidArgument`: `min` or `max` is not finite, or `min` is greater than `max`
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferAdjustAlpha;
+
+    // Load an image and make sure it is never more than 30% opaque
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.to_rgba16();
+    img1_buffer.clamp_alpha(0., 0.3).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_clamp_alpha_result.png").unwrap();
+    ```
+    */
+    fn clamp_alpha(
+        &mut self,
+        min: f64,
+        max: f64,
+    ) -> Result<(), Error>;
+
+    /**
+    Multiply this image's alpha channel by `factor`, e.g. `0.7` to show it at 70% of its current opacity.
+
+    Unlike setting alpha to a fixed value, this keeps the relative transparency of every pixel. Results are clamped to the range of the image's type, so a `factor` above `1.` makes the image more opaque without wrapping, and a negative `factor` makes it fully transparent.
+
+    # Errors
+    `NoAlphaChannel`: `self` does not have an alpha channel
+
+    `InvalidArgument`: `factor` is NaN
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferAdjustAlpha;
+
+    // Load an image and halve its opacity
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.to_rgba16();
+    img1_buffer.scale_alpha(0.5).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_scale_alpha_result.png").unwrap();
+    ```
+    */
+    fn scale_alpha(
+        &mut self,
+        factor: f64,
+    ) -> Result<(), Error>;
+
+    /**
+    Premultiply this image's color channels by its alpha channel in place.
+
+    Premultiplied images store `color * alpha` instead of `color`, which is what most GPU blending pipelines expect. Nothing in the image's type marks it as premultiplied, so keep track of it yourself, and note that the other operations in this crate expect straight (non-premultiplied) alpha.
+
+    # Errors
+    `NoAlphaChannel`: `self` does not have an alpha channel
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferAdjustAlpha;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.to_rgba16();
+    img1_buffer.premultiply().unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_premultiply_result.png").unwrap();
+    ```
+    */
+    fn premultiply(
+        &mut self,
+    ) -> Result<(), Error>;
+
+    /**
+    Undo `premultiply`, dividing this image's color channels by its alpha channel in place.
+
+    Use this to convert a premultiplied image (e.g. read back from a GPU) to the straight alpha the other operations in this crate expect. Fully transparent pixels have no recoverable color and are set to black.
+
+    # Errors
+    `NoAlphaChannel`: `self` does not have an alpha channel
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferAdjustAlpha;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.to_rgba16();
+    img1_buffer.premultiply().unwrap();
+    img1_buffer.unpremultiply().unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_unpremultiply_result.png").unwrap();
+    ```
+    */
+    fn unpremultiply(
+        &mut self,
+    ) -> Result<(), Error>;
+
+    /**
+    Set this image's alpha channel from the edges in its luminance, for matting line art: edges become opaque and flat areas transparent.
+
+    Edge strength is the Sobel gradient magnitude of the Rec. 709 luminance (of the color channels as stored), normalized so a hard step from black to white is `1.`. Pixels past the border repeat the edge pixels, so the border of the image is not an edge. A pixel whose edge strength reaches `threshold` becomes fully opaque, and weaker edges fade linearly to transparent. The previous alpha is ignored.
+
+    # Errors
+    `NoAlphaChannel`: `self` does not have an alpha channel
+
+    `InvalidArgument`: `threshold` is not in the range `0.0 < threshold <= 1.0`
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferAdjustAlpha;
+
+    // Keep only the outlines of an image
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.to_rgba16();
+    img1_buffer.alpha_from_edges(0.25).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_alpha_from_edges_result.png").unwrap();
+    ```
+    */
+    fn alpha_from_edges(
+        &mut self,
+        threshold: f64,
+    ) -> Result<(), Error>;
+}
+impl<Pmut, ContainerMut> BufferAdjustAlpha<Pmut, ContainerMut> for ImageBuffer<Pmut, ContainerMut>
+where
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+        + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    fn clamp_alpha(
+        &mut self,
+        min: f64,
+        max: f64,
+    ) -> Result<(), Error> {
+        let structure: ColorStructure = self.sample_layout().try_into()?;
+        let alpha_channel = structure.alpha_channel().ok_or(Error::NoAlphaChannel)?;
+        if !(min.is_finite() && max.is_finite()) {
+            return Err(Error::InvalidArgument("min and max must be finite"));
+        }
+        if min > max {
+            return Err(Error::InvalidArgument("min must not be greater than max"));
+        }
+        let type_max = type_max::<Pmut>();
+        let (mut low, mut high) = (min.clamp(0., 1.) * type_max, max.clamp(0., 1.) * type_max);
+        // Round the bounds inwards for integer types (float images have a max of 1.0)
+        if type_max - 1. != 0. {
+            (low, high) = if low.ceil() <= high.floor() {
+                (low.ceil(), high.floor())
+            } else {
+                // The range falls between two values of the type
+                let nearest = f64::midpoint(low, high).round();
+                (nearest, nearest)
+            };
+        }
+        let low_val: <Pmut as Pixel>::Subpixel = NumCast::from(low).unwrap();
+        let high_val: <Pmut as Pixel>::Subpixel = NumCast::from(high).unwrap();
+        self.pixels_mut().for_each(|px| {
+            let alpha = &mut px.channels_mut()[alpha_channel];
+            let alpha_f64: f64 = <f64 as NumCast>::from(*alpha).unwrap();
+            if alpha_f64 < low {
+                *alpha = low_val;
+            } else if alpha_f64 > high {
+                *alpha = high_val;
+            }
+        });
+        Ok(())
+    }
+    fn scale_alpha(
+        &mut self,
+        factor: f64,
+    ) -> Result<(), Error> {
+        let structure: ColorStructure = self.sample_layout().try_into()?;
+        let alpha_channel = structure.alpha_channel().ok_or(Error::NoAlphaChannel)?;
+        if factor.is_nan() {
+            return Err(Error::InvalidArgument("factor must not be NaN"));
+        }
+        let max = type_max::<Pmut>();
+        self.pixels_mut().for_each(|px| {
+            let alpha = &mut px.channels_mut()[alpha_channel];
+            let alpha_f64: f64 = <f64 as NumCast>::from(*alpha).unwrap() / max;
+            *alpha = denormalize::<Pmut>(alpha_f64 * factor, max);
+        });
+        Ok(())
+    }
+    fn premultiply(
+        &mut self,
+    ) -> Result<(), Error> {
+        let structure: ColorStructure = self.sample_layout().try_into()?;
+        let alpha_channel = structure.alpha_channel().ok_or(Error::NoAlphaChannel)?;
+        let max = type_max::<Pmut>();
+        self.pixels_mut().for_each(|px| {
+            let channels = px.channels_mut();
+            let alpha: f64 = <f64 as NumCast>::from(channels[alpha_channel]).unwrap() / max;
+            for ch in (0..structure.channels()).filter(|ch| *ch != alpha_channel) {
+                let v: f64 = <f64 as NumCast>::from(channels[ch]).unwrap() / max;
+                channels[ch] = denormalize::<Pmut>(v * alpha, max);
+            }
+        });
+        Ok(())
+    }
+    fn unpremultiply(
+        &mut self,
+    ) -> Result<(), Error> {
+        let structure: ColorStructure = self.sample_layout().try_into()?;
+        let alpha_channel = structure.alpha_channel().ok_or(Error::NoAlphaChannel)?;
+        let max = type_max::<Pmut>();
+        self.pixels_mut().for_each(|px| {
+            let channels = px.channels_mut();
+            let alpha: f64 = <f64 as NumCast>::from(channels[alpha_channel]).unwrap() / max;
+            for ch in (0..structure.channels()).filter(|ch| *ch != alpha_channel) {
+                let v: f64 = <f64 as NumCast>::from(channels[ch]).unwrap() / max;
+                let straight = if alpha == 0. { 0. } else { v / alpha };
+                channels[ch] = denormalize::<Pmut>(straight, max);
+            }
+        });
+        Ok(())
+    }
+    fn alpha_from_edges(
+        &mut self,
+        threshold: f64,
+    ) -> Result<(), Error> {
+        let structure: ColorStructure = self.sample_layout().try_into()?;
+        let alpha_channel = structure.alpha_channel().ok_or(Error::NoAlphaChannel)?;
+        if !(threshold > 0. && threshold <= 1.) {
+            return Err(Error::InvalidArgument("threshold must be in the range 0.0 < threshold <= 1.0"));
+        }
+        let max = type_max::<Pmut>();
+        let (width, height) = (self.width() as usize, self.height() as usize);
+        let lumas: Vec<f64> = self.pixels().map(|px| {
+            let channels = px.channels();
+            let value = |ch: usize| <f64 as NumCast>::from(channels[ch]).unwrap() / max;
+            if structure.rgb() { luminance([value(0), value(1), value(2)]) } else { value(0) }
+        }).collect();
+        // Clamp to the image so the border repeats
+        let luma_at = |x: usize, y: usize, dx: isize, dy: isize| {
+            let x = x.saturating_add_signed(dx).min(width - 1);
+            let y = y.saturating_add_signed(dy).min(height - 1);
+            lumas[y * width + x]
+        };
+        for (i, px) in self.pixels_mut().enumerate() {
+            let (x, y) = (i % width, i / width);
+            let l = |dx, dy| luma_at(x, y, dx, dy);
+            let gx = (l(1, -1) + 2. * l(1, 0) + l(1, 1)) - (l(-1, -1) + 2. * l(-1, 0) + l(-1, 1));
+            let gy = (l(-1, 1) + 2. * l(0, 1) + l(1, 1)) - (l(-1, -1) + 2. * l(0, -1) + l(1, -1));
+            // A step from 0 to 1 gives a gradient of 4 along one axis
+            let strength = gx.hypot(gy) / 4.;
+            px.channels_mut()[alpha_channel] = denormalize::<Pmut>(strength / threshold, max);
+        }
+        Ok(())
+    }
+}
@@ -1,15 +1,21 @@
 use std::{
     iter::{zip, Zip},
+    mem::size_of,
     ops::{Deref, DerefMut},
     vec,
 };
 
-use image::{GenericImageView, ImageBuffer, Pixel};
-use num_traits::{Bounded, NumCast};
+use image::{imageops, GenericImage, GenericImageView, ImageBuffer, Luma, LumaA, Pixel, Primitive};
+use num_traits::NumCast;
 
 use crate::{
-    enums::{ColorString, ColorStructure},
+    color::{blend_components, blend_normals, clip_gamut, linear_to_srgb, luminance, srgb_to_linear, LumaCoefficients},
+    compat::CompatibilityReport,
+    enums::{AlphaCoverage, Anchor, Arithmetic, BlendMode, Channel, ColorModel, ColorString, ColorStructure, ComponentBlend, DimensionPolicy, GamutClip, IntOp, NormalBlend, Overlap, Rect, Rounding, TonalRange},
     error::Error,
+    options::BlendOptions,
+    pixelops::pixel_normal,
+    strategy::BlendStrategy,
 };
 
 pub(crate) fn dims_match<T: GenericImageView, U: GenericImageView>(a: &mut T, b: &U) -> Result<(), Error> {
@@ -31,8 +37,12 @@ where
 
     You may blend a luma image into an rgb image (in which case the luma image will be treated as a grayscale rgb image), but you cannot blend an rgba image into a luma image.
 
+    This holds for any subpixel type, so e.g. a `GrayImage` or `GrayAlphaImage` blends into an `RgbaImage` or `Rgb32FImage`, while an `RgbImage` into a `GrayImage` returns `UnsupportedBlend`.
+
     If `other` has an alpha channel, the output is weighted by this alpha channel (so if alpha for `other` for this pixel is 0.5, the blend effect will be 0.5 as strong)
 
+    This simple weighting is only exact when `self` is opaque. When both images have an alpha channel, color is instead composited source-over in premultiplied space, so blending onto translucent pixels of `self` gives the same color as flattening the layers would. Blend the alpha channel with `pixel_screen` to get the matching source-over alpha.
+
     # Arguments
 
     Use `apply_to_color` and `apply_to_alpha` to control which channels are affected.
@@ -41,6 +51,8 @@ where
 
     `op` is a function that takes two f64 values and returns a f64 value. (e.g. `|self, other| self + other`)
 
+    `op` is generic, so passing a function such as `pixel_mult` or a closure directly lets the compiler inline it into the pixel loop. A `fn(f64, f64) -> f64` pointer also works, but costs an indirect call per subpixel.
+
     Standard blend modes such as those found in photoshop are provided as functions (e.g. `pixel_add`, `pixel_mult`, etc.).
 
     The values are normalized to the range 0.0..1.0 before blending, and then scaled back to the input type's range.
@@ -111,111 +123,2266 @@ where
     fn blend(
         &mut self,
         other: &ImageBuffer<P, Container>,
-        op: fn(f64, f64) -> f64,
+        op: impl Fn(f64, f64) -> f64,
         apply_to_color: bool,
         apply_to_alpha: bool,
     ) -> Result<(), Error>;
-}
-impl<P, Pmut, Container, ContainerMut> BufferBlend<P, Container> for ImageBuffer<Pmut, ContainerMut>
-where
-    Pmut: Pixel,
-    P: Pixel,
-    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
-    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
-        + DerefMut<Target = [Pmut::Subpixel]>
-        + AsMut<[<Pmut as Pixel>::Subpixel]>,
-{
-    fn blend(
+
+    /**
+    Blend `other` into `self` as described by `options`.
+
+    Behaves like `blend`, but exposes the less common settings (such as `min_delta`) through `BlendOptions`.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions and `options.dimension_policy` is `Strict`
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    `UnsupportedBlend`: `options.fourth_channel_is_alpha` is false and only one of `self` and `other` has 4 channels
+
+    `UnsupportedType`: `options.arithmetic` is `FixedPoint` and `self` and `other` are not integer images with the same bit depth
+
+    `InvalidArgument`: `options.arithmetic` is `FixedPoint` and an option it can't honour is set (see `BlendOptions::arithmetic`)
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BlendOptions, BufferBlend};
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    let options = BlendOptions {
+        min_delta: 0.01,
+        ..BlendOptions::new(pixel_mult)
+    };
+    img1_buffer.blend_with_options(&img2_buffer, &options).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_with_options_result.png").unwrap();
+    ```
+    */
+    fn blend_with_options(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        options: &BlendOptions,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using the function `op`, with the color channels converted from sRGB to linear light before blending and back afterwards.
+
+    Blending in linear light avoids the darkening seen when mixing saturated colors in gamma encoded space. The alpha weighting is also done in linear light. The alpha channel itself is blended exactly as in `blend`.
+
+    When `self` is an rgb image, `gamut_clip` controls how an out of range result of `op` is brought back into 0.0..1.0 before the alpha weighting. Luma images are always clamped.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BufferBlend, GamutClip};
+    use image_blend::pixelops::pixel_add;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    img1_buffer.blend_linear(&img2_buffer, pixel_add, GamutClip::DesatToGray, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_linear_result.png").unwrap();
+    ```
+    */
+    fn blend_linear(
         &mut self,
         other: &ImageBuffer<P, Container>,
-        op: fn(f64, f64) -> f64,
+        op: impl Fn(f64, f64) -> f64,
+        gamut_clip: GamutClip,
         apply_to_color: bool,
         apply_to_alpha: bool,
-    ) -> Result<(), Error> {
-        dims_match(self, other)?;
-        let structure_a: ColorStructure = self.sample_layout().try_into()?;
-        let structure_b: ColorStructure = other.sample_layout().try_into()?;
+    ) -> Result<(), Error>;
 
-        let (color_channels, alpha_channels) = get_channels(&structure_a, &structure_b)?;
+    /**
+    Blend `other` into `self` using the fallible function `op`, where arg 0 is self and 1 is other.
 
-        let a_max = type_max::<Pmut>();
-        let b_max = type_max::<P>();
+    Behaves like `blend`, but stops at the first pixel for which `op` returns an error and returns that error.
 
-        if apply_to_color {
-            zip(self.pixels_mut(), other.pixels()).for_each(|(px_a, px_b)| {
-                let channel_a = px_a.channels_mut();
-                let channel_b = px_b.channels();
-                let alpha_weight = match structure_b.alpha_channel() {
-                    Some(alpha_channel) => {
-                        <f64 as NumCast>::from(channel_b[alpha_channel]).unwrap() / b_max
-                    }
-                    None => 1.,
-                };
-                if alpha_weight == 0. {
-                    return;
-                };
-                color_channels.clone().for_each(|(ch_a, ch_b)| {
-                    let a_f64: f64 = <f64 as NumCast>::from(channel_a[ch_a]).unwrap() / a_max;
-                    let b_f64: f64 = <f64 as NumCast>::from(channel_b[ch_b]).unwrap() / b_max;
-                    let new_64_unweighted: f64 = NumCast::from(op(a_f64, b_f64)).unwrap();
-                    let new_64 = new_64_unweighted * alpha_weight + a_f64 * (1. - alpha_weight);
-                    let new_val = NumCast::from(new_64.clamp(0., 1.0) * a_max).unwrap();
-                    channel_a[ch_a] = new_val;
-                });
-            });
-        };
-        if apply_to_alpha {
-            if let Some((alpha_a, alpha_b)) = alpha_channels {
-                zip(self.pixels_mut(), other.pixels()).for_each(|(px_a, px_b)| {
-                    let channel_a = px_a.channels_mut();
-                    let channel_b = px_b.channels();
-
-                    let a_f64: f64 = <f64 as NumCast>::from(channel_a[alpha_a]).unwrap() / a_max;
-                    let b_f64: f64 = <f64 as NumCast>::from(channel_b[alpha_b]).unwrap() / b_max;
-                    let new_64: f64 = NumCast::from(op(a_f64, b_f64)).unwrap();
-                    let new_val = NumCast::from(new_64.clamp(0., 1.0) * a_max).unwrap();
-                    channel_a[alpha_a] = new_val;
-                });
-            }
-        }
+    Pixels are written as they are blended, so pixels processed before the error (including the color pass if the error happened in the alpha pass) are left modified. Blend into a copy if you need the original on failure.
 
-        Ok(())
-    }
-}
+    # Errors
 
-pub(crate) fn type_max<P>() -> f64 where P: Pixel {
-    let max: f64 = NumCast::from(<P as Pixel>::Subpixel::max_value()).unwrap();
-    let f32_max: f64 = NumCast::from(<f32 as Bounded>::max_value()).unwrap();
-    // Hack to get around f32 images having a max value of 1.0 not f32::MAX
-    if max - f32_max == 0. {
-        return 1.
-    }
-    max
-}
+    `DimensionMismatch`: `self` and `other` have different dimensions
 
-type ChannelIter = (
-    Zip<vec::IntoIter<usize>, vec::IntoIter<usize>>,
-    Option<(usize, usize)>,
-);
-fn get_channels(
-    structure_a: &ColorStructure,
-    structure_b: &ColorStructure,
-) -> Result<ChannelIter, Error> {
-    let color_channels = match (structure_a.rgb(), structure_b.rgb()) {
-        (true, true) => zip(vec![0usize, 1, 2], vec![0usize, 1, 2]),
-        (true, false) => zip(vec![0, 1, 2], vec![0, 0, 0]),
-        (false, false) => zip(vec![0], vec![0]),
-        (false, true) => Err(Error::UnsupportedBlend(
-            structure_a.color_str(),
-            structure_b.color_str(),
-        ))?,
-    };
-    let alpha_channels = match (structure_a.alpha(), structure_b.alpha()) {
-        (true, true) => Some((
-            structure_a.alpha_channel().unwrap(),
-            structure_b.alpha_channel().unwrap(),
-        )),
-        _ => None,
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    Any error returned by `op`
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BufferBlend, Error};
+
+    let checked_div = |a: f64, b: f64| {
+        if b == 0. {
+            return Err(Error::BlendOp("division by zero".to_string()));
+        }
+        Ok(a / b)
     };
-    Ok((color_channels, alpha_channels))
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    let res = img1_buffer.try_blend(&img2_buffer, checked_div, true, false);
+    ```
+    */
+    fn try_blend(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> Result<f64, Error>,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend a single channel of `other` into the channel of `self` with the role `channel`, leaving every other channel untouched.
+
+    Color roles are matched to the same role in `other` (or its luma channel if it is a grayscale image) and weighted by `other`'s alpha like `blend`. `Channel::Alpha` blends the two alpha channels without weighting.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `MissingChannel`: `self` does not have a channel with this role (e.g. `Channel::Red` on a luma image, or `Channel::Luma` on an rgb image)
+
+    `NoAlphaChannel`: `channel` is `Channel::Alpha` and `other` does not have an alpha channel
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{Channel, BufferBlend};
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    img1_buffer.blend_channel_role(&img2_buffer, pixel_mult, Channel::Green).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_channel_role_result.png").unwrap();
+    ```
+    */
+    fn blend_channel_role(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        channel: Channel,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using a blend strategy chosen at runtime, where arg 0 of `strategy.apply` is self and 1 is other.
+
+    Behaves exactly like `blend`, but takes the operation as a trait object so modes can be stored and selected dynamically (e.g. from a `Vec<Box<dyn BlendStrategy>>`).
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BlendMode, BlendStrategy, BufferBlend};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    let strategy: Box<dyn BlendStrategy> = Box::new(BlendMode::Screen);
+    img1_buffer.blend_dyn(&img2_buffer, strategy.as_ref(), true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_dyn_result.png").unwrap();
+    ```
+    */
+    fn blend_dyn(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        strategy: &dyn BlendStrategy,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using one of the built in `BlendMode`s.
+
+    Equivalent to calling `blend` with `mode.op()`, but `BlendMode::Normal` between two images with the same subpixel type and channel layout takes a fast path: fully opaque pixels of `other` are copied directly instead of being normalized to f64 and back. The output is identical to the float path.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BlendMode, BufferBlend};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba8();
+
+    img1_buffer.blend_mode(&img2_buffer, BlendMode::Normal, true, true).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_mode_result.png").unwrap();
+    ```
+    */
+    fn blend_mode(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        mode: BlendMode,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` with an operation that also receives the index of the channel being written, where arg 0 of `op` is the channel index in `self`, 1 is self and 2 is other.
+
+    Behaves exactly like `blend` otherwise. This allows treating channels differently (e.g. only boosting green) without writing a whole pixel operation. Channel indices are those of `self`'s layout, so for an rgba image 0, 1 and 2 are red, green and blue and 3 is alpha, and for a luma alpha image 0 is luma and 1 is alpha.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferBlend;
+    use image_blend::pixelops::pixel_normal;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    // Screen the green channel, and keep the others as a normal blend
+    let green_screen = |ch: usize, a: f64, b: f64| if ch == 1 { 1. - (1. - a) * (1. - b) } else { pixel_normal(a, b) };
+    img1_buffer.blend_indexed(&img2_buffer, green_screen, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_indexed_result.png").unwrap();
+    ```
+    */
+    fn blend_indexed(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(usize, f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` as if `other` were padded with transparent pixels to the size of `self`, where arg 0 of `op` is self and 1 is other.
+
+    `anchor` decides where `other` is placed within `self` (e.g. `Anchor::Center` to center an overlay). Pixels of `self` outside of `other` are left untouched, including their alpha, and parts of `other` that fall outside of `self` are ignored. Otherwise behaves exactly like `blend`.
+
+    # Errors
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{Anchor, BufferBlend};
+    use image_blend::pixelops::pixel_screen;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let small_buffer = img2_dynamic.thumbnail(64, 64).to_rgba16();
+
+    // Screen a smaller copy of the second image over the middle of the first
+    img1_buffer.blend_padded(&small_buffer, pixel_screen, Anchor::Center, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_padded_result.png").unwrap();
+    ```
+    */
+    fn blend_padded(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        anchor: Anchor,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` by replacing one component of each pixel's color with `other`'s, after splitting both colors into hue, saturation and lightness or value.
+
+    Unlike `blend`, this works on whole pixels rather than channel by channel. `model` picks HSL or HSV: a `ComponentBlend::Value` blend in HSV keeps `self`'s hue and saturation with `other`'s value (its largest channel), which keeps saturated colors saturated where an HSL lightness blend would wash them out towards white. A luma `other` is treated as gray. The result is weighted by `other`'s alpha like `blend`, and the alpha channel is left untouched.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `MissingChannel`: `self` is a luma image, which has no hue or saturation to blend
+
+    `UnsupportedType`: `self` or `other` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BufferBlend, ColorModel, ComponentBlend};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    // Take the value of the second image, keeping the hue and saturation of the first
+    img1_buffer.blend_components(&img2_buffer, ComponentBlend::Value, ColorModel::Hsv).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_components_result.png").unwrap();
+    ```
+    */
+    fn blend_components(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        component: ComponentBlend,
+        model: ColorModel,
+    ) -> Result<(), Error>;
+
+    /**
+    Dissolve `other` into `self`: each pixel either keeps `self`'s value or takes `other`'s, chosen at random with a probability of `other`'s alpha.
+
+    Where `other` has an alpha of 0.3, roughly 30% of pixels take `other`'s color, giving a dithered transparency instead of a smooth mix. Pixels taken from `other` become fully opaque if `self` has an alpha channel. An `other` without an alpha channel replaces every pixel.
+
+    The choice for each pixel depends only on `seed` and the pixel's position, so the same seed always gives the same output.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferBlend;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    img1_buffer.dissolve(&img2_buffer, 42).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_dissolve_result.png").unwrap();
+    ```
+    */
+    fn dissolve(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        seed: u64,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using the function `op` within `tile` only, where arg 0 is self and 1 is other.
+
+    `other` is the same size as `self` and in the same coordinate space, so only the pixels of both images within `tile` are read, and only the pixels of `self` within `tile` are written. This suits tiled processing, where each tile can be blended separately without cropping `other`. Otherwise behaves exactly like `blend`.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `InvalidArgument`: `tile` does not lie entirely within `self`
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BufferBlend, Rect};
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    let tile = Rect { x: 0, y: 0, width: 64, height: 64 };
+    img1_buffer.blend_tile(&img2_buffer, pixel_mult, tile, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_tile_result.png").unwrap();
+    ```
+    */
+    fn blend_tile(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        tile: Rect,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using the function `op`, but only within `selection`, where arg 0 is self and 1 is other.
+
+    `selection` is a mask the same size as `self`, where a pixel is selected if its first channel is above 0.5 (e.g. white on black). This allows arbitrary, non-rectangular selections such as circles or paths rasterized into an image. Unselected pixels of `self` are left untouched, and selected pixels are blended exactly as by `blend`.
+
+    # Errors
+
+    `DimensionMismatch`: `self`, `other` and `selection` do not all have the same dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    `UnsupportedType`: `selection` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::{open, GrayImage, Luma};
+    use image_blend::BufferBlend;
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    // Select the left half of the image
+    let (width, height) = (img1_buffer.width(), img1_buffer.height());
+    let selection = GrayImage::from_fn(width, height, |x, _| if x < width / 2 { Luma([255]) } else { Luma([0]) });
+    img1_buffer.blend_selection(&img2_buffer, pixel_mult, &selection, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_selection_result.png").unwrap();
+    ```
+    */
+    fn blend_selection<S, SContainer>(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        selection: &ImageBuffer<S, SContainer>,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>
+    where
+        S: Pixel,
+        SContainer: Deref<Target = [S::Subpixel]>;
+
+    /**
+    Blend `other` into `self` using the function `op`, with the top left corner of `other` placed at (`x`, `y`) in `self`, and only iterating over the part of `other` that is not fully transparent. Arg 0 of `op` is self and 1 is other.
+
+    Meant for stamping sprites: the bounding box of `other`'s non-transparent pixels is found first, and only that rectangle is blended, so large transparent borders cost nothing beyond the scan. Parts of `other` outside of `self` (including at negative positions) are ignored. Since fully transparent pixels never change the color of `self`, the result is the same as blending all of `other` at that position, unless `apply_to_alpha` is set.
+
+    # Errors
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    `UnsupportedType`: `other` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferBlend;
+    use image_blend::pixelops::pixel_normal;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    img1_buffer.blend_trimmed(&img2_buffer, pixel_normal, -20, 10, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_trimmed_result.png").unwrap();
+    ```
+    */
+    fn blend_trimmed(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        x: i64,
+        y: i64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using `op_opaque` where `other` is mostly opaque and `op_transparent` where it is mostly transparent. Arg 0 of each op is self and 1 is other.
+
+    A pixel uses `op_opaque` if the alpha of `other` (in the range 0.0..1.0) is at least `alpha_cut`, and `op_transparent` otherwise. Each pixel is then blended exactly as by `blend` with the chosen op, including weighting by `other`'s alpha. This allows knockout style effects, e.g. multiplying under the solid body of a layer while screening its soft edges. If `other` has no alpha channel, every pixel uses `op_opaque`.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferBlend;
+    use image_blend::pixelops::{pixel_mult, pixel_screen};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    img1_buffer.blend_threshold_op(&img2_buffer, pixel_mult, pixel_screen, 0.5, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_threshold_op_result.png").unwrap();
+    ```
+    */
+    fn blend_threshold_op(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op_opaque: impl Fn(f64, f64) -> f64,
+        op_transparent: impl Fn(f64, f64) -> f64,
+        alpha_cut: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using the function `op`, where arg 0 is self and 1 is other, calling `progress` after each row with the fraction of the image done so far.
+
+    Otherwise the same as `blend`. Meant for keeping a progress bar up to date while blending very large images: `progress` receives values increasing up to exactly 1.0, which it is always called with last (even for an empty image). Blending row by row costs a little more than `blend`, so prefer `blend` when progress is not needed.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferBlend;
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    img1_buffer.blend_with_progress(&img2_buffer, pixel_mult, |done| println!("{:.0}%", done * 100.), true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_with_progress_result.png").unwrap();
+    ```
+    */
+    fn blend_with_progress(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        progress: impl FnMut(f64),
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` with the exact integer operation `op`, working directly on the stored subpixel values without converting them to floats.
+
+    Meant for tools such as pixel art editors that need results to match integer arithmetic bit for bit, e.g. `IntOp::Add` of 200 and 100 on 8 bit images is exactly 255, the same as `u8::saturating_add`. Channels are matched up as by `blend`, but `other`'s alpha channel does not weight the result: each selected channel is simply `op` of the two values.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedType`: `self` and `other` are not integer images with the same bit depth
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BufferBlend, IntOp};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba8();
+
+    img1_buffer.blend_saturating_int(&img2_buffer, IntOp::Add, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_saturating_int_result.png").unwrap();
+    ```
+    */
+    fn blend_saturating_int(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: IntOp,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` exactly as `blend` does, then count how many pixels of `self` ended up fully transparent or fully opaque.
+
+    Meant for validating composites, e.g. catching an alpha blend that accidentally knocked out the whole image. Pixels that were already fully transparent or opaque before the blend are counted too.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferBlend;
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    let coverage = img1_buffer.blend_alpha_coverage(&img2_buffer, pixel_mult, true, true).unwrap();
+    println!("{} pixels are now fully transparent", coverage.transparent);
+    ```
+    */
+    fn blend_alpha_coverage(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<AlphaCoverage, Error>;
+
+    /**
+    Combine the tangent space normal map `other` into this normal map, e.g. to add fine surface detail to a base material.
+
+    Per channel blending can't do this correctly: each pixel is decoded from rgb to a normal vector, the two normals are combined with `method`, and the result is normalized and encoded back to rgb. Where `other` is flat (`(0.5, 0.5, 1.0)`) this normal map is kept as it is. The result is weighted by `other`'s alpha like `blend`, and the alpha channel is left untouched.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `MissingChannel`: `self` or `other` is a luma image, which can't hold a normal
+
+    `UnsupportedType`: `self` or `other` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::ColorType;
+    use image_blend::{gradient, solid_color, Direction, BufferBlend, NormalBlend};
+
+    // A flat surface, and a detail map that tilts from left to right
+    let mut img1_dynamic = solid_color(64, 64, ColorType::Rgb8, &[0.5, 0.5, 1.]).unwrap();
+    let img2_dynamic = gradient(64, 64, Direction::Horizontal, ColorType::Rgb8, &[0.2, 0.5, 0.9], &[0.8, 0.5, 0.9]).unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgb8().unwrap();
+    let img2_buffer = img2_dynamic.to_rgb16();
+    img1_buffer.blend_normal_maps(&img2_buffer, NormalBlend::Reoriented).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_normal_maps_result.png").unwrap();
+    ```
+    */
+    fn blend_normal_maps(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        method: NormalBlend,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using the function `op`, with the opacity of each pixel read from `opacity_map`, where arg 0 is self and 1 is other.
+
+    The first channel of `opacity_map` is normalized to 0.0..1.0 whatever its type, so an existing grayscale asset can be reused at any bit depth, and a 16 bit or float map gives a smoother falloff than an 8 bit one. The opacity of each pixel scales the color blend like `BlendOptions::opacity` does, on top of `other`'s alpha: black leaves `self` untouched and white blends exactly as by `blend`. Like `BlendOptions::opacity`, it does not affect the alpha channel.
+
+    # Errors
+
+    `DimensionMismatch`: `self`, `other` and `opacity_map` do not all have the same dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    `UnsupportedType`: `opacity_map` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::{open, ColorType};
+    use image_blend::{gradient, Direction, BufferBlend};
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    // Fade the blend in from left to right, using a 16 bit map
+    let (width, height) = (img1_dynamic.width(), img1_dynamic.height());
+    let opacity_map = gradient(width, height, Direction::Horizontal, ColorType::L16, &[0.], &[1.]).unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+    img1_buffer.blend_opacity_map(&img2_buffer, pixel_mult, &opacity_map.to_luma16(), true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_opacity_map_result.png").unwrap();
+    ```
+    */
+    fn blend_opacity_map<S, SContainer>(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        opacity_map: &ImageBuffer<S, SContainer>,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>
+    where
+        S: Pixel,
+        SContainer: Deref<Target = [S::Subpixel]>;
+
+    /**
+    Blend `other` into `self` using the function `op`, with subpixels normalized by `a_max` for `self` and `b_max` for `other` instead of by the maximum of their types, where arg 0 is self and 1 is other.
+
+    For integer data that does not use the full range of its type, such as 10 or 12 bit samples stored in `u16` buffers (common in medical and scientific imaging). Pass 1023 or 4095 so those samples map to 0.0..1.0 as `op` expects, and the result is written back in the same range. Subpixels above their maximum are clamped before blending, as by `BlendOptions::clamp_inputs`.
+
+    # Errors
+
+    `InvalidArgument`: `a_max` or `b_max` is not a positive, finite number
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::{ImageBuffer, Luma};
+    use image_blend::BufferBlend;
+    use image_blend::pixelops::pixel_mult;
+
+    // 12 bit samples in u16 buffers
+    let mut img1_buffer: ImageBuffer<Luma<u16>, Vec<u16>> = ImageBuffer::from_pixel(4, 4, Luma([4095]));
+    let img2_buffer: ImageBuffer<Luma<u16>, Vec<u16>> = ImageBuffer::from_pixel(4, 4, Luma([2048]));
+    img1_buffer.blend_with_max(&img2_buffer, pixel_mult, 4095., 4095., true, false).unwrap();
+    assert_eq!(img1_buffer.get_pixel(0, 0).0, [2048]);
+    ```
+    */
+    fn blend_with_max(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        a_max: f64,
+        b_max: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using the function `op`, only in the shadows, midtones or highlights of `self`, where arg 0 is self and 1 is other.
+
+    The opacity of each pixel is a smooth bell curve of `self`'s luminance (Rec. 709, of the color channels as stored) centered on `range`, as described by `TonalRange`. Pixels at the center of the range are blended exactly as by `blend`, and the blend fades out smoothly away from it, so there are no hard edges between ranges. Like `blend_opacity_map`, the opacity scales the color blend on top of `other`'s alpha and does not affect the alpha channel.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BufferBlend, TonalRange};
+    use image_blend::pixelops::pixel_screen;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    // Lift the shadows, leaving the highlights alone
+    img1_buffer.blend_tonal(&img2_buffer, pixel_screen, TonalRange::Shadows, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_tonal_result.png").unwrap();
+    ```
+    */
+    fn blend_tonal(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        range: TonalRange,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using the function `op` as if `other` were the layer below `self`, where arg 0 is other (the base) and 1 is self (the top).
+
+    The result is what `blend` gives when blending `self` into a copy of `other`, but it is stored in `self` without cloning either image, and `other` may have fewer channels than `self` as with `blend`. This is the natural way to insert a background layer: with `pixel_normal`, opaque pixels of `self` stay on top, transparent ones show `other`, and translucent ones are composited over it. `self`'s alpha weights the color as `other`'s does in `blend`.
+
+    # Arguments
+
+    With `apply_to_color` false the color is left untouched. With `apply_to_alpha` the alpha channel is blended as `op(other, self)`, so use `pixel_screen` for the matching source-over alpha. If `self` or `other` does not have an alpha channel, `apply_to_alpha` has no effect.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferBlend;
+    use image_blend::pixelops::{pixel_normal, pixel_screen};
+
+    // A layer with transparent areas
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    // Put a background underneath it
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+    img1_buffer.blend_under(&img2_buffer, pixel_normal, true, false).unwrap();
+    img1_buffer.blend_under(&img2_buffer, pixel_screen, false, true).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_under_result.png").unwrap();
+    ```
+    */
+    fn blend_under(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Replace the pixels of `self` whose color is within `tolerance` of `target` with the corresponding pixels of `other`.
+
+    `target` is a normalized rgb color (0.0..1.0), and a pixel matches if the euclidean distance from its color channels to `target` is at most `tolerance`, so a `tolerance` of 0.0 only matches `target` exactly. Luma pixels are compared as the gray with all three channels equal to their value. The alpha of `self` is not compared.
+
+    Matching pixels take `other`'s color, and its alpha if both images have an alpha channel, as is: `other`'s alpha does not weight the copy. Other pixels are left untouched. This is a keying primitive, e.g. to swap a solid background for another image.
+
+    # Errors
+
+    `InvalidArgument`: `tolerance` is negative or NaN
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferBlend;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img2_buffer = img2_dynamic.to_rgba16();
+
+    // Replace the white areas, and those close to white
+    img1_buffer.replace_color(&img2_buffer, [1., 1., 1.], 0.1).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_replace_color_result.png").unwrap();
+    ```
+    */
+    fn replace_color(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        target: [f64; 3],
+        tolerance: f64,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend `other` into `self` using the function `op`, skipping the pixels of `other` whose color is `key`, where arg 0 is self and 1 is other.
+
+    For legacy sprites that mark transparency with a magic color, such as pure magenta, instead of an alpha channel. `key` is a normalized rgb color (0.0..1.0), and a pixel of `other` is keyed out if each of its color channels is within half an 8 bit level of `key`, so the key survives conversion between bit depths. Luma pixels are compared as the gray with all three channels equal to their value. Keyed pixels leave `self` untouched, including its alpha, and the rest are blended exactly as by `blend`.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::{open, Rgb, RgbImage};
+    use image_blend::BufferBlend;
+    use image_blend::pixelops::pixel_normal;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    // A sprite of a white square on a magenta background
+    let (width, height) = img1_buffer.dimensions();
+    let sprite = RgbImage::from_fn(width, height, |x, y| {
+        if (width / 4..width * 3 / 4).contains(&x) && (height / 4..height * 3 / 4).contains(&y) { Rgb([255, 255, 255]) } else { Rgb([255, 0, 255]) }
+    });
+    img1_buffer.blend_color_keyed(&sprite, [1., 0., 1.], pixel_normal, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_color_keyed_result.png").unwrap();
+    ```
+    */
+    fn blend_color_keyed(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        key: [f64; 3],
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Check whether `other` can be blended into `self`, without blending.
+
+    Checks the dimensions and color types in the same order as `blend`, so this returns the error `blend` would. Use `compatibility_report` for every finding at once, including whether the alpha channel can be blended.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedType`: `self` or `other` is of an unsupported color type
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::{GrayImage, RgbImage};
+    use image_blend::{BufferBlend, Error};
+
+    let rgb = RgbImage::new(4, 4);
+    assert!(rgb.compatibility(&GrayImage::new(4, 4)).is_ok());
+    assert!(matches!(rgb.compatibility(&GrayImage::new(2, 2)), Err(Error::DimensionMismatch)));
+    ```
+    */
+    fn compatibility(
+        &self,
+        other: &ImageBuffer<P, Container>,
+    ) -> Result<(), Error>;
+
+    /// Report on whether `other` can be blended into `self` and why not, without blending. See `CompatibilityReport`.
+    fn compatibility_report(
+        &self,
+        other: &ImageBuffer<P, Container>,
+    ) -> CompatibilityReport;
+}
+impl<P, Pmut, Container, ContainerMut> BufferBlend<P, Container> for ImageBuffer<Pmut, ContainerMut>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+        + DerefMut<Target = [Pmut::Subpixel]>
+        + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    fn blend(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        // `op` is passed straight to the pixel loop rather than through `BlendOptions::op`, so it can be inlined
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        blend_impl(self, other, |_, a, b| op(a, b), &options)
+    }
+    fn blend_with_options(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        options: &BlendOptions,
+    ) -> Result<(), Error> {
+        if self.dimensions() == other.dimensions() {
+            return blend_options_impl(self, other, options);
+        }
+        match options.dimension_policy {
+            DimensionPolicy::Strict => Err(Error::DimensionMismatch),
+            DimensionPolicy::Crop => {
+                let (width, height) = (self.width().min(other.width()), self.height().min(other.height()));
+                blend_overlap(self, other, ((0, 0), (0, 0), (width, height)), |region, other_region| blend_options_impl(region, other_region, options))
+            }
+            DimensionPolicy::Pad => {
+                let Some(overlap) = Anchor::Center.overlap(self.dimensions(), other.dimensions()) else {
+                    return Ok(());
+                };
+                blend_overlap(self, other, overlap, |region, other_region| blend_options_impl(region, other_region, options))
+            }
+            DimensionPolicy::Scale => {
+                let scaled = scale_bilinear(other, self.dimensions()).ok_or(Error::DimensionMismatch)?;
+                blend_options_impl(self, &scaled, options)
+            }
+        }
+    }
+    fn blend_linear(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        gamut_clip: GamutClip,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        let structure_a: ColorStructure = self.sample_layout().try_into()?;
+        let alpha_channel = structure_a.alpha_channel();
+
+        let a_max = type_max::<Pmut>();
+        let b_max = type_max::<P>();
+
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            linear_alpha_weighting: true,
+            ..BlendOptions::new(pixel_normal)
+        };
+        // Desaturating needs the whole pixel, so those results are worked out before blending
+        let desaturated = if apply_to_color && structure_a.rgb() && gamut_clip == GamutClip::DesatToGray {
+            let linear_op = |a: [f64; 3], b: [f64; 3]| clip_gamut(std::array::from_fn(|ch| op(srgb_to_linear(a[ch]), srgb_to_linear(b[ch]))), gamut_clip);
+            Some(rgb_blends(self, other, linear_op, &options)?)
+        } else {
+            None
+        };
+        try_blend_impl_with_max(self, other, |i, channel, a, b| {
+            let new = if Some(channel) == alpha_channel {
+                op(a, b)
+            } else if let Some(desaturated) = &desaturated {
+                linear_to_srgb(desaturated[i][channel])
+            } else {
+                linear_to_srgb(op(srgb_to_linear(a), srgb_to_linear(b)).clamp(0., 1.))
+            };
+            Ok(Some(new))
+        }, &options, |_| 1., (a_max, b_max))
+    }
+    fn try_blend(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> Result<f64, Error>,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        try_blend_impl_with_max(self, other, |_, _, a, b| op(a, b).map(Some), &options, |_| 1., (type_max::<Pmut>(), type_max::<P>()))
+    }
+    fn blend_channel_role(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        channel: Channel,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        let structure_a: ColorStructure = self.sample_layout().try_into()?;
+        let structure_b: ColorStructure = other.sample_layout().try_into()?;
+
+        let ch_a = structure_a
+            .channel_index(channel)
+            .ok_or(Error::MissingChannel(channel, structure_a.color_str()))?;
+        let is_alpha = channel == Channel::Alpha;
+        if is_alpha {
+            structure_b.alpha_channel().ok_or(Error::NoAlphaChannel)?;
+        }
+        let options = BlendOptions {
+            apply_to_color: !is_alpha,
+            apply_to_alpha: is_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        let maxima = (type_max::<Pmut>(), type_max::<P>());
+        try_blend_impl_with_max(self, other, |_, ch, a, b| Ok((ch == ch_a).then(|| op(a, b))), &options, |_| 1., maxima)
+    }
+    fn blend_dyn(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        strategy: &dyn BlendStrategy,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        blend_impl(self, other, |_, a, b| strategy.apply(a, b), &options)
+    }
+    fn blend_mode(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        mode: BlendMode,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(mode.op())
+        };
+        match mode {
+            BlendMode::Normal => return paste_impl(self, other, &options),
+            BlendMode::Dissolve(seed) => return self.dissolve(other, seed),
+            _ => {}
+        }
+        let op = mode.op();
+        blend_impl(self, other, |_, a, b| op(a, b), &options)
+    }
+    fn blend_indexed(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(usize, f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        blend_impl(self, other, op, &options)
+    }
+    fn blend_padded(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        anchor: Anchor,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let Some(overlap) = anchor.overlap(self.dimensions(), other.dimensions()) else {
+            return Ok(());
+        };
+        blend_overlap(self, other, overlap, |region, other_region| region.blend(other_region, op, apply_to_color, apply_to_alpha))
+    }
+    fn blend_components(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        component: ComponentBlend,
+        model: ColorModel,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        let structure_a: ColorStructure = self.sample_layout().try_into()?;
+        if !structure_a.rgb() {
+            return Err(Error::MissingChannel(Channel::Red, structure_a.color_str()));
+        }
+        blend_rgb_impl(self, other, |a, b| blend_components(a, b, component, model), &BlendOptions::default())
+    }
+    fn dissolve(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        seed: u64,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        let structure_a: ColorStructure = self.sample_layout().try_into()?;
+        let structure_b: ColorStructure = other.sample_layout().try_into()?;
+
+        let (color_channels, _) = get_channels(&structure_a, &structure_b)?;
+
+        let a_max = type_max::<Pmut>();
+        let b_max = type_max::<P>();
+
+        zip(self.pixels_mut(), other.pixels()).enumerate().for_each(|(i, (px_a, px_b))| {
+            let channel_a = px_a.channels_mut();
+            let channel_b = px_b.channels();
+            let alpha_weight = match structure_b.alpha_channel() {
+                Some(alpha_channel) => {
+                    <f64 as NumCast>::from(channel_b[alpha_channel]).unwrap() / b_max
+                }
+                None => 1.,
+            };
+            if random_unit(seed, i as u64) >= alpha_weight {
+                return;
+            }
+            color_channels.clone().for_each(|(ch_a, ch_b)| {
+                let b_f64: f64 = <f64 as NumCast>::from(channel_b[ch_b]).unwrap() / b_max;
+                channel_a[ch_a] = denormalize::<Pmut>(b_f64, a_max);
+            });
+            if let Some(alpha_a) = structure_a.alpha_channel() {
+                channel_a[alpha_a] = denormalize::<Pmut>(1., a_max);
+            }
+        });
+        Ok(())
+    }
+    fn blend_tile(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        tile: Rect,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        if !tile.fits_within(self.dimensions()) {
+            return Err(Error::InvalidArgument("tile must lie within the image"));
+        }
+        let Rect { x, y, width, height } = tile;
+        blend_overlap(self, other, ((x, y), (x, y), (width, height)), |region, other_region| region.blend(other_region, op, apply_to_color, apply_to_alpha))
+    }
+    fn blend_selection<S, SContainer>(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        selection: &ImageBuffer<S, SContainer>,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>
+    where
+        S: Pixel,
+        SContainer: Deref<Target = [S::Subpixel]>,
+    {
+        dims_match(self, other)?;
+        if self.dimensions() != selection.dimensions() {
+            return Err(Error::DimensionMismatch);
+        }
+        let _: ColorStructure = selection.sample_layout().try_into()?;
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        let s_max = type_max::<S>();
+        let s_channels = <usize as From<u8>>::from(S::CHANNEL_COUNT);
+        let selected = |i: usize| <f64 as NumCast>::from(selection.as_raw()[i * s_channels]).unwrap() / s_max > 0.5;
+        let maxima = (type_max::<Pmut>(), type_max::<P>());
+        try_blend_impl_with_max(self, other, |i, _, a, b| Ok(selected(i).then(|| op(a, b))), &options, |_| 1., maxima)
+    }
+    fn blend_trimmed(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        x: i64,
+        y: i64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let Some(bounds) = opaque_bounds(other)? else {
+            return Ok(());
+        };
+        let Some(overlap) = bounds.overlap_at((x, y), self.dimensions()) else {
+            return Ok(());
+        };
+        blend_overlap(self, other, overlap, |region, other_region| region.blend(other_region, op, apply_to_color, apply_to_alpha))
+    }
+    fn blend_threshold_op(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op_opaque: impl Fn(f64, f64) -> f64,
+        op_transparent: impl Fn(f64, f64) -> f64,
+        alpha_cut: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        let structure_b: ColorStructure = other.sample_layout().try_into()?;
+        let Some(alpha_b) = structure_b.alpha_channel() else {
+            return self.blend(other, op_opaque, apply_to_color, apply_to_alpha);
+        };
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        let b_max = type_max::<P>();
+        let b_channels = structure_b.channels();
+        let opaque = |i: usize| <f64 as NumCast>::from(other.as_raw()[i * b_channels + alpha_b]).unwrap() / b_max >= alpha_cut;
+        let maxima = (type_max::<Pmut>(), b_max);
+        try_blend_impl_with_max(self, other, |i, _, a, b| {
+            Ok(Some(if opaque(i) { op_opaque(a, b) } else { op_transparent(a, b) }))
+        }, &options, |_| 1., maxima)
+    }
+    fn blend_with_progress(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        mut progress: impl FnMut(f64),
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        let (width, height) = self.dimensions();
+        if width == 0 || height == 0 {
+            progress(1.);
+            return Ok(());
+        }
+        let row_a = width as usize * Pmut::CHANNEL_COUNT as usize;
+        let row_b = width as usize * P::CHANNEL_COUNT as usize;
+        // Each row is viewed as a one pixel high image, so the normal blend loop can run between progress reports
+        for (y, (row_a, row_b)) in zip(0..height, zip(self.chunks_exact_mut(row_a), other.chunks_exact(row_b))) {
+            let mut row_a: ImageBuffer<Pmut, &mut [Pmut::Subpixel]> = ImageBuffer::from_raw(width, 1, row_a).unwrap();
+            let row_b: ImageBuffer<P, &[P::Subpixel]> = ImageBuffer::from_raw(width, 1, row_b).unwrap();
+            blend_impl(&mut row_a, &row_b, |_, a, b| op(a, b), &options)?;
+            progress(<f64 as From<u32>>::from(y + 1) / <f64 as From<u32>>::from(height));
+        }
+        Ok(())
+    }
+    fn blend_saturating_int(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: IntOp,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        let a_max = type_max::<Pmut>();
+        if is_float::<Pmut>() || a_max - type_max::<P>() != 0. {
+            return Err(Error::UnsupportedType);
+        }
+        let structure_a: ColorStructure = self.sample_layout().try_into()?;
+        let structure_b: ColorStructure = other.sample_layout().try_into()?;
+        let (color_channels, alpha_channels) = get_channels(&structure_a, &structure_b)?;
+        let mut channels: Vec<(usize, usize)> = vec![];
+        if apply_to_color {
+            channels.extend(color_channels);
+        }
+        if apply_to_alpha {
+            channels.extend(alpha_channels);
+        }
+        let max: u128 = NumCast::from(a_max).unwrap();
+        zip(self.pixels_mut(), other.pixels()).for_each(|(px_a, px_b)| {
+            let channel_a = px_a.channels_mut();
+            let channel_b = px_b.channels();
+            for &(ch_a, ch_b) in &channels {
+                let a: u128 = NumCast::from(channel_a[ch_a]).unwrap();
+                let b: u128 = NumCast::from(channel_b[ch_b]).unwrap();
+                channel_a[ch_a] = NumCast::from(op.apply(a, b, max)).unwrap();
+            }
+        });
+        Ok(())
+    }
+    fn blend_alpha_coverage(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<AlphaCoverage, Error> {
+        self.blend(other, op, apply_to_color, apply_to_alpha)?;
+        let structure: ColorStructure = self.sample_layout().try_into()?;
+        let Some(alpha_channel) = structure.alpha_channel() else {
+            return Ok(AlphaCoverage { transparent: 0, opaque: self.pixels().len() });
+        };
+        let a_max = type_max::<Pmut>();
+        Ok(self.pixels().fold(AlphaCoverage::default(), |mut coverage, px| {
+            let alpha: f64 = <f64 as NumCast>::from(px.channels()[alpha_channel]).unwrap() / a_max;
+            if alpha <= 0. {
+                coverage.transparent += 1;
+            } else if alpha >= 1. {
+                coverage.opaque += 1;
+            }
+            coverage
+        }))
+    }
+    fn blend_normal_maps(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        method: NormalBlend,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        let structure_a: ColorStructure = self.sample_layout().try_into()?;
+        let structure_b: ColorStructure = other.sample_layout().try_into()?;
+        if !structure_a.rgb() {
+            return Err(Error::MissingChannel(Channel::Red, structure_a.color_str()));
+        }
+        if !structure_b.rgb() {
+            return Err(Error::MissingChannel(Channel::Red, structure_b.color_str()));
+        }
+        blend_rgb_impl(self, other, |a, b| blend_normals(a, b, method), &BlendOptions::default())
+    }
+    fn blend_opacity_map<S, SContainer>(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        opacity_map: &ImageBuffer<S, SContainer>,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>
+    where
+        S: Pixel,
+        SContainer: Deref<Target = [S::Subpixel]>,
+    {
+        if self.dimensions() != opacity_map.dimensions() {
+            return Err(Error::DimensionMismatch);
+        }
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        let s_max = type_max::<S>();
+        let s_channels = <usize as From<u8>>::from(S::CHANNEL_COUNT);
+        let opacity_at = |i: usize| <f64 as NumCast>::from(opacity_map.as_raw()[i * s_channels]).unwrap() / s_max;
+        blend_impl_with_opacity(self, other, |_, a, b| op(a, b), &options, opacity_at)
+    }
+    fn blend_with_max(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        a_max: f64,
+        b_max: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        if !(a_max.is_finite() && a_max > 0. && b_max.is_finite() && b_max > 0.) {
+            return Err(Error::InvalidArgument("maxima must be positive and finite"));
+        }
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        blend_impl_with_max(self, other, |_, a, b| op(a, b), &options, |_| 1., (a_max, b_max))
+    }
+    fn blend_tonal(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        range: TonalRange,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let structure: ColorStructure = self.sample_layout().try_into()?;
+        let options = BlendOptions {
+            apply_to_color,
+            apply_to_alpha,
+            ..BlendOptions::new(pixel_normal)
+        };
+        let max = type_max::<Pmut>();
+        // Read the luminance up front, as blending changes it
+        let weights: Vec<f64> = self.pixels().map(|px| {
+            let channels = px.channels();
+            let value = |ch: usize| <f64 as NumCast>::from(channels[ch]).unwrap() / max;
+            range.weight(if structure.rgb() { luminance([value(0), value(1), value(2)]) } else { value(0) })
+        }).collect();
+        blend_impl_with_opacity(self, other, |_, a, b| op(a, b), &options, |i| weights[i])
+    }
+    fn blend_under(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        dims_match(self, other)?;
+        let structure_a: ColorStructure = self.sample_layout().try_into()?;
+        let structure_b: ColorStructure = other.sample_layout().try_into()?;
+        let (color_channels, alpha_channels) = get_channels(&structure_a, &structure_b)?;
+
+        let a_max = type_max::<Pmut>();
+        let b_max = type_max::<P>();
+        let op = |top: f64, base: f64| op(base.clamp(0., 1.), top.clamp(0., 1.));
+
+        zip(self.pixels_mut(), other.pixels()).for_each(|(px_a, px_b)| {
+            let channel_a = px_a.channels_mut();
+            let channel_b = px_b.channels();
+            if apply_to_color {
+                // `self` is the source, weighted by its alpha, and `other` the backdrop
+                let top_alpha = structure_a.alpha_channel().map_or(1., |alpha_a| <f64 as NumCast>::from(channel_a[alpha_a]).unwrap() / a_max);
+                let base_alpha = structure_b.alpha_channel().map(|alpha_b| <f64 as NumCast>::from(channel_b[alpha_b]).unwrap() / b_max);
+                color_channels.clone().for_each(|(ch_a, ch_b)| {
+                    let a_f64: f64 = <f64 as NumCast>::from(channel_a[ch_a]).unwrap() / a_max;
+                    let b_f64: f64 = <f64 as NumCast>::from(channel_b[ch_b]).unwrap() / b_max;
+                    let new_64 = composite(b_f64, a_f64, op(a_f64, b_f64), base_alpha, top_alpha);
+                    if is_identity(new_64, a_f64) {
+                        return;
+                    }
+                    channel_a[ch_a] = denormalize::<Pmut>(new_64, a_max);
+                });
+            }
+            if let (true, Some((alpha_a, alpha_b))) = (apply_to_alpha, alpha_channels) {
+                let a_f64: f64 = <f64 as NumCast>::from(channel_a[alpha_a]).unwrap() / a_max;
+                let b_f64: f64 = <f64 as NumCast>::from(channel_b[alpha_b]).unwrap() / b_max;
+                let new_64 = op(a_f64, b_f64);
+                if !is_identity(new_64, a_f64) {
+                    channel_a[alpha_a] = denormalize::<Pmut>(new_64, a_max);
+                }
+            }
+        });
+        Ok(())
+    }
+    fn replace_color(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        target: [f64; 3],
+        tolerance: f64,
+    ) -> Result<(), Error> {
+        if tolerance.is_nan() || tolerance < 0. {
+            return Err(Error::InvalidArgument("tolerance must not be negative or NaN"));
+        }
+        dims_match(self, other)?;
+        let structure_a: ColorStructure = self.sample_layout().try_into()?;
+        let structure_b: ColorStructure = other.sample_layout().try_into()?;
+        let (color_channels, alpha_channels) = get_channels(&structure_a, &structure_b)?;
+
+        let a_max = type_max::<Pmut>();
+        let b_max = type_max::<P>();
+
+        zip(self.pixels_mut(), other.pixels()).for_each(|(px_a, px_b)| {
+            let channel_a = px_a.channels_mut();
+            let channel_b = px_b.channels();
+            let value = |ch: usize| <f64 as NumCast>::from(channel_a[ch]).unwrap() / a_max;
+            let color = if structure_a.rgb() { [value(0), value(1), value(2)] } else { [value(0); 3] };
+            let distance = zip(color, target).map(|(v, t)| (v - t) * (v - t)).sum::<f64>().sqrt();
+            if distance > tolerance {
+                return;
+            }
+            let copied = color_channels.clone().chain(alpha_channels);
+            copied.for_each(|(ch_a, ch_b)| {
+                let b_f64: f64 = <f64 as NumCast>::from(channel_b[ch_b]).unwrap() / b_max;
+                channel_a[ch_a] = denormalize::<Pmut>(b_f64, a_max);
+            });
+        });
+        Ok(())
+    }
+    fn blend_color_keyed(
+        &mut self,
+        other: &ImageBuffer<P, Container>,
+        key: [f64; 3],
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let structure: ColorStructure = other.sample_layout().try_into()?;
+        let max = type_max::<P>();
+        let selection: ImageBuffer<Luma<u8>, Vec<u8>> = ImageBuffer::from_fn(other.width(), other.height(), |x, y| {
+            let channels = other.get_pixel(x, y).channels();
+            let value = |ch: usize| <f64 as NumCast>::from(channels[ch]).unwrap() / max;
+            let color = if structure.rgb() { [value(0), value(1), value(2)] } else { [value(0); 3] };
+            let keyed = zip(color, key).all(|(v, k)| (v - k).abs() <= KEY_TOLERANCE);
+            Luma([if keyed { 0 } else { 255 }])
+        });
+        self.blend_selection(other, op, &selection, apply_to_color, apply_to_alpha)
+    }
+    fn compatibility(
+        &self,
+        other: &ImageBuffer<P, Container>,
+    ) -> Result<(), Error> {
+        self.compatibility_report(other).result()
+    }
+    fn compatibility_report(
+        &self,
+        other: &ImageBuffer<P, Container>,
+    ) -> CompatibilityReport {
+        let structure_a: Option<ColorStructure> = self.sample_layout().try_into().ok();
+        let structure_b: Option<ColorStructure> = other.sample_layout().try_into().ok();
+        CompatibilityReport::new(self.dimensions(), other.dimensions(), structure_a.as_ref(), structure_b.as_ref())
+    }
+}
+
+/// Reduce rgb `other` to its luminance (keeping any alpha channel), then blend that into luma `subject`.
+fn blend_luminance<P, Pmut, Container, ContainerMut, F, O>(
+    subject: &mut ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    op: F,
+    options: &BlendOptions,
+    coefficients: LumaCoefficients,
+    alpha_channel: Option<usize>,
+    opacity_at: O,
+) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+    F: Fn(usize, f64, f64) -> f64,
+    O: Fn(usize) -> f64,
+{
+    let b_max = type_max::<P>();
+    let normalized = |x, y, channel: usize| <f64 as NumCast>::from(other.get_pixel(x, y).channels()[channel]).unwrap() / b_max;
+    #[allow(clippy::cast_possible_truncation)]
+    let luminance = |x, y| coefficients.luminance([normalized(x, y, 0), normalized(x, y, 1), normalized(x, y, 2)]) as f32;
+    if let Some(alpha) = alpha_channel {
+        #[allow(clippy::cast_possible_truncation)]
+        let gray: ImageBuffer<LumaA<f32>, Vec<f32>> = ImageBuffer::from_fn(other.width(), other.height(), |x, y| LumaA([luminance(x, y), normalized(x, y, alpha) as f32]));
+        blend_impl_with_opacity(subject, &gray, op, options, opacity_at)
+    } else {
+        let gray: ImageBuffer<Luma<f32>, Vec<f32>> = ImageBuffer::from_fn(other.width(), other.height(), |x, y| Luma([luminance(x, y)]));
+        blend_impl_with_opacity(subject, &gray, op, options, opacity_at)
+    }
+}
+
+/// Blend the `overlap` region of `other` into the matching region of `subject` through copies of both regions, so the blend only sees pixels that overlap.
+fn blend_overlap<P, Pmut, Container, ContainerMut, B>(
+    subject: &mut ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    overlap: Overlap,
+    blend: B,
+) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+    B: FnOnce(&mut ImageBuffer<Pmut, Vec<Pmut::Subpixel>>, &ImageBuffer<P, Vec<P::Subpixel>>) -> Result<(), Error>,
+{
+    let ((x0, y0), (other_x, other_y), (width, height)) = overlap;
+    if width == 0 || height == 0 {
+        return Ok(());
+    }
+    let mut region: ImageBuffer<Pmut, Vec<Pmut::Subpixel>> = ImageBuffer::from_fn(width, height, |x, y| *subject.get_pixel(x0 + x, y0 + y));
+    let other_region: ImageBuffer<P, Vec<P::Subpixel>> = ImageBuffer::from_fn(width, height, |x, y| *other.get_pixel(other_x + x, other_y + y));
+    blend(&mut region, &other_region)?;
+    for (x, y, px) in region.enumerate_pixels() {
+        *subject.get_pixel_mut(x0 + x, y0 + y) = *px;
+    }
+    Ok(())
+}
+
+/// Blend `other` into `subject` in the arithmetic selected by `options.arithmetic`.
+fn blend_options_impl<P, Pmut, Container, ContainerMut>(
+    subject: &mut ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    options: &BlendOptions,
+) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    match options.arithmetic {
+        Arithmetic::Float => blend_impl(subject, other, |_, a, b| (options.op)(a, b), options),
+        Arithmetic::FixedPoint(op) => blend_fixed_point(subject, other, op, options),
+    }
+}
+
+/// `blend_impl` for `Arithmetic::FixedPoint`: blend integer images of the same bit depth with `op` without leaving integers.
+fn blend_fixed_point<P, Pmut, Container, ContainerMut>(
+    subject: &mut ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    op: IntOp,
+    options: &BlendOptions,
+) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    dims_match(subject, other)?;
+    let a_max = type_max::<Pmut>();
+    if is_float::<Pmut>() || a_max - type_max::<P>() != 0. {
+        return Err(Error::UnsupportedType);
+    }
+    if options.min_delta != 0.
+        || options.skip_transparent
+        || options.opacity - 1. != 0.
+        || options.color_opacity - 1. != 0.
+        || options.alpha_opacity - 1. != 0.
+        || options.rgb_into_luma.is_some()
+        || options.linear_alpha_weighting
+    {
+        return Err(Error::InvalidArgument("fixed point arithmetic does not support min_delta, skip_transparent, opacities, rgb_into_luma or linear_alpha_weighting"));
+    }
+    let structure_a: ColorStructure = subject.sample_layout().try_into()?;
+    let structure_b: ColorStructure = other.sample_layout().try_into()?;
+    let (color_channels, alpha_channels) = if options.fourth_channel_is_alpha {
+        get_channels(&structure_a, &structure_b)?
+    } else {
+        get_four_color_channels(&structure_a, &structure_b)?
+    };
+    let other_alpha = match structure_b {
+        ColorStructure::Rgba if !options.fourth_channel_is_alpha => None,
+        _ => structure_b.alpha_channel(),
+    };
+    let max: u128 = NumCast::from(a_max).unwrap();
+    let apply = |a: u128, b: u128| if options.reverse_operands { op.apply(b, a, max) } else { op.apply(a, b, max) };
+    let normalize = |v: u128| <f64 as NumCast>::from(v).unwrap() / a_max;
+    zip(subject.pixels_mut(), other.pixels()).for_each(|(px_a, px_b)| {
+        let channel_a = px_a.channels_mut();
+        let channel_b = px_b.channels();
+        if options.apply_to_color {
+            let alpha_b: u128 = other_alpha.map_or(max, |alpha_channel| NumCast::from(channel_b[alpha_channel]).unwrap());
+            let alpha_a: Option<u128> = alpha_channels.map(|(alpha_a, _)| NumCast::from(channel_a[alpha_a]).unwrap());
+            for (ch_a, ch_b) in color_channels.clone() {
+                let a: u128 = NumCast::from(channel_a[ch_a]).unwrap();
+                let b: u128 = NumCast::from(channel_b[ch_b]).unwrap();
+                let blended = apply(a, b);
+                // Opaque pixels keep the exact result, translucent ones are weighted like any other blend
+                channel_a[ch_a] = if alpha_b == max && alpha_a.is_none_or(|alpha_a| alpha_a == max) {
+                    NumCast::from(blended).unwrap()
+                } else {
+                    let weighted = composite(normalize(a), normalize(b), normalize(blended), alpha_a.map(normalize), normalize(alpha_b));
+                    denormalize_with::<Pmut>(weighted, a_max, options.rounding)
+                };
+            }
+        }
+        if let (true, Some((alpha_a, alpha_b))) = (options.apply_to_alpha, alpha_channels) {
+            let a: u128 = NumCast::from(channel_a[alpha_a]).unwrap();
+            let b: u128 = NumCast::from(channel_b[alpha_b]).unwrap();
+            channel_a[alpha_a] = NumCast::from(apply(a, b)).unwrap();
+        }
+    });
+    Ok(())
+}
+
+/// A copy of `image` stretched to `dimensions` with bilinear filtering, or `None` if `image` is empty.
+fn scale_bilinear<P, Container>(image: &ImageBuffer<P, Container>, dimensions: (u32, u32)) -> Option<ImageBuffer<P, Vec<P::Subpixel>>>
+where
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]>,
+{
+    if image.width() == 0 || image.height() == 0 {
+        return None;
+    }
+    let (width, height) = dimensions;
+    // Sample at pixel centers, so scaling to the same size is exact
+    #[allow(clippy::cast_possible_truncation)]
+    let center = |position: u32, size: u32| ((<f64 as From<u32>>::from(position) + 0.5) / <f64 as From<u32>>::from(size)) as f32;
+    Some(ImageBuffer::from_fn(width, height, |x, y| {
+        imageops::sample_bilinear(image, center(x, width), center(y, height)).unwrap()
+    }))
+}
+
+/// The smallest region of `image` containing every pixel that is not fully transparent, or `None` if every pixel is. Images without an alpha channel are opaque everywhere.
+pub(crate) fn opaque_bounds<P, Container>(image: &ImageBuffer<P, Container>) -> Result<Option<Rect>, Error>
+where
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]>,
+{
+    let structure: ColorStructure = image.sample_layout().try_into()?;
+    let Some(alpha_channel) = structure.alpha_channel() else {
+        let (width, height) = image.dimensions();
+        return Ok((width > 0 && height > 0).then_some(Rect { x: 0, y: 0, width, height }));
+    };
+    let zero = <P::Subpixel as num_traits::Zero>::zero();
+    let bounds = image
+        .enumerate_pixels()
+        .filter(|(_, _, px)| px.channels()[alpha_channel] > zero)
+        .fold(None, |bounds: Option<(u32, u32, u32, u32)>, (x, y, _)| match bounds {
+            None => Some((x, y, x, y)),
+            Some((x0, y0, x1, y1)) => Some((x0.min(x), y0.min(y), x1.max(x), y1.max(y))),
+        });
+    Ok(bounds.map(|(x0, y0, x1, y1)| Rect { x: x0, y: y0, width: x1 - x0 + 1, height: y1 - y0 + 1 }))
+}
+
+/// The value of a fully saturated subpixel: 1.0 for float subpixels, and the largest value for integers.
+pub(crate) fn type_max<P>() -> f64 where P: Pixel {
+    NumCast::from(<P::Subpixel as Primitive>::DEFAULT_MAX_VALUE).unwrap()
+}
+
+/// Whether `P` has float subpixels, which are not rounded and can hold values outside of 0.0..1.0.
+pub(crate) fn is_float<P>() -> bool where P: Pixel {
+    // Integer types truncate 0.5 to 0
+    <P::Subpixel as NumCast>::from(0.5).and_then(<f64 as NumCast>::from).is_some_and(|half| half > 0.)
+}
+
+/**
+Blend `other` into `dst` using the function `op`, where `dst` is any `GenericImage` rather than an `ImageBuffer`, and arg 0 of `op` is dst and 1 is other.
+
+This lets you blend into sub-images (e.g. from `image::imageops::crop`, dereferenced to the view it wraps) or custom image types. Pixels are read with `get_pixel` and written back with `put_pixel` through temporary buffers, so this is slower than `BufferBlend::blend` and uses extra memory, but otherwise behaves exactly like it.
+
+# Errors
+
+`DimensionMismatch`: `dst` and `other` have different dimensions
+
+`UnsupportedBlend`: `dst` is a luma image and `other` is an rgb image
+
+`UnsupportedType`: a pixel type does not have between 1 and 4 channels
+
+# Examples
+
+```
+use image::{open, imageops};
+use image_blend::blend_generic;
+use image_blend::pixelops::pixel_mult;
+
+let mut img1_buffer = open("test_data/1.png").unwrap().to_rgba8();
+let img2_buffer = open("test_data/2.png").unwrap().to_rgba16();
+let (width, height) = (img1_buffer.width() / 2, img1_buffer.height() / 2);
+let other = imageops::crop_imm(&img2_buffer, 0, 0, width, height);
+
+// Multiply into the top left quarter only
+let mut quarter = imageops::crop(&mut img1_buffer, 0, 0, width, height);
+blend_generic(&mut *quarter, &*other, pixel_mult, true, false).unwrap();
+img1_buffer.save("tests_out/doctest_blend_generic_result.png").unwrap();
+```
+*/
+pub fn blend_generic<I, J>(
+    dst: &mut I,
+    other: &J,
+    op: impl Fn(f64, f64) -> f64,
+    apply_to_color: bool,
+    apply_to_alpha: bool,
+) -> Result<(), Error>
+where
+    I: GenericImage,
+    J: GenericImageView,
+{
+    dims_match(dst, other)?;
+    let (width, height) = dst.dimensions();
+    let mut dst_buffer: ImageBuffer<I::Pixel, Vec<<I::Pixel as Pixel>::Subpixel>> = ImageBuffer::from_fn(width, height, |x, y| dst.get_pixel(x, y));
+    let other_buffer: ImageBuffer<J::Pixel, Vec<<J::Pixel as Pixel>::Subpixel>> = ImageBuffer::from_fn(width, height, |x, y| other.get_pixel(x, y));
+    dst_buffer.blend(&other_buffer, op, apply_to_color, apply_to_alpha)?;
+    for (x, y, px) in dst_buffer.enumerate_pixels() {
+        dst.put_pixel(x, y, *px);
+    }
+    Ok(())
+}
+
+/// A uniformly distributed value in 0.0..1.0, determined by `seed` and `index` alone, using the `SplitMix64` mixer.
+pub(crate) fn random_unit(seed: u64, index: u64) -> f64 {
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    #[allow(clippy::cast_precision_loss)]
+    let unit = (z >> 11) as f64 / (1u64 << 53) as f64;
+    unit
+}
+
+/**
+Weight `blended`, the result of blending `b` into `a`, by the alphas of the two pixels.
+
+When `self` has no alpha channel (`alpha_a` is `None`) it is opaque, and `blended` is simply mixed with `a` by `alpha_b`. Otherwise this is the W3C source-over composite of a blended source over a translucent backdrop, worked out in premultiplied space and divided back to straight alpha. Where `a` is transparent the result tends to `b`, as there is nothing to blend with. For an opaque `a` both agree.
+*/
+pub(crate) fn composite(a: f64, b: f64, blended: f64, alpha_a: Option<f64>, alpha_b: f64) -> f64 {
+    let Some(alpha_a) = alpha_a else {
+        return blended * alpha_b + a * (1. - alpha_b);
+    };
+    let alpha_out = alpha_b + alpha_a * (1. - alpha_b);
+    if alpha_out == 0. {
+        return a;
+    }
+    let source = (1. - alpha_a) * b + alpha_a * blended;
+    (alpha_b * source + alpha_a * a * (1. - alpha_b)) / alpha_out
+}
+
+/// Largest difference from the key color, per normalized channel, for `blend_color_keyed` to skip a pixel: half an 8 bit level.
+const KEY_TOLERANCE: f64 = 0.5 / 255.;
+
+/// Largest difference between a blended value and the original for the blend to count as the identity.
+const IDENTITY_EPSILON: f64 = 1e-12;
+
+/**
+Whether blending left a normalized subpixel `original` unchanged, compared before clamping.
+
+Such subpixels are not written back, so the destination keeps its exact value rather than being re-quantized, which can shift it by one level (e.g. with `Rounding::Truncate`, or float error on 16-bit values). This keeps repeated edits and round trips lossless.
+*/
+fn is_identity(blended: f64, original: f64) -> bool {
+    (blended - original).abs() <= IDENTITY_EPSILON
+}
+
+/// Clamp a normalized value to 0.0..1.0 and scale it back to a subpixel of `P`, rounding to the nearest value for integer types.
+pub(crate) fn denormalize<P>(value: f64, max: f64) -> P::Subpixel
+where
+    P: Pixel,
+{
+    denormalize_with::<P>(value, max, Rounding::Round)
+}
+
+/// Like `denormalize`, but with the rounding of integer types chosen by `rounding`.
+pub(crate) fn denormalize_with<P>(value: f64, max: f64, rounding: Rounding) -> P::Subpixel
+where
+    P: Pixel,
+{
+    let scaled = value.clamp(0., 1.) * max;
+    // Decided by the type rather than by `max`, which can be 1.0 for integer types too (see `blend_with_max`)
+    if is_float::<P>() {
+        return NumCast::from(scaled).unwrap();
+    }
+    NumCast::from(rounding.apply(scaled)).unwrap()
+}
+
+type ChannelIter = (
+    Zip<vec::IntoIter<usize>, vec::IntoIter<usize>>,
+    Option<(usize, usize)>,
+);
+fn get_channels(
+    structure_a: &ColorStructure,
+    structure_b: &ColorStructure,
+) -> Result<ChannelIter, Error> {
+    if !structure_a.accepts(structure_b) {
+        return Err(Error::UnsupportedBlend(
+            structure_a.color_str(),
+            structure_b.color_str(),
+        ));
+    }
+    let color_channels = match (structure_a.rgb(), structure_b.rgb()) {
+        (true, true) => zip(vec![0usize, 1, 2], vec![0usize, 1, 2]),
+        (true, false) => zip(vec![0, 1, 2], vec![0, 0, 0]),
+        (false, _) => zip(vec![0], vec![0]),
+    };
+    let alpha_channels = match (structure_a.alpha(), structure_b.alpha()) {
+        (true, true) => Some((
+            structure_a.alpha_channel().unwrap(),
+            structure_b.alpha_channel().unwrap(),
+        )),
+        _ => None,
+    };
+    Ok((color_channels, alpha_channels))
+}
+/// The channels to blend when `BlendOptions::fourth_channel_is_alpha` is false: every channel of a 4 channel image is color, and there is no alpha channel.
+fn get_four_color_channels(
+    structure_a: &ColorStructure,
+    structure_b: &ColorStructure,
+) -> Result<ChannelIter, Error> {
+    match (structure_a, structure_b) {
+        (ColorStructure::Rgba, ColorStructure::Rgba) => Ok((zip(vec![0usize, 1, 2, 3], vec![0usize, 1, 2, 3]), None)),
+        (ColorStructure::Rgba, _) | (_, ColorStructure::Rgba) => Err(Error::UnsupportedBlend(
+            structure_a.color_str(),
+            structure_b.color_str(),
+        )),
+        _ => get_channels(structure_a, structure_b),
+    }
+}
+/// Counts of blended values that reached (or were clamped to) 0.0 or 1.0, logged after each blend with the `log` feature.
+#[cfg(feature = "log")]
+#[derive(Default)]
+struct ClampStats {
+    low: usize,
+    high: usize,
+    total: usize,
+}
+#[cfg(feature = "log")]
+impl ClampStats {
+    fn record(&mut self, value: f64) {
+        self.total += 1;
+        if value <= 0. {
+            self.low += 1;
+        } else if value >= 1. {
+            self.high += 1;
+        }
+    }
+}
+
+/// The core per-channel blend loop shared by the blend methods, generic over `op` so it can be inlined. `op` also receives the index of the channel of `subject` being written.
+pub(crate) fn blend_impl<P, Pmut, Container, ContainerMut, F>(
+    subject: &mut ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    op: F,
+    options: &BlendOptions,
+) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+    F: Fn(usize, f64, f64) -> f64,
+{
+    blend_impl_with_opacity(subject, other, op, options, |_| 1.)
+}
+
+/// `blend_impl`, with the color blend of each pixel further weighted by `opacity_at` its index, e.g. to read an opacity map.
+fn blend_impl_with_opacity<P, Pmut, Container, ContainerMut, F, O>(
+    subject: &mut ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    op: F,
+    options: &BlendOptions,
+    opacity_at: O,
+) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+    F: Fn(usize, f64, f64) -> f64,
+    O: Fn(usize) -> f64,
+{
+    dims_match(subject, other)?;
+    let structure_a: ColorStructure = subject.sample_layout().try_into()?;
+    let structure_b: ColorStructure = other.sample_layout().try_into()?;
+
+    if let (false, true, Some(coefficients)) = (structure_a.rgb(), structure_b.rgb(), options.rgb_into_luma) {
+        return blend_luminance(subject, other, op, options, coefficients, structure_b.alpha_channel(), opacity_at);
+    }
+    blend_impl_with_max(subject, other, op, options, opacity_at, (type_max::<Pmut>(), type_max::<P>()))
+}
+
+/// The pixel loop of `blend_impl_with_opacity`, normalizing the subpixels of `subject` and `other` by the given maxima rather than those of their types.
+fn blend_impl_with_max<P, Pmut, Container, ContainerMut, F, O>(
+    subject: &mut ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    op: F,
+    options: &BlendOptions,
+    opacity_at: O,
+    maxima: (f64, f64),
+) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+    F: Fn(usize, f64, f64) -> f64,
+    O: Fn(usize) -> f64,
+{
+    try_blend_impl_with_max(subject, other, |_, channel, a, b| Ok(Some(op(channel, a, b))), options, opacity_at, maxima)
+}
+
+/// Blend the rgb `subject` with `op`, which works on whole rgb pixels rather than one subpixel at a time, e.g. to blend hue.
+fn blend_rgb_impl<P, Pmut, Container, ContainerMut, F>(
+    subject: &mut ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    op: F,
+    options: &BlendOptions,
+) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+    F: Fn([f64; 3], [f64; 3]) -> [f64; 3],
+{
+    let blended = rgb_blends(subject, other, op, options)?;
+    try_blend_impl_with_max(subject, other, |i, ch, _, _| Ok(Some(blended[i][ch])), options, |_| 1., (type_max::<Pmut>(), type_max::<P>()))
+}
+
+/// `op` worked out for the rgb of every pixel of `subject` and `other`, clamping the inputs as `options` asks. A luma `other` is treated as gray.
+fn rgb_blends<P, Pmut, Container, ContainerMut, F>(
+    subject: &ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    op: F,
+    options: &BlendOptions,
+) -> Result<Vec<[f64; 3]>, Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: Deref<Target = [Pmut::Subpixel]>,
+    F: Fn([f64; 3], [f64; 3]) -> [f64; 3],
+{
+    let structure_a: ColorStructure = subject.sample_layout().try_into()?;
+    let structure_b: ColorStructure = other.sample_layout().try_into()?;
+    let (color_channels, _) = get_channels(&structure_a, &structure_b)?;
+    let a_max = type_max::<Pmut>();
+    let b_max = type_max::<P>();
+    let clamp = |v: f64| if options.clamp_inputs { v.clamp(0., 1.) } else { v };
+    Ok(zip(subject.pixels(), other.pixels()).map(|(px_a, px_b)| {
+        let mut a_rgb = [0f64; 3];
+        let mut b_rgb = [0f64; 3];
+        color_channels.clone().for_each(|(ch_a, ch_b)| {
+            a_rgb[ch_a] = clamp(<f64 as NumCast>::from(px_a.channels()[ch_a]).unwrap() / a_max);
+            b_rgb[ch_a] = clamp(<f64 as NumCast>::from(px_b.channels()[ch_b]).unwrap() / b_max);
+        });
+        if options.reverse_operands { op(b_rgb, a_rgb) } else { op(a_rgb, b_rgb) }
+    }).collect())
+}
+
+/**
+`blend_impl_with_max` for ops that need more than one subpixel, or can fail.
+
+`op` also receives the index of the pixel before the index of the channel, e.g. to look up a blend worked out for the whole pixel. It returns `None` to leave a subpixel unchanged, or an error to stop the blend, in which case the pixels before it are left blended.
+*/
+fn try_blend_impl_with_max<P, Pmut, Container, ContainerMut, F, O>(
+    subject: &mut ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    mut op: F,
+    options: &BlendOptions,
+    opacity_at: O,
+    (a_max, b_max): (f64, f64),
+) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+    F: FnMut(usize, usize, f64, f64) -> Result<Option<f64>, Error>,
+    O: Fn(usize) -> f64,
+{
+    dims_match(subject, other)?;
+    let structure_a: ColorStructure = subject.sample_layout().try_into()?;
+    let structure_b: ColorStructure = other.sample_layout().try_into()?;
+    let (color_channels, alpha_channels) = if options.fourth_channel_is_alpha {
+        get_channels(&structure_a, &structure_b)?
+    } else {
+        get_four_color_channels(&structure_a, &structure_b)?
+    };
+    let other_alpha = match structure_b {
+        ColorStructure::Rgba if !options.fourth_channel_is_alpha => None,
+        _ => structure_b.alpha_channel(),
+    };
+
+    #[cfg(feature = "log")]
+    let mut clamp_stats = ClampStats::default();
+    let mut op = |i, channel, a: f64, b: f64| {
+        let (a, b) = if options.clamp_inputs { (a.clamp(0., 1.), b.clamp(0., 1.)) } else { (a, b) };
+        if options.reverse_operands { op(i, channel, b, a) } else { op(i, channel, a, b) }
+    };
+
+    if options.apply_to_color {
+        for (i, (px_a, px_b)) in zip(subject.pixels_mut(), other.pixels()).enumerate() {
+            let channel_a = px_a.channels_mut();
+            let channel_b = px_b.channels();
+            let alpha_weight = match other_alpha {
+                Some(alpha_channel) => {
+                    <f64 as NumCast>::from(channel_b[alpha_channel]).unwrap() / b_max
+                }
+                None => 1.,
+            } * options.opacity.clamp(0., 1.) * options.color_opacity.clamp(0., 1.) * opacity_at(i).clamp(0., 1.);
+            if alpha_weight == 0. {
+                continue;
+            }
+            let self_alpha = alpha_channels.map(|(alpha_a, _)| <f64 as NumCast>::from(channel_a[alpha_a]).unwrap() / a_max);
+            for (ch_a, ch_b) in color_channels.clone() {
+                let a_f64: f64 = <f64 as NumCast>::from(channel_a[ch_a]).unwrap() / a_max;
+                let b_f64: f64 = <f64 as NumCast>::from(channel_b[ch_b]).unwrap() / b_max;
+                let Some(new_64_unweighted) = op(i, ch_a, a_f64, b_f64)? else {
+                    continue;
+                };
+                #[cfg(feature = "log")]
+                clamp_stats.record(new_64_unweighted);
+                let new_64_unclamped = if options.linear_alpha_weighting {
+                    let linear = composite(srgb_to_linear(a_f64), srgb_to_linear(b_f64), srgb_to_linear(new_64_unweighted), self_alpha, alpha_weight);
+                    linear_to_srgb(linear)
+                } else {
+                    composite(a_f64, b_f64, new_64_unweighted, self_alpha, alpha_weight)
+                };
+                if is_identity(new_64_unclamped, a_f64) {
+                    continue;
+                }
+                let new_64 = new_64_unclamped.clamp(0., 1.0);
+                if (new_64 - a_f64).abs() < options.min_delta {
+                    continue;
+                }
+                let new_val = denormalize_with::<Pmut>(new_64, a_max, options.rounding);
+                channel_a[ch_a] = new_val;
+            }
+        }
+    }
+    if options.apply_to_alpha {
+        if let Some((alpha_a, alpha_b)) = alpha_channels {
+            for (i, (px_a, px_b)) in zip(subject.pixels_mut(), other.pixels()).enumerate() {
+                let channel_a = px_a.channels_mut();
+                let channel_b = px_b.channels();
+
+                let a_f64: f64 = <f64 as NumCast>::from(channel_a[alpha_a]).unwrap() / a_max;
+                let b_f64: f64 = <f64 as NumCast>::from(channel_b[alpha_b]).unwrap() / b_max;
+                if options.skip_transparent && b_f64 == 0. {
+                    continue;
+                }
+                let Some(new_64_unweighted) = op(i, alpha_a, a_f64, b_f64)? else {
+                    continue;
+                };
+                #[cfg(feature = "log")]
+                clamp_stats.record(new_64_unweighted);
+                let new_64_unclamped = a_f64 + (new_64_unweighted - a_f64) * options.alpha_opacity.clamp(0., 1.);
+                if is_identity(new_64_unclamped, a_f64) {
+                    continue;
+                }
+                let new_64 = new_64_unclamped.clamp(0., 1.0);
+                if (new_64 - a_f64).abs() < options.min_delta {
+                    continue;
+                }
+                let new_val = denormalize_with::<Pmut>(new_64, a_max, options.rounding);
+                channel_a[alpha_a] = new_val;
+            }
+        }
+    }
+    #[cfg(feature = "log")]
+    log::debug!(
+        "blend: {} of {} subpixels hit 0 and {} hit 1",
+        clamp_stats.low,
+        clamp_stats.total,
+        clamp_stats.high
+    );
+
+    Ok(())
+}
+
+/**
+Fast path for `BlendMode::Normal`.
+
+When both images share a channel layout and subpixel type, fully opaque pixels of `other` are copied without a float round trip. Partially transparent pixels use the same formula as `blend_impl`, and float values outside of 0.0..1.0 are clamped as `blend_impl` clamps them, so the output is identical.
+*/
+fn paste_impl<P, Pmut, Container, ContainerMut>(
+    subject: &mut ImageBuffer<Pmut, ContainerMut>,
+    other: &ImageBuffer<P, Container>,
+    options: &BlendOptions,
+) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + AsRef<[<P as Pixel>::Subpixel]>,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    dims_match(subject, other)?;
+    let structure_a: ColorStructure = subject.sample_layout().try_into()?;
+    let structure_b: ColorStructure = other.sample_layout().try_into()?;
+
+    let a_max = type_max::<Pmut>();
+    let b_max = type_max::<P>();
+
+    let same_type = structure_a.channels() == structure_b.channels()
+        && a_max - b_max == 0.
+        && size_of::<P::Subpixel>() == size_of::<Pmut::Subpixel>();
+    if !same_type {
+        return blend_impl(subject, other, |_, a, b| pixel_normal(a, b), options);
+    }
+    let (color_channels, alpha_channels) = get_channels(&structure_a, &structure_b)?;
+    // Only float subpixels can be out of range. Copying them would skip the clamping `blend_impl` does
+    let float = is_float::<Pmut>();
+    let copy = |from: P::Subpixel| -> Pmut::Subpixel {
+        if float {
+            denormalize::<Pmut>(<f64 as NumCast>::from(from).unwrap(), a_max)
+        } else {
+            // Same type, so this cast is lossless
+            NumCast::from(from).unwrap()
+        }
+    };
+
+    zip(subject.pixels_mut(), other.pixels()).for_each(|(px_a, px_b)| {
+        let channel_a = px_a.channels_mut();
+        let channel_b = px_b.channels();
+        if options.apply_to_color {
+            let alpha_weight = match structure_b.alpha_channel() {
+                Some(alpha_channel) => {
+                    <f64 as NumCast>::from(channel_b[alpha_channel]).unwrap() / b_max
+                }
+                None => 1.,
+            };
+            if 1. - alpha_weight == 0. {
+                color_channels.clone().for_each(|(ch_a, ch_b)| {
+                    channel_a[ch_a] = copy(channel_b[ch_b]);
+                });
+            } else if alpha_weight != 0. {
+                let self_alpha = alpha_channels.map(|(alpha_a, _)| <f64 as NumCast>::from(channel_a[alpha_a]).unwrap() / a_max);
+                color_channels.clone().for_each(|(ch_a, ch_b)| {
+                    let a_f64: f64 = <f64 as NumCast>::from(channel_a[ch_a]).unwrap() / a_max;
+                    let b_f64: f64 = <f64 as NumCast>::from(channel_b[ch_b]).unwrap() / b_max;
+                    let blended = if options.clamp_inputs { b_f64.clamp(0., 1.) } else { b_f64 };
+                    let new_64 = composite(a_f64, b_f64, blended, self_alpha, alpha_weight);
+                    if is_identity(new_64, a_f64) {
+                        return;
+                    }
+                    channel_a[ch_a] = denormalize_with::<Pmut>(new_64.clamp(0., 1.0), a_max, options.rounding);
+                });
+            }
+        }
+        if options.apply_to_alpha {
+            if let Some((alpha_a, alpha_b)) = alpha_channels {
+                channel_a[alpha_a] = copy(channel_b[alpha_b]);
+            }
+        }
+    });
+    Ok(())
 }
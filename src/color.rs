@@ -0,0 +1,195 @@
+use crate::enums::{ColorModel, ComponentBlend, GamutClip, NormalBlend};
+
+/// Convert a normalized sRGB encoded value to linear light.
+pub(crate) fn srgb_to_linear(v: f64) -> f64 {
+    if v <= 0.040_45 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a normalized linear light value to sRGB encoding.
+pub(crate) fn linear_to_srgb(v: f64) -> f64 {
+    if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// Weights used to compute luminance from rgb, which sum to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LumaCoefficients {
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+}
+impl LumaCoefficients {
+    /// ITU-R BT.601, used by standard definition video and many older tools.
+    pub const REC_601: Self = Self { red: 0.299, green: 0.587, blue: 0.114 };
+    /// ITU-R BT.709, which shares its primaries with sRGB. The default.
+    pub const REC_709: Self = Self { red: 0.2126, green: 0.7152, blue: 0.0722 };
+    /// ITU-R BT.2020, used by wide gamut and HDR video.
+    pub const REC_2020: Self = Self { red: 0.2627, green: 0.6780, blue: 0.0593 };
+
+    /// Luminance of a normalized rgb triplet.
+    #[must_use]
+    pub fn luminance(&self, rgb: [f64; 3]) -> f64 {
+        self.red * rgb[0] + self.green * rgb[1] + self.blue * rgb[2]
+    }
+}
+impl Default for LumaCoefficients {
+    fn default() -> Self {
+        Self::REC_709
+    }
+}
+
+/// Rec. 709 luminance of a linear rgb triplet.
+pub(crate) fn luminance(rgb: [f64; 3]) -> f64 {
+    LumaCoefficients::REC_709.luminance(rgb)
+}
+
+/**
+Bring `rgb` back into the 0.0..1.0 range according to `clip`.
+
+`DesatToGray` moves the color in a straight line towards the gray of the same luminance, stopping at the first point where every channel is in range. This keeps the hue that a per-channel clamp would shift.
+*/
+pub(crate) fn clip_gamut(rgb: [f64; 3], clip: GamutClip) -> [f64; 3] {
+    match clip {
+        GamutClip::Clamp => rgb.map(|v| v.clamp(0., 1.)),
+        GamutClip::DesatToGray => {
+            let gray = luminance(rgb).clamp(0., 1.);
+            let t = rgb.iter().fold(1f64, |t, &v| {
+                if v > 1. {
+                    t.min((1. - gray) / (v - gray))
+                } else if v < 0. {
+                    t.min(gray / (gray - v))
+                } else {
+                    t
+                }
+            });
+            rgb.map(|v| (gray + t * (v - gray)).clamp(0., 1.))
+        }
+    }
+}
+
+/// Hue (0.0..1.0), largest channel and smallest channel of an rgb triplet.
+#[allow(clippy::float_cmp)]
+fn hue_max_min(rgb: [f64; 3]) -> (f64, f64, f64) {
+    let [r, g, b] = rgb;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    let hue = if chroma == 0. {
+        0.
+    } else if max == r {
+        ((g - b) / chroma).rem_euclid(6.)
+    } else if max == g {
+        (b - r) / chroma + 2.
+    } else {
+        (r - g) / chroma + 4.
+    };
+    (hue / 6., max, min)
+}
+
+/// The rgb triplet with the given hue (0.0..1.0), chroma, and offset added to every channel.
+fn from_hue_chroma(hue: f64, chroma: f64, offset: f64) -> [f64; 3] {
+    let h = hue.rem_euclid(1.) * 6.;
+    let x = chroma * (1. - (h % 2. - 1.).abs());
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let rgb = match h as u8 {
+        0 => [chroma, x, 0.],
+        1 => [x, chroma, 0.],
+        2 => [0., chroma, x],
+        3 => [0., x, chroma],
+        4 => [x, 0., chroma],
+        _ => [chroma, 0., x],
+    };
+    rgb.map(|v| v + offset)
+}
+
+/// Convert a normalized rgb triplet to hue, saturation and value, all in 0.0..1.0.
+pub(crate) fn rgb_to_hsv(rgb: [f64; 3]) -> [f64; 3] {
+    let (hue, max, min) = hue_max_min(rgb);
+    let saturation = if max == 0. { 0. } else { (max - min) / max };
+    [hue, saturation, max]
+}
+
+/// Convert hue, saturation and value in 0.0..1.0 to a normalized rgb triplet.
+pub(crate) fn hsv_to_rgb(hsv: [f64; 3]) -> [f64; 3] {
+    let [hue, saturation, value] = hsv;
+    let chroma = value * saturation;
+    from_hue_chroma(hue, chroma, value - chroma)
+}
+
+/// Convert a normalized rgb triplet to hue, saturation and lightness, all in 0.0..1.0.
+#[allow(clippy::float_cmp)]
+pub(crate) fn rgb_to_hsl(rgb: [f64; 3]) -> [f64; 3] {
+    let (hue, max, min) = hue_max_min(rgb);
+    let lightness = f64::midpoint(max, min);
+    let saturation = if max == min {
+        0.
+    } else {
+        (max - min) / (1. - (2. * lightness - 1.).abs())
+    };
+    [hue, saturation, lightness]
+}
+
+/// Convert hue, saturation and lightness in 0.0..1.0 to a normalized rgb triplet.
+pub(crate) fn hsl_to_rgb(hsl: [f64; 3]) -> [f64; 3] {
+    let [hue, saturation, lightness] = hsl;
+    let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+    from_hue_chroma(hue, chroma, lightness - chroma / 2.)
+}
+
+/// Converts between rgb and a cylindrical color model.
+type ModelConversion = fn([f64; 3]) -> [f64; 3];
+
+/// Combine `a` and `b` by taking `component` from `b` and the rest from `a`, both split into `model`.
+pub(crate) fn blend_components(a: [f64; 3], b: [f64; 3], component: ComponentBlend, model: ColorModel) -> [f64; 3] {
+    let (split, join): (ModelConversion, ModelConversion) = match model {
+        ColorModel::Hsl => (rgb_to_hsl, hsl_to_rgb),
+        ColorModel::Hsv => (rgb_to_hsv, hsv_to_rgb),
+    };
+    let [a_hue, a_saturation, a_value] = split(a);
+    let [b_hue, b_saturation, b_value] = split(b);
+    join(match component {
+        ComponentBlend::Hue => [b_hue, a_saturation, a_value],
+        ComponentBlend::Saturation => [a_hue, b_saturation, a_value],
+        ComponentBlend::Color => [b_hue, b_saturation, a_value],
+        ComponentBlend::Value => [a_hue, a_saturation, b_value],
+    })
+}
+
+/// Decode a normal map color (0.0..1.0 per channel) to a vector with components in -1.0..1.0.
+fn decode_normal(rgb: [f64; 3]) -> [f64; 3] {
+    rgb.map(|v| v * 2. - 1.)
+}
+/// Encode a vector as a normal map color, normalizing it first. A zero length vector has no direction and gives `None`.
+fn encode_normal(n: [f64; 3]) -> Option<[f64; 3]> {
+    let length = n.iter().map(|v| v * v).sum::<f64>().sqrt();
+    (length > 0. && length.is_finite()).then(|| n.map(|v| v / length * 0.5 + 0.5))
+}
+
+/// Combine the tangent space normals encoded as colors in `base` and `detail` with `method`, returning the encoded result.
+///
+/// Where the combination is undefined (the base normal points straight into the surface for RNM, or the normals cancel out), `base` is returned unchanged.
+pub(crate) fn blend_normals(base: [f64; 3], detail: [f64; 3], method: NormalBlend) -> [f64; 3] {
+    let [bx, by, bz] = decode_normal(base);
+    let [dx, dy, dz] = decode_normal(detail);
+    let combined = match method {
+        NormalBlend::Reoriented => {
+            // Barré-Brisebois and Hill: t = base + (0, 0, 1), u = detail * (-1, -1, 1), r = t * dot(t, u) / t.z - u
+            let t = [bx, by, bz + 1.];
+            let u = [-dx, -dy, dz];
+            if t[2] == 0. {
+                return base;
+            }
+            let scale = (t[0] * u[0] + t[1] * u[1] + t[2] * u[2]) / t[2];
+            [t[0] * scale - u[0], t[1] * scale - u[1], t[2] * scale - u[2]]
+        }
+        NormalBlend::PartialDerivative => [bx * dz + dx * bz, by * dz + dy * bz, bz * dz],
+    };
+    encode_normal(combined).unwrap_or(base)
+}
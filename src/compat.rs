@@ -0,0 +1,168 @@
+use image::ColorType;
+
+use crate::{
+    enums::{ColorString, ColorStructure},
+    error::Error,
+};
+
+/// Every color type this crate can blend, in the order of `ColorType`.
+const SUPPORTED_TYPES: [ColorType; 10] = [
+    ColorType::L8,
+    ColorType::La8,
+    ColorType::Rgb8,
+    ColorType::Rgba8,
+    ColorType::L16,
+    ColorType::La16,
+    ColorType::Rgb16,
+    ColorType::Rgba16,
+    ColorType::Rgb32F,
+    ColorType::Rgba32F,
+];
+
+/// The structure of `color`, or `None` if it is not supported.
+fn structure(color: ColorType) -> Option<ColorStructure> {
+    SUPPORTED_TYPES.contains(&color).then(|| ColorStructure::from(color))
+}
+
+/**
+Whether an image of color type `b` can be blended into one of color type `a` (of the same size) by `blend`.
+
+Every supported type can be blended into every other, except that an rgb image cannot be blended into a luma image. Unsupported types, such as those added to `ColorType` after this crate was written, cannot be blended at all.
+
+# Examples
+
+```
+use image::ColorType;
+use image_blend::can_blend;
+
+assert!(can_blend(ColorType::Rgba8, ColorType::L16));
+assert!(!can_blend(ColorType::L8, ColorType::Rgb8));
+```
+*/
+#[must_use]
+pub fn can_blend(a: ColorType, b: ColorType) -> bool {
+    match (structure(a), structure(b)) {
+        (Some(a), Some(b)) => a.accepts(&b),
+        _ => false,
+    }
+}
+
+/**
+Every pair of supported color types as `(self_type, other_type, can_blend(self_type, other_type))`, for tooling and UIs to show which blends are valid.
+
+# Examples
+
+```
+use image_blend::supported_combinations;
+
+for (a, b, supported) in supported_combinations() {
+    println!("{a:?} <- {b:?}: {supported}");
+}
+```
+*/
+#[must_use]
+pub fn supported_combinations() -> Vec<(ColorType, ColorType, bool)> {
+    SUPPORTED_TYPES
+        .iter()
+        .flat_map(|&a| SUPPORTED_TYPES.iter().map(move |&b| (a, b, can_blend(a, b))))
+        .collect()
+}
+
+/**
+Whether `other` can be blended into `self`, as found by `compatibility_report` without performing the blend.
+
+Lets tooling validate a blend up front, e.g. to gray out operations a UI cannot perform.
+
+# Examples
+
+```
+use image::{GrayImage, RgbImage};
+use image_blend::{BufferBlend, Error};
+
+let luma = GrayImage::new(4, 4);
+let rgb = RgbImage::new(4, 4);
+
+// Luma can be blended into rgb, but not the other way around
+assert!(rgb.compatibility_report(&luma).is_compatible());
+let report = luma.compatibility_report(&rgb);
+assert!(report.dimensions_match() && !report.color_blendable);
+assert!(matches!(report.result(), Err(Error::UnsupportedBlend(..))));
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    /// Width and height of `self`.
+    pub self_dimensions: (u32, u32),
+    /// Width and height of `other`.
+    pub other_dimensions: (u32, u32),
+    /// Channel layout of `self` ("L", "La", "Rgb" or "Rgba"), or `None` if it is not supported.
+    pub self_layout: Option<&'static str>,
+    /// Channel layout of `other` ("L", "La", "Rgb" or "Rgba"), or `None` if it is not supported.
+    pub other_layout: Option<&'static str>,
+    /// The color channels of `other` can be blended into those of `self`: both layouts are supported, and `other` is not rgb when `self` is luma.
+    pub color_blendable: bool,
+    /// Both images have an alpha channel, so blending with `apply_to_alpha` has an effect.
+    pub alpha_blendable: bool,
+}
+impl CompatibilityReport {
+    pub(crate) fn new(
+        self_dimensions: (u32, u32),
+        other_dimensions: (u32, u32),
+        structure_a: Option<&ColorStructure>,
+        structure_b: Option<&ColorStructure>,
+    ) -> Self {
+        let (color_blendable, alpha_blendable) = match (structure_a, structure_b) {
+            (Some(a), Some(b)) => (a.accepts(b), a.alpha() && b.alpha()),
+            _ => (false, false),
+        };
+        Self {
+            self_dimensions,
+            other_dimensions,
+            self_layout: structure_a.map(ColorString::color_str),
+            other_layout: structure_b.map(ColorString::color_str),
+            color_blendable,
+            alpha_blendable,
+        }
+    }
+    pub(crate) fn from_color_types(
+        self_dimensions: (u32, u32),
+        other_dimensions: (u32, u32),
+        color_a: ColorType,
+        color_b: ColorType,
+    ) -> Self {
+        Self::new(self_dimensions, other_dimensions, structure(color_a).as_ref(), structure(color_b).as_ref())
+    }
+    /// Both images have the same width and height.
+    #[must_use]
+    pub fn dimensions_match(&self) -> bool {
+        self.self_dimensions == self.other_dimensions
+    }
+    /// `blend` would succeed. The same as `result().is_ok()`.
+    #[must_use]
+    pub fn is_compatible(&self) -> bool {
+        self.result().is_ok()
+    }
+    /**
+    The error `blend` would return, if any.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedType`: `self` or `other` is of an unsupported color type
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+    */
+    pub fn result(&self) -> Result<(), Error> {
+        if !self.dimensions_match() {
+            return Err(Error::DimensionMismatch);
+        }
+        let (Some(self_layout), Some(other_layout)) = (self.self_layout, self.other_layout) else {
+            return Err(Error::UnsupportedType);
+        };
+        if !self.color_blendable {
+            return Err(Error::UnsupportedBlend(self_layout, other_layout));
+        }
+        Ok(())
+    }
+}
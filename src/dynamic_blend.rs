@@ -1,8 +1,11 @@
 use std::ops::DerefMut;
 
-use image::{ColorType, DynamicImage, ImageBuffer, Pixel};
+use image::{imageops::FilterType, ColorType, DynamicImage, GrayImage, ImageBuffer, Luma, Pixel, Rgba32FImage};
 
-use crate::{BufferBlend, BufferGetAlpha, BufferSetAlpha, BufferStripAlpha, Error};
+use crate::{AlphaCoverage, Anchor, BlendMode, BlendOptions, BlendStrategy, BufferAdjustAlpha, BufferBlend, BufferMapPixels, Channel, BufferGetAlpha, BufferSetAlpha, BufferStripAlpha, ColorModel, CompatibilityReport, ComponentBlend, Error, GamutClip, IntOp, LumaCoefficients, Lut3D, MismatchPolicy, NormalBlend, Rect, TonalRange};
+use crate::map_ops::luminance_in_place;
+use crate::pixelops::{pixel_diff, pixel_normal, pixel_screen};
+use crate::reduce::blend_weighted;
 
 pub trait DynamicChops {
     /**
@@ -14,6 +17,8 @@ pub trait DynamicChops {
 
     If `other` has an alpha channel, the output is weighted by this alpha channel (so if alpha for `other` for this pixel is 0.5, the blend effect will be 0.5 as strong)
 
+    This simple weighting is only exact when `self` is opaque. When both images have an alpha channel, color is instead composited source-over in premultiplied space, so blending onto translucent pixels of `self` gives the same color as flattening the layers would. Blend the alpha channel with `pixel_screen` to get the matching source-over alpha.
+
     # Arguments
 
     Use `apply_to_color` and `apply_to_alpha` to control which channels are affected.
@@ -22,6 +27,8 @@ pub trait DynamicChops {
 
     `op` is a function that takes two f64 values and returns a f64 value. (e.g. `|self, other| self + other`)
 
+    `op` can be any function or closure. To keep the code for every pair of color types small it is called through a `&dyn Fn`, which costs an indirect call per subpixel. Blend the underlying buffers with `BufferBlend::blend` to have `op` inlined into the pixel loop instead.
+
     Standard blend modes such as those found in photoshop are provided as functions (e.g. `pixel_add`, `pixel_mult`, etc.).
 
     The values are normalized to the range 0.0..1.0 before blending, and then scaled back to the input type's range.
@@ -88,7 +95,121 @@ pub trait DynamicChops {
     fn blend (
         &mut self,
         other: &Self,
-        op: fn(f64, f64) -> f64,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` as described by `options`.
+
+    Behaves like `blend`, but exposes the less common settings (such as `min_delta`) through `BlendOptions`.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions and `options.dimension_policy` is `Strict`
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    `UnsupportedType`: `options.arithmetic` is `FixedPoint` and `self` and `other` are not integer images with the same bit depth
+
+    `InvalidArgument`: `options.arithmetic` is `FixedPoint` and an option it can't honour is set (see `BlendOptions::arithmetic`)
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BlendOptions, DynamicChops};
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    let options = BlendOptions {
+        min_delta: 0.01,
+        ..BlendOptions::new(pixel_mult)
+    };
+    img1_dynamic.blend_with_options(&img2_dynamic, &options).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_with_options_result.png").unwrap();
+    ```
+    */
+    fn blend_with_options(
+        &mut self,
+        other: &Self,
+        options: &BlendOptions,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using the function `op`, with the color channels converted from sRGB to linear light before blending and back afterwards.
+
+    Blending in linear light avoids the darkening seen when mixing saturated colors in gamma encoded space. The alpha weighting is also done in linear light. The alpha channel itself is blended exactly as in `blend`.
+
+    When `self` is an rgb image, `gamut_clip` controls how an out of range result is brought back into 0.0..1.0. Luma images are always clamped.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{DynamicChops, GamutClip};
+    use image_blend::pixelops::pixel_add;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    img1_dynamic.blend_linear(&img2_dynamic, pixel_add, GamutClip::DesatToGray, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_linear_result.png").unwrap();
+    ```
+    */
+    fn blend_linear(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        gamut_clip: GamutClip,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using the fallible function `op`, where arg 0 is self and 1 is other.
+
+    Behaves like `blend`, but stops at the first pixel for which `op` returns an error and returns that error.
+
+    Pixels are written as they are blended, so pixels processed before the error (including the color pass if the error happened in the alpha pass) are left modified. Blend into a copy if you need the original on failure.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    Any error returned by `op`
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{DynamicChops, Error};
+
+    let checked_div = |a: f64, b: f64| {
+        if b == 0. {
+            return Err(Error::BlendOp("division by zero".to_string()));
+        }
+        Ok(a / b)
+    };
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    let res = img1_dynamic.try_blend(&img2_dynamic, checked_div, true, false);
+    ```
+    */
+    fn try_blend(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> Result<f64, Error>,
         apply_to_color: bool,
         apply_to_alpha: bool,
     ) -> Result<(), Error>;
@@ -212,159 +333,3013 @@ pub trait DynamicChops {
     fn strip_alpha(
         &mut self
     ) -> Result<(), Error> where Self: std::marker::Sized;
-}
-impl DynamicChops for DynamicImage {
-    fn blend (
+    /**
+    Blend a single channel of `other` into the channel of `self` with the role `channel`, leaving every other channel untouched.
+
+    Color roles are matched to the same role in `other` (or its luma channel if it is a grayscale image) and weighted by `other`'s alpha like `blend`. `Channel::Alpha` blends the two alpha channels without weighting.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `MissingChannel`: `self` does not have a channel with this role (e.g. `Channel::Red` on a luma image, or `Channel::Luma` on an rgb image)
+
+    `NoAlphaChannel`: `channel` is `Channel::Alpha` and `other` does not have an alpha channel
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{Channel, DynamicChops};
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    img1_dynamic.blend_channel_role(&img2_dynamic, pixel_mult, Channel::Green).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_channel_role_result.png").unwrap();
+    ```
+    */
+    fn blend_channel_role(
         &mut self,
         other: &Self,
-        op: fn(f64, f64) -> f64,
+        op: impl Fn(f64, f64) -> f64,
+        channel: Channel,
+    ) -> Result<(), Error>;
+    /**
+    Apply the function `f` to every channel of every pixel of this image.
+
+    Values are normalized to 0.0..1.0 before being passed to `f`, and the output of `f` is clamped to 0.0..1.0 and rounded to the nearest value of the image's type.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected. If `apply_to_alpha` is true but `self` does not have an alpha channel, this option has no effect.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+
+    // Darken the image
+    img1_dynamic.map_pixels(|v| v * 0.5, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_map_pixels_result.png").unwrap();
+    ```
+    */
+    fn map_pixels<F: Fn(f64) -> f64>(
+        &mut self,
+        f: F,
         apply_to_color: bool,
         apply_to_alpha: bool,
-    ) -> Result<(), Error> {
-        match self.color() {
-            ColorType::L8 => blend_step_a(self.as_mut_luma8().unwrap(), other, op, apply_to_color, apply_to_alpha),
-            ColorType::La8 => blend_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, apply_to_color, apply_to_alpha),
-            ColorType::Rgb8 => blend_step_a(self.as_mut_rgb8().unwrap(), other, op, apply_to_color, apply_to_alpha),
-            ColorType::Rgba8 => blend_step_a(self.as_mut_rgba8().unwrap(), other, op, apply_to_color, apply_to_alpha),
-            ColorType::L16 => blend_step_a(self.as_mut_luma16().unwrap(), other, op, apply_to_color, apply_to_alpha),
-            ColorType::La16 => blend_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, apply_to_color, apply_to_alpha),
-            ColorType::Rgb16 => blend_step_a(self.as_mut_rgb16().unwrap(), other, op, apply_to_color, apply_to_alpha),
-            ColorType::Rgba16 => blend_step_a(self.as_mut_rgba16().unwrap(), other, op, apply_to_color, apply_to_alpha),
-            ColorType::Rgb32F => blend_step_a(self.as_mut_rgb32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
-            ColorType::Rgba32F => blend_step_a(self.as_mut_rgba32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
-            _ => Err(Error::UnsupportedType),
+    ) -> Result<(), Error>;
 
-        }
-    }
-    fn get_alpha(
-        &self,
-    ) -> Option<DynamicImage> {
-        let color = self.color();
-        let mut copy = self.clone();
-        match color {
-            ColorType::L8 => get_alpha_step_a(copy.as_mut_luma8().unwrap()),
-            ColorType::La8 => get_alpha_step_a(copy.as_mut_luma_alpha8().unwrap()),
-            ColorType::Rgb8 => get_alpha_step_a(copy.as_mut_rgb8().unwrap()),
-            ColorType::Rgba8 => get_alpha_step_a(copy.as_mut_rgba8().unwrap()),
-            ColorType::L16 => get_alpha_step_a(copy.as_mut_luma16().unwrap()),
-            ColorType::La16 => get_alpha_step_a(copy.as_mut_luma_alpha16().unwrap()),
-            ColorType::Rgb16 => get_alpha_step_a(copy.as_mut_rgb16().unwrap()),
-            ColorType::Rgba16 => get_alpha_step_a(copy.as_mut_rgba16().unwrap()),
-            ColorType::Rgb32F => get_alpha_step_a(copy.as_mut_rgb32f().unwrap()),
-            ColorType::Rgba32F => get_alpha_step_a(copy.as_mut_rgba32f().unwrap()),
-            _ => Err(Error::UnsupportedType),
-        }.ok()?;
-        Some(copy)
-    }
-    fn transplant_alpha(
-            &mut self,
-            other: &Self
-    ) -> Result<(), Error> {
-        match self.color() {
-            ColorType::L8 => transplant_alpha_step_a(self.as_mut_luma8().unwrap(), other),
-            ColorType::La8 => transplant_alpha_step_a(self.as_mut_luma_alpha8().unwrap(), other),
-            ColorType::Rgb8 => transplant_alpha_step_a(self.as_mut_rgb8().unwrap(), other),
-            ColorType::Rgba8 => transplant_alpha_step_a(self.as_mut_rgba8().unwrap(), other),
-            ColorType::L16 => transplant_alpha_step_a(self.as_mut_luma16().unwrap(), other),
-            ColorType::La16 => transplant_alpha_step_a(self.as_mut_luma_alpha16().unwrap(), other),
-            ColorType::Rgb16 => transplant_alpha_step_a(self.as_mut_rgb16().unwrap(), other),
-            ColorType::Rgba16 => transplant_alpha_step_a(self.as_mut_rgba16().unwrap(), other),
-            ColorType::Rgb32F => transplant_alpha_step_a(self.as_mut_rgb32f().unwrap(), other),
-            ColorType::Rgba32F => transplant_alpha_step_a(self.as_mut_rgba32f().unwrap(), other),
-            _ => Err(Error::UnsupportedType),
-        }?;
-        Ok(())
-    }
-    fn set_alpha(
+    /**
+    Invert this image, replacing every value `v` with `1 - v`.
+
+    Unlike `image::imageops::invert` (and `DynamicImage::invert`), the alpha channel can be inverted too.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+
+    img1_dynamic.invert_channels(true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_invert_channels_result.png").unwrap();
+    ```
+    */
+    fn invert_channels(
         &mut self,
-        other: &Self
-    ) -> Result<(), Error> {
-        match self.color() {
-            ColorType::L8 => set_alpha_step_a(self.as_mut_luma8().unwrap(), other),
-            ColorType::La8 => set_alpha_step_a(self.as_mut_luma_alpha8().unwrap(), other),
-            ColorType::Rgb8 => set_alpha_step_a(self.as_mut_rgb8().unwrap(), other),
-            ColorType::Rgba8 => set_alpha_step_a(self.as_mut_rgba8().unwrap(), other),
-            ColorType::L16 => set_alpha_step_a(self.as_mut_luma16().unwrap(), other),
-            ColorType::La16 => set_alpha_step_a(self.as_mut_luma_alpha16().unwrap(), other),
-            ColorType::Rgb16 => set_alpha_step_a(self.as_mut_rgb16().unwrap(), other),
-            ColorType::Rgba16 => set_alpha_step_a(self.as_mut_rgba16().unwrap(), other),
-            ColorType::Rgb32F => set_alpha_step_a(self.as_mut_rgb32f().unwrap(), other),
-            ColorType::Rgba32F => set_alpha_step_a(self.as_mut_rgba32f().unwrap(), other),
-            _ => Err(Error::UnsupportedType),
-        }?;
-        Ok(())
-    }
-    fn strip_alpha(
-            &mut self
-        ) -> Result<(), Error> where Self: std::marker::Sized {
-        match self.color() {
-            ColorType::L8 => self.as_mut_luma8().unwrap().strip_alpha(),
-            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().strip_alpha(),
-            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().strip_alpha(),
-            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().strip_alpha(),
-            ColorType::L16 => self.as_mut_luma16().unwrap().strip_alpha(),
-            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().strip_alpha(),
-            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().strip_alpha(),
-            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().strip_alpha(),
-            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().strip_alpha(),
-            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().strip_alpha(),
-            _ => Err(Error::UnsupportedType),
-        }?;
-        Ok(())
-    }
-}
-fn blend_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: fn(f64, f64) -> f64, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
-where 
-    Pmut: Pixel,
-    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
-    + DerefMut<Target = [Pmut::Subpixel]>
-    + AsMut<[<Pmut as Pixel>::Subpixel]>
-{
-    match other.color() {
-        ColorType::L8 => subject.blend(other.as_luma8().unwrap(), op, apply_to_color, apply_to_alpha),
-        ColorType::La8 => subject.blend(other.as_luma_alpha8().unwrap(), op, apply_to_color, apply_to_alpha),
-        ColorType::Rgb8 => subject.blend(other.as_rgb8().unwrap(), op, apply_to_color, apply_to_alpha),
-        ColorType::Rgba8 => subject.blend(other.as_rgba8().unwrap(), op, apply_to_color, apply_to_alpha),
-        ColorType::L16 => subject.blend(other.as_luma16().unwrap(), op, apply_to_color, apply_to_alpha),
-        ColorType::La16 => subject.blend(other.as_luma_alpha16().unwrap(), op, apply_to_color, apply_to_alpha),
-        ColorType::Rgb16 => subject.blend(other.as_rgb16().unwrap(), op, apply_to_color, apply_to_alpha),
-        ColorType::Rgba16 => subject.blend(other.as_rgba16().unwrap(), op, apply_to_color, apply_to_alpha),
-        ColorType::Rgb32F => subject.blend(other.as_rgb32f().unwrap(), op, apply_to_color, apply_to_alpha),
-        ColorType::Rgba32F => subject.blend(other.as_rgba32f().unwrap(), op, apply_to_color, apply_to_alpha),
-        _ => Err(Error::UnsupportedType),
-    }
-}
-fn get_alpha_step_a<P, Container>(subject: &mut ImageBuffer<P, Container>) -> Result<(), Error>
-where 
-    P: Pixel,
-    Container: DerefMut<Target = [P::Subpixel]> + AsRef<[<P as image::Pixel>::Subpixel]> + Clone,
-{
-    let alpha = subject.get_alpha().ok_or(Error::NoAlphaChannel)?;
-    *subject = alpha;
-    Ok(())
-}
-fn set_alpha_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage) -> Result<(), Error>
-where 
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Apply a levels adjustment, remapping every value `v` to `((v - black) / (white - black)).clamp(0, 1).powf(1 / gamma)`.
+
+    Values at or below `black` become 0, values at or above `white` become 1. A `gamma` above 1 brightens the midtones, below 1 darkens them.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected.
+
+    # Errors
+
+    `InvalidArgument`: `black`, `white` or `gamma` is not finite, `white` is not greater than `black`, or `gamma` is not positive
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+
+    img1_dynamic.levels(0.1, 0.9, 1.2, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_levels_result.png").unwrap();
+    ```
+    */
+    fn levels(
+        &mut self,
+        black: f64,
+        white: f64,
+        gamma: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using a blend strategy chosen at runtime, where arg 0 of `strategy.apply` is self and 1 is other.
+
+    Behaves exactly like `blend`, but takes the operation as a trait object so modes can be stored and selected dynamically (e.g. from a `Vec<Box<dyn BlendStrategy>>`).
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BlendMode, BlendStrategy, DynamicChops};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    let strategy: Box<dyn BlendStrategy> = Box::new(BlendMode::Screen);
+    img1_dynamic.blend_dyn(&img2_dynamic, strategy.as_ref(), true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_dyn_result.png").unwrap();
+    ```
+    */
+    fn blend_dyn(
+        &mut self,
+        other: &Self,
+        strategy: &dyn BlendStrategy,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Clamp this image's alpha channel to the range `min..=max`, where `0.` is fully transparent and `1.` fully opaque.
+
+    Bounds are rounded inwards to the nearest value of the image's type, so no pixel ends up outside the range. If the range is too narrow to contain any value of the type, e.g. `min == max == 0.3` on an 8 bit image, every alpha is set to the value nearest to it instead.
+
+    # Errors
+    `NoAlphaChannel`: `self` does not have an alpha channel
+
+    `InvalidArgument`: `min` or `max` is not finite, or `min` is greater than `max`
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    // Load an image and make sure it is never more than 30% opaque
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    img1_dynamic.clamp_alpha(0., 0.3).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_clamp_alpha_result.png").unwrap();
+    ```
+    */
+    fn clamp_alpha(
+        &mut self,
+        min: f64,
+        max: f64,
+    ) -> Result<(), Error>;
+    /**
+    Multiply this image's alpha channel by `factor`, e.g. `0.7` to show it at 70% of its current opacity.
+
+    Unlike setting alpha to a fixed value, this keeps the relative transparency of every pixel. Results are clamped to the range of the image's type, so a `factor` above `1.` makes the image more opaque without wrapping, and a negative `factor` makes it fully transparent.
+
+    # Errors
+    `NoAlphaChannel`: `self` does not have an alpha channel
+
+    `InvalidArgument`: `factor` is NaN
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    // Load an image and halve its opacity
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    img1_dynamic.scale_alpha(0.5).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_scale_alpha_result.png").unwrap();
+    ```
+    */
+    fn scale_alpha(
+        &mut self,
+        factor: f64,
+    ) -> Result<(), Error>;
+    /**
+    Multiply this image's alpha channel by another image's alpha channel, keeping only the coverage the two have in common.
+
+    Handles type conversion and alpha channel placement automatically.
+
+    # Errors
+    `NoAlphaChannel`: `self` or `other` does not have an alpha channel
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    // Load an image that has an alpha channel
+    let img1_dynamic = open("test_data/1.png").unwrap();
+
+    // Load another image and mask it by the first image's alpha channel.
+    let mut img2_dynamic = open("test_data/2.png").unwrap();
+    img2_dynamic.multiply_alpha(&img1_dynamic).unwrap();
+    img2_dynamic.save("tests_out/doctest_dynamic_multiplyalpha_result.png").unwrap();
+    ```
+    */
+    fn multiply_alpha(
+        &mut self,
+        other: &Self
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using one of the built in `BlendMode`s.
+
+    Equivalent to calling `blend` with `mode.op()`, but `BlendMode::Normal` between two images with the same subpixel type and channel layout takes a fast path: fully opaque pixels of `other` are copied directly instead of being normalized to f64 and back. The output is identical to the float path.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BlendMode, DynamicChops};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    img1_dynamic.blend_mode(&img2_dynamic, BlendMode::Normal, true, true).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_mode_result.png").unwrap();
+    ```
+    */
+    fn blend_mode(
+        &mut self,
+        other: &Self,
+        mode: BlendMode,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Apply the function `f` to every channel of every pixel of this image, passing the pixel's coordinates as well as its value (`f(x, y, value)`).
+
+    Useful for procedural effects such as vignettes, gradients and noise that depend on position rather than on a second image.
+
+    Values are normalized to 0.0..1.0 before being passed to `f`, and the output of `f` is clamped to 0.0..1.0 and rounded to the nearest value of the image's type.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected. If `apply_to_alpha` is true but `self` does not have an alpha channel, this option has no effect.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let (width, height) = (img1_dynamic.width(), img1_dynamic.height());
+
+    // Darken towards the right hand side of the image
+    img1_dynamic.map_pixels_positional(|x, _y, v| v * (1. - f64::from(x) / f64::from(width)), true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_map_pixels_positional_result.png").unwrap();
+    ```
+    */
+    fn map_pixels_positional<F: Fn(u32, u32, f64) -> f64>(
+        &mut self,
+        f: F,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Get the alpha channel of this image as a grayscale, like `get_alpha`, but return a fully opaque (all channels at the maximum value of the input type) image of the same type if there is no alpha channel.
+
+    Images of an unsupported color type return an opaque `Rgba32F` image of the same dimensions.
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img1_rgb = image::DynamicImage::ImageRgb8(img1_dynamic.to_rgb8());
+    let mask = img1_rgb.get_alpha_or_opaque();
+    assert!(mask.as_rgb8().unwrap().pixels().all(|px| px.0 == [255, 255, 255]));
+    ```
+    */
+    #[must_use]
+    fn get_alpha_or_opaque(
+        &self,
+    ) -> Self where Self: std::marker::Sized;
+    /**
+    Blend `other` into `self` with an operation that also receives the index of the channel being written, where arg 0 of `op` is the channel index in `self`, 1 is self and 2 is other.
+
+    Behaves exactly like `blend` otherwise. This allows treating channels differently (e.g. only boosting green) without writing a whole pixel operation. Channel indices are those of `self`'s layout, so for an rgba image 0, 1 and 2 are red, green and blue and 3 is alpha, and for a luma alpha image 0 is luma and 1 is alpha.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::pixel_normal;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    // Screen the green channel, and keep the others as a normal blend
+    let green_screen = |ch: usize, a: f64, b: f64| if ch == 1 { 1. - (1. - a) * (1. - b) } else { pixel_normal(a, b) };
+    img1_dynamic.blend_indexed(&img2_dynamic, green_screen, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_indexed_result.png").unwrap();
+    ```
+    */
+    fn blend_indexed(
+        &mut self,
+        other: &Self,
+        op: impl Fn(usize, f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Convert this image to another color type supported by this crate.
+
+    Color channels are scaled to the bit depth of `target`. Converting to a type with an alpha channel fills it with the maximum value, converting to a type without one drops it. Converting rgb to luma uses the `image` crate's luminance weights.
+
+    # Errors
+
+    `UnsupportedType`: `target` is not a color type supported by this crate
+
+    # Examples
+
+    ```
+    use image::{open, ColorType};
+    use image_blend::DynamicChops;
+
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img1_rgb16 = img1_dynamic.convert_to(ColorType::Rgb16).unwrap();
+    assert_eq!(img1_rgb16.color(), ColorType::Rgb16);
+    ```
+    */
+    fn convert_to(
+        &self,
+        target: ColorType,
+    ) -> Result<Self, Error> where Self: std::marker::Sized;
+    /**
+    Blend a constant value into every channel of this image, as if blending with a solid image where every channel is `value`, where arg 0 of `op` is self and 1 is `value`.
+
+    `value` is normalized (0.0..1.0). This is cheaper than building a solid image to blend with, and useful for operations against a fixed pivot such as a contrast boost around 0.5.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected. If `apply_to_alpha` is true but `self` does not have an alpha channel, this option has no effect.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+
+    // Boost contrast around the midtones
+    img1_dynamic.blend_against_constant(0.5, |a, pivot| pivot + (a - pivot) * 1.5, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_against_constant_result.png").unwrap();
+    ```
+    */
+    fn blend_against_constant(
+        &mut self,
+        value: f64,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Premultiply this image's color channels by its alpha channel in place.
+
+    Premultiplied images store `color * alpha` instead of `color`, which is what most GPU blending pipelines expect. Nothing in the image's type marks it as premultiplied, so keep track of it yourself, and note that the other operations in this crate expect straight (non-premultiplied) alpha.
+
+    # Errors
+    `NoAlphaChannel`: `self` does not have an alpha channel
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    img1_dynamic.premultiply().unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_premultiply_result.png").unwrap();
+    ```
+    */
+    fn premultiply(
+        &mut self,
+    ) -> Result<(), Error>;
+    /**
+    Return a copy of this image with its color channels premultiplied by alpha, leaving `self` untouched.
+
+    See `premultiply` for what this means. The returned image has the same type as `self`, and is premultiplied by convention only. Images without an alpha channel are fully opaque, so they are returned unchanged.
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let premultiplied = img1_dynamic.into_premultiplied();
+    premultiplied.save("tests_out/doctest_dynamic_into_premultiplied_result.png").unwrap();
+    ```
+    */
+    #[must_use]
+    #[allow(clippy::wrong_self_convention)]
+    fn into_premultiplied(
+        &self,
+    ) -> Self where Self: std::marker::Sized;
+    /**
+    Blend a virtual image generated by `gen` into `self`, where `gen(x, y)` returns the normalized rgba value of the virtual image at each pixel, and arg 0 of `op` is self and 1 is the generated value.
+
+    Behaves like `blend` with the generated image as `other`, including weighting by its alpha, but without allocating a second image. Useful for procedural overlays such as gradients and noise. Luma images use the red channel of the generated value.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::pixel_screen;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let width = f64::from(img1_dynamic.width());
+    // Screen a horizontal gradient that fades out towards the right
+    let gradient = |x: u32, _y: u32| [1., 0.5, 0., 1. - f64::from(x) / width];
+    img1_dynamic.blend_generated(gradient, pixel_screen, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_generated_result.png").unwrap();
+    ```
+    */
+    fn blend_generated<G: Fn(u32, u32) -> [f64; 4]>(
+        &mut self,
+        gen: G,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Split this image into a color image without an alpha channel and a grayscale image of its alpha channel.
+
+    The color image has the same bit depth as `self` (e.g. `Rgba16` becomes `Rgb16`). The alpha image is `L8` for 8 bit images and `L16` otherwise. Use `join_alpha` to combine them again.
+
+    # Errors
+    `NoAlphaChannel`: `self` does not have an alpha channel
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{join_alpha, DynamicChops};
+
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let (mut color, alpha) = img1_dynamic.split_alpha().unwrap();
+
+    // Edit the color without touching the alpha, then put them back together
+    color.invert_channels(true, false).unwrap();
+    let joined = join_alpha(&color, &alpha).unwrap();
+    joined.save("tests_out/doctest_dynamic_split_alpha_result.png").unwrap();
+    ```
+    */
+    fn split_alpha(
+        &self,
+    ) -> Result<(Self, Self), Error> where Self: std::marker::Sized;
+    /**
+    Return a copy of this image with its color replaced by its luminance, computed with `coefficients`.
+
+    The copy has the same type as `self`, with every color channel set to the luminance and alpha untouched. Luminance is computed directly on the stored (gamma encoded) values, like most editors' desaturate. Luma images are returned unchanged.
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{DynamicChops, LumaCoefficients};
+
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let gray = img1_dynamic.to_luminance(LumaCoefficients::REC_601);
+    gray.save("tests_out/doctest_dynamic_to_luminance_result.png").unwrap();
+    ```
+    */
+    #[must_use]
+    fn to_luminance(
+        &self,
+        coefficients: LumaCoefficients,
+    ) -> Self where Self: std::marker::Sized;
+    /**
+    Blend `other` into `self` as if `other` were padded with transparent pixels to the size of `self`, where arg 0 of `op` is self and 1 is other.
+
+    `anchor` decides where `other` is placed within `self` (e.g. `Anchor::Center` to center an overlay). Pixels of `self` outside of `other` are left untouched, including their alpha, and parts of `other` that fall outside of `self` are ignored. Otherwise behaves exactly like `blend`.
+
+    # Errors
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{Anchor, DynamicChops};
+    use image_blend::pixelops::pixel_screen;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let small_dynamic = open("test_data/2.png").unwrap().thumbnail(64, 64);
+
+    // Screen a smaller copy of the second image over the middle of the first
+    img1_dynamic.blend_padded(&small_dynamic, pixel_screen, Anchor::Center, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_padded_result.png").unwrap();
+    ```
+    */
+    fn blend_padded(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        anchor: Anchor,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend each image yielded by `frames` into `self` in turn, where arg 0 of `op` is self and 1 is the frame, and return the number of frames blended.
+
+    Useful for temporal effects over a video stream, such as motion blur with `pixel_average` or a max-hold with `pixel_lighter`, without collecting the frames first. `on_mismatch` decides what happens to frames with different dimensions to `self`.
+
+    # Errors
+
+    `DimensionMismatch`: a frame has different dimensions to `self` and `on_mismatch` is `MismatchPolicy::Error`. Frames before it have already been blended.
+
+    `UnsupportedBlend`: `self` is a luma image and a frame is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{DynamicChops, MismatchPolicy};
+    use image_blend::pixelops::pixel_lighter;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let frames = vec![open("test_data/2.png").unwrap(), open("test_data/1.png").unwrap().fliph()];
+
+    let blended = img1_dynamic.blend_stream(frames.into_iter(), pixel_lighter, MismatchPolicy::Skip, true, false).unwrap();
+    assert_eq!(blended, 2);
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_stream_result.png").unwrap();
+    ```
+    */
+    fn blend_stream<I: Iterator<Item = DynamicImage>>(
+        &mut self,
+        frames: I,
+        op: impl Fn(f64, f64) -> f64,
+        on_mismatch: MismatchPolicy,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<usize, Error>;
+    /**
+    Undo `premultiply`, dividing this image's color channels by its alpha channel in place.
+
+    Use this to convert a premultiplied image (e.g. read back from a GPU) to the straight alpha the other operations in this crate expect. Fully transparent pixels have no recoverable color and are set to black.
+
+    # Errors
+    `NoAlphaChannel`: `self` does not have an alpha channel
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    img1_dynamic.premultiply().unwrap();
+    img1_dynamic.unpremultiply().unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_unpremultiply_result.png").unwrap();
+    ```
+    */
+    fn unpremultiply(
+        &mut self,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` by replacing one component of each pixel's color with `other`'s, after splitting both colors into hue, saturation and lightness or value.
+
+    Unlike `blend`, this works on whole pixels rather than channel by channel. `model` picks HSL or HSV: a `ComponentBlend::Value` blend in HSV keeps `self`'s hue and saturation with `other`'s value (its largest channel), which keeps saturated colors saturated where an HSL lightness blend would wash them out towards white. A luma `other` is treated as gray. The result is weighted by `other`'s alpha like `blend`, and the alpha channel is left untouched.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `MissingChannel`: `self` is a luma image, which has no hue or saturation to blend
+
+    `UnsupportedType`: `self` or `other` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{DynamicChops, ColorModel, ComponentBlend};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    // Take the value of the second image, keeping the hue and saturation of the first
+    img1_dynamic.blend_components(&img2_dynamic, ComponentBlend::Value, ColorModel::Hsv).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_components_result.png").unwrap();
+    ```
+    */
+    fn blend_components(
+        &mut self,
+        other: &Self,
+        component: ComponentBlend,
+        model: ColorModel,
+    ) -> Result<(), Error>;
+    /**
+    Dissolve `other` into `self`: each pixel either keeps `self`'s value or takes `other`'s, chosen at random with a probability of `other`'s alpha.
+
+    Where `other` has an alpha of 0.3, roughly 30% of pixels take `other`'s color, giving a dithered transparency instead of a smooth mix. Pixels taken from `other` become fully opaque if `self` has an alpha channel. An `other` without an alpha channel replaces every pixel.
+
+    The choice for each pixel depends only on `seed` and the pixel's position, so the same seed always gives the same output.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    img1_dynamic.dissolve(&img2_dynamic, 42).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_dissolve_result.png").unwrap();
+    ```
+    */
+    fn dissolve(
+        &mut self,
+        other: &Self,
+        seed: u64,
+    ) -> Result<(), Error>;
+    /**
+    Blend each image in `others` into `self` with `op`, and replace `self` with the average of the results weighted by each image's weight.
+
+    This is a weighted average of several blends at once, e.g. for exposure fusion or merging brackets with `pixel_normal`. Each pixel's weight is also scaled by the other image's alpha like `blend`, and the weighted sum is divided by the total weight of that pixel, so weights don't need to sum to 1. Pixels where every other image is transparent keep their value. Sums are accumulated in f64 with a pairwise reduction so many images don't lose precision. The alpha channel of `self` is left untouched.
+
+    `op` must be `Sync`, as the pixels are averaged on several threads with the `rayon` feature.
+
+    # Errors
+
+    `NoImages`: `others` is empty
+
+    `DimensionMismatch`: an image in `others` has different dimensions to `self`
+
+    `InvalidArgument`: a weight is negative or not finite, or all weights are zero
+
+    `UnsupportedBlend`: `self` is a luma image and an image in `others` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::pixel_normal;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let img3_dynamic = img2_dynamic.fliph();
+
+    img1_dynamic.blend_weighted(&[(img2_dynamic, 0.75), (img3_dynamic, 0.25)], pixel_normal).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_weighted_result.png").unwrap();
+    ```
+    */
+    fn blend_weighted(
+        &mut self,
+        others: &[(DynamicImage, f64)],
+        op: impl Fn(f64, f64) -> f64 + Sync,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into a copy of `self` using the function `op`, and return the result converted to `target`. `self` is left unchanged.
+
+    The blend happens at `self`'s type exactly as `blend` would, and the conversion only afterwards, so e.g. two 8 bit images blended into an `Rgb16` result give the 8 bit result scaled up to 16 bits. See `convert_to` for how the conversion handles channels.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    `UnsupportedType`: `self`, `other` or `target` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::{open, ColorType};
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::pixel_mult;
+
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    let blended = img1_dynamic.blend_as(&img2_dynamic, ColorType::Rgb16, pixel_mult, true, false).unwrap();
+    assert_eq!(blended.color(), ColorType::Rgb16);
+    blended.save("tests_out/doctest_dynamic_blend_as_result.png").unwrap();
+    ```
+    */
+    fn blend_as(
+        &self,
+        other: &Self,
+        target: ColorType,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<Self, Error> where Self: std::marker::Sized;
+    /**
+    Composite this image source-over onto a solid background color `bg` (normalized `[r, g, b]`), and return the opaque rgb result.
+
+    This is the usual way to remove transparency: each pixel's color is mixed with `bg` by its alpha. The result keeps the bit depth of `self` (`Rgb8`, `Rgb16` or `Rgb32F`). Images without an alpha channel are simply converted to rgb. Values of `bg` outside 0.0..1.0 are clamped.
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let flattened = img1_dynamic.flatten_onto_color([1., 1., 1.]);
+    flattened.save("tests_out/doctest_dynamic_flatten_onto_color_result.png").unwrap();
+    ```
+    */
+    #[must_use]
+    fn flatten_onto_color(
+        &self,
+        bg: [f64; 3],
+    ) -> Self where Self: std::marker::Sized;
+    /**
+    Composite this image source-over onto `background`, and return the opaque rgb result.
+
+    The same as `flatten_onto_color`, but with a different backdrop color for every pixel, e.g. to place a transparent foreground onto a photo. `background` is treated as opaque: its alpha channel, if it has one, is ignored. The result keeps the bit depth of `self` (`Rgb8`, `Rgb16` or `Rgb32F`).
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `background` have different dimensions
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    let flattened = img1_dynamic.flatten_onto(&img2_dynamic).unwrap();
+    flattened.save("tests_out/doctest_dynamic_flatten_onto_result.png").unwrap();
+    ```
+    */
+    fn flatten_onto(
+        &self,
+        background: &Self,
+    ) -> Result<Self, Error> where Self: std::marker::Sized;
+    /**
+    Threshold this image, snapping every value to 0 if it is below `cutoff` and to the maximum otherwise.
+
+    `cutoff` is normalized (0.0..1.0). Each channel is thresholded on its own, so an rgb image ends up with at most 8 colors, and a luma image is converted to pure black and white.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+
+    img1_dynamic.threshold(0.5, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_threshold_result.png").unwrap();
+    ```
+    */
+    fn threshold(
+        &mut self,
+        cutoff: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using the function `op` within `tile` only, where arg 0 is self and 1 is other.
+
+    `other` is the same size as `self` and in the same coordinate space, so only the pixels of both images within `tile` are read, and only the pixels of `self` within `tile` are written. This suits tiled processing, where each tile can be blended separately without cropping `other`. Otherwise behaves exactly like `blend`.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `InvalidArgument`: `tile` does not lie entirely within `self`
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{DynamicChops, Rect};
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    let tile = Rect { x: 0, y: 0, width: 64, height: 64 };
+    img1_dynamic.blend_tile(&img2_dynamic, pixel_mult, tile, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_tile_result.png").unwrap();
+    ```
+    */
+    fn blend_tile(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        tile: Rect,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using the function `op`, but only within `selection`, where arg 0 is self and 1 is other.
+
+    `selection` is a mask the same size as `self`, where a pixel is selected if its first channel is above 0.5 (e.g. white on black). This allows arbitrary, non-rectangular selections such as circles or paths rasterized into an image. Unselected pixels of `self` are left untouched, and selected pixels are blended exactly as by `blend`.
+
+    # Errors
+
+    `DimensionMismatch`: `self`, `other` and `selection` do not all have the same dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    `UnsupportedType`: `self` is of an unsupported color type. `selection` can be of any color type, as only its first channel is read
+
+    # Examples
+
+    ```
+    use image::{open, GrayImage, Luma};
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    // Select the left half of the image
+    let (width, height) = (img1_dynamic.width(), img1_dynamic.height());
+    let selection = GrayImage::from_fn(width, height, |x, _| if x < width / 2 { Luma([255]) } else { Luma([0]) });
+    let selection = image::DynamicImage::ImageLuma8(selection);
+    img1_dynamic.blend_selection(&img2_dynamic, pixel_mult, &selection, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_selection_result.png").unwrap();
+    ```
+    */
+    fn blend_selection(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        selection: &Self,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using the function `op`, with the top left corner of `other` placed at (`x`, `y`) in `self`, and only iterating over the part of `other` that is not fully transparent. Arg 0 of `op` is self and 1 is other.
+
+    Meant for stamping sprites: the bounding box of `other`'s non-transparent pixels is found first, and only that rectangle is blended, so large transparent borders cost nothing beyond the scan. Parts of `other` outside of `self` (including at negative positions) are ignored. Since fully transparent pixels never change the color of `self`, the result is the same as blending all of `other` at that position, unless `apply_to_alpha` is set.
+
+    # Errors
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    `UnsupportedType`: `other` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::pixel_normal;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    img1_dynamic.blend_trimmed(&img2_dynamic, pixel_normal, -20, 10, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_trimmed_result.png").unwrap();
+    ```
+    */
+    fn blend_trimmed(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        x: i64,
+        y: i64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using `op_opaque` where `other` is mostly opaque and `op_transparent` where it is mostly transparent. Arg 0 of each op is self and 1 is other.
+
+    A pixel uses `op_opaque` if the alpha of `other` (in the range 0.0..1.0) is at least `alpha_cut`, and `op_transparent` otherwise. Each pixel is then blended exactly as by `blend` with the chosen op, including weighting by `other`'s alpha. This allows knockout style effects, e.g. multiplying under the solid body of a layer while screening its soft edges. If `other` has no alpha channel, every pixel uses `op_opaque`.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::{pixel_mult, pixel_screen};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    img1_dynamic.blend_threshold_op(&img2_dynamic, pixel_mult, pixel_screen, 0.5, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_threshold_op_result.png").unwrap();
+    ```
+    */
+    fn blend_threshold_op(
+        &mut self,
+        other: &Self,
+        op_opaque: impl Fn(f64, f64) -> f64,
+        op_transparent: impl Fn(f64, f64) -> f64,
+        alpha_cut: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using the function `op`, where arg 0 is self and 1 is other, calling `progress` after each row with the fraction of the image done so far.
+
+    Otherwise the same as `blend`. Meant for keeping a progress bar up to date while blending very large images: `progress` receives values increasing up to exactly 1.0, which it is always called with last (even for an empty image). Blending row by row costs a little more than `blend`, so prefer `blend` when progress is not needed.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    img1_dynamic.blend_with_progress(&img2_dynamic, pixel_mult, |done| println!("{:.0}%", done * 100.), true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_with_progress_result.png").unwrap();
+    ```
+    */
+    fn blend_with_progress(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        progress: impl FnMut(f64),
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other`'s alpha channel into this image's alpha channel using the function `op`, where arg 0 is self's alpha and 1 is other's. Color is never read or written.
+
+    Unlike `blend` with only `apply_to_alpha`, the color channels of the two images do not need to be compatible, so e.g. an rgba mask can be intersected with the alpha of a luma alpha image.
+
+    # Errors
+    `NoAlphaChannel`: `self` or `other` does not have an alpha channel
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::pixel_mult;
+
+    // Load an image that has an alpha channel
+    let img1_dynamic = open("test_data/1.png").unwrap();
+
+    // Load another image as luma alpha, whose color is incompatible with the first
+    let mut img2_dynamic = open("test_data/2.png").unwrap();
+    img2_dynamic = img2_dynamic.into_luma_alpha8().into();
+
+    // Intersect its alpha with the first image's
+    img2_dynamic.blend_alpha_only(&img1_dynamic, pixel_mult).unwrap();
+    img2_dynamic.save("tests_out/doctest_dynamic_blend_alpha_only_result.png").unwrap();
+    ```
+    */
+    fn blend_alpha_only(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` with the exact integer operation `op`, working directly on the stored subpixel values without converting them to floats.
+
+    Meant for tools such as pixel art editors that need results to match integer arithmetic bit for bit, e.g. `IntOp::Add` of 200 and 100 on 8 bit images is exactly 255, the same as `u8::saturating_add`. Channels are matched up as by `blend`, but `other`'s alpha channel does not weight the result: each selected channel is simply `op` of the two values.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedType`: `self` and `other` are not integer images with the same bit depth
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{DynamicChops, IntOp};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap().to_rgba8().into();
+
+    img1_dynamic.blend_saturating_int(&img2_dynamic, IntOp::Add, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_saturating_int_result.png").unwrap();
+    ```
+    */
+    fn blend_saturating_int(
+        &mut self,
+        other: &Self,
+        op: IntOp,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` exactly as `blend` does, then count how many pixels of `self` ended up fully transparent or fully opaque.
+
+    Meant for validating composites, e.g. catching an alpha blend that accidentally knocked out the whole image. Pixels that were already fully transparent or opaque before the blend are counted too.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    let coverage = img1_dynamic.blend_alpha_coverage(&img2_dynamic, pixel_mult, true, true).unwrap();
+    println!("{} pixels are now fully transparent", coverage.transparent);
+    ```
+    */
+    fn blend_alpha_coverage(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<AlphaCoverage, Error>;
+    /**
+    Combine the tangent space normal map `detail` into this normal map, e.g. to add fine surface detail to a base material.
+
+    Per channel blending can't do this correctly: each pixel is decoded from rgb to a normal vector, the two normals are combined with `method`, and the result is normalized and encoded back to rgb. Where `detail` is flat (`(0.5, 0.5, 1.0)`) this normal map is kept as it is. The result is weighted by `detail`'s alpha like `blend`, and the alpha channel is left untouched.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `detail` have different dimensions
+
+    `MissingChannel`: `self` or `detail` is a luma image, which can't hold a normal
+
+    `UnsupportedType`: `self` or `detail` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::ColorType;
+    use image_blend::{gradient, solid_color, Direction, DynamicChops, NormalBlend};
+
+    // A flat surface, and a detail map that tilts from left to right
+    let mut img1_dynamic = solid_color(64, 64, ColorType::Rgb8, &[0.5, 0.5, 1.]).unwrap();
+    let img2_dynamic = gradient(64, 64, Direction::Horizontal, ColorType::Rgb8, &[0.2, 0.5, 0.9], &[0.8, 0.5, 0.9]).unwrap();
+    img1_dynamic.blend_normal_maps(&img2_dynamic, NormalBlend::Reoriented).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_normal_maps_result.png").unwrap();
+    ```
+    */
+    fn blend_normal_maps(
+        &mut self,
+        detail: &DynamicImage,
+        method: NormalBlend,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using the function `op`, with the opacity of each pixel read from `opacity_map`, where arg 0 is self and 1 is other.
+
+    The first channel of `opacity_map` is normalized to 0.0..1.0 whatever its type, so an existing grayscale asset can be reused at any bit depth, and a 16 bit or float map gives a smoother falloff than an 8 bit one. The opacity of each pixel scales the color blend like `BlendOptions::opacity` does, on top of `other`'s alpha: black leaves `self` untouched and white blends exactly as by `blend`. Like `BlendOptions::opacity`, it does not affect the alpha channel.
+
+    # Errors
+
+    `DimensionMismatch`: `self`, `other` and `opacity_map` do not all have the same dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    `UnsupportedType`: `opacity_map` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::{open, ColorType};
+    use image_blend::{gradient, Direction, DynamicChops};
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    // Fade the blend in from left to right, using a 16 bit map
+    let (width, height) = (img1_dynamic.width(), img1_dynamic.height());
+    let opacity_map = gradient(width, height, Direction::Horizontal, ColorType::L16, &[0.], &[1.]).unwrap();
+    img1_dynamic.blend_opacity_map(&img2_dynamic, pixel_mult, &opacity_map, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_opacity_map_result.png").unwrap();
+    ```
+    */
+    fn blend_opacity_map(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        opacity_map: &Self,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Map the color of every pixel of this image through a 1D lookup table, such as a set of curves.
+
+    Each channel is looked up in its own column of `lut`: the entries are spread evenly over 0.0..1.0, so the first entry is the output for 0.0 and the last for 1.0, and values in between are interpolated linearly. All values are normalized. Luma images are treated as gray, and take the luminance (Rec. 709) of the looked up color. The alpha channel is left untouched.
+
+    # Errors
+
+    `InvalidArgument`: `lut` is empty
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    // Warm the image: lift red, lower blue
+    let lut = [[0.1, 0., 0.], [0.65, 0.5, 0.4], [1., 1., 0.9]];
+    img1_dynamic.apply_lut_1d(&lut).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_apply_lut_1d_result.png").unwrap();
+    ```
+    */
+    fn apply_lut_1d(
+        &mut self,
+        lut: &[[f64; 3]],
+    ) -> Result<(), Error>;
+
+    /**
+    Map the color of every pixel of this image through the 3D lookup table `lut`, e.g. a color grade loaded from a `.cube` file.
+
+    Colors between the table's grid points are interpolated trilinearly. Luma images are treated as gray, and take the luminance (Rec. 709) of the looked up color. The alpha channel is left untouched.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{DynamicChops, Lut3D};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    // Desaturate halfway by mixing each color with its gray
+    let mut table = Lut3D::identity(17).table().to_vec();
+    for color in &mut table {
+        let gray = (color[0] + color[1] + color[2]) / 3.;
+        *color = color.map(|v| (v + gray) / 2.);
+    }
+    let lut = Lut3D::new(17, table).unwrap();
+    img1_dynamic.apply_lut_3d(&lut).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_apply_lut_3d_result.png").unwrap();
+    ```
+    */
+    fn apply_lut_3d(
+        &mut self,
+        lut: &Lut3D,
+    ) -> Result<(), Error>;
+    /**
+    Composite `sprites` onto this image in order, each a sprite image and the position of its top left corner in `self`, so later sprites are drawn over earlier ones.
+
+    Each sprite is stamped source-over like `blend_trimmed` with `pixel_normal`: fully transparent borders are skipped, parts outside of `self` (including at negative positions) are clipped, and the color is weighted by the sprite's alpha. If both `self` and a sprite have an alpha channel, `self`'s alpha is composited too (`a + b - a * b`), so sprites drawn onto a transparent canvas stay opaque. The color type of `self` is only dispatched on once, so this is cheaper than calling `blend_trimmed` for every sprite.
+
+    # Errors
+
+    `UnsupportedBlend`: `self` is a luma image and a sprite is an rgb image. Sprites before it have already been drawn.
+
+    `UnsupportedType`: `self` or a sprite is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::{ColorType, DynamicImage};
+    use image_blend::{solid_color, DynamicChops};
+
+    let mut canvas = DynamicImage::new_rgba8(64, 64);
+    let red = solid_color(32, 32, ColorType::Rgba8, &[1., 0., 0., 1.]).unwrap();
+    let blue = solid_color(32, 32, ColorType::Rgba8, &[0., 0., 1., 0.5]).unwrap();
+
+    canvas.composite_sprites(&[(red, 8, 8), (blue, 24, 24)]).unwrap();
+    canvas.save("tests_out/doctest_dynamic_composite_sprites_result.png").unwrap();
+    ```
+    */
+    fn composite_sprites(
+        &mut self,
+        sprites: &[(DynamicImage, i64, i64)],
+    ) -> Result<(), Error>;
+    /**
+    Set this image's alpha channel from the edges in its luminance, for matting line art: edges become opaque and flat areas transparent.
+
+    Images without an alpha channel are converted to the matching type with one first, e.g. `Rgb8` to `Rgba8`.
+
+    Edge strength is the Sobel gradient magnitude of the Rec. 709 luminance (of the color channels as stored), normalized so a hard step from black to white is `1.`. Pixels past the border repeat the edge pixels, so the border of the image is not an edge. A pixel whose edge strength reaches `threshold` becomes fully opaque, and weaker edges fade linearly to transparent. The previous alpha is ignored.
+
+    # Errors
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    `InvalidArgument`: `threshold` is not in the range `0.0 < threshold <= 1.0`
+
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    // Keep only the outlines of an image
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    img1_dynamic.alpha_from_edges(0.25).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_alpha_from_edges_result.png").unwrap();
+    ```
+    */
+    fn alpha_from_edges(
+        &mut self,
+        threshold: f64,
+    ) -> Result<(), Error>;
+    /**
+    Check whether `other` can be blended into `self`, without blending.
+
+    Checks the dimensions and color types in the same order as `blend`, so this returns the error `blend` would. Use `compatibility_report` for every finding at once, including whether the alpha channel can be blended.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedType`: `self` or `other` is of an unsupported color type
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::{open, DynamicImage};
+    use image_blend::DynamicChops;
+
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    assert!(img1_dynamic.compatibility(&img2_dynamic).is_ok());
+
+    // An rgb image cannot be blended into a luma image
+    let img1_luma = DynamicImage::ImageLuma8(img1_dynamic.to_luma8());
+    assert!(img1_luma.compatibility(&img2_dynamic).is_err());
+    ```
+    */
+    fn compatibility(
+        &self,
+        other: &Self,
+    ) -> Result<(), Error>;
+    /// Report on whether `other` can be blended into `self` and why not, without blending. See `CompatibilityReport`.
+    fn compatibility_report(
+        &self,
+        other: &Self,
+    ) -> CompatibilityReport;
+    /**
+    Blend `other` into `self` using the function `op`, only in the shadows, midtones or highlights of `self`, where arg 0 is self and 1 is other.
+
+    The opacity of each pixel is a smooth bell curve of `self`'s luminance (Rec. 709, of the color channels as stored) centered on `range`, as described by `TonalRange`. Pixels at the center of the range are blended exactly as by `blend`, and the blend fades out smoothly away from it, so there are no hard edges between ranges. Like `blend_opacity_map`, the opacity scales the color blend on top of `other`'s alpha and does not affect the alpha channel.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{DynamicChops, TonalRange};
+    use image_blend::pixelops::pixel_mult;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    // Deepen the shadows, leaving the highlights alone
+    img1_dynamic.blend_tonal(&img2_dynamic, pixel_mult, TonalRange::Shadows, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_tonal_result.png").unwrap();
+    ```
+    */
+    fn blend_tonal(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        range: TonalRange,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using the function `op` as if `other` were the layer below `self`, where arg 0 is other (the base) and 1 is self (the top).
+
+    The result is what `blend` gives when blending `self` into a copy of `other`, but it is stored in `self` without cloning either image, and `other` may have fewer channels than `self` as with `blend`. This is the natural way to insert a background layer: with `pixel_normal`, opaque pixels of `self` stay on top, transparent ones show `other`, and translucent ones are composited over it. `self`'s alpha weights the color as `other`'s does in `blend`.
+
+    # Arguments
+
+    With `apply_to_color` false the color is left untouched. With `apply_to_alpha` the alpha channel is blended as `op(other, self)`, so use `pixel_screen` for the matching source-over alpha. If `self` or `other` does not have an alpha channel, `apply_to_alpha` has no effect.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::{pixel_normal, pixel_screen};
+
+    // A layer with transparent areas
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+
+    // Put a background underneath it
+    let img2_dynamic = open("test_data/2.png").unwrap();
+    img1_dynamic.blend_under(&img2_dynamic, pixel_normal, true, false).unwrap();
+    img1_dynamic.blend_under(&img2_dynamic, pixel_screen, false, true).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_under_result.png").unwrap();
+    ```
+    */
+    fn blend_under(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Replace the pixels of `self` whose color is within `tolerance` of `target` with the corresponding pixels of `other`.
+
+    `target` is a normalized rgb color (0.0..1.0), and a pixel matches if the euclidean distance from its color channels to `target` is at most `tolerance`, so a `tolerance` of 0.0 only matches `target` exactly. Luma pixels are compared as the gray with all three channels equal to their value. The alpha of `self` is not compared.
+
+    Matching pixels take `other`'s color, and its alpha if both images have an alpha channel, as is: `other`'s alpha does not weight the copy. Other pixels are left untouched. This is a keying primitive, e.g. to swap a solid background for another image.
+
+    # Errors
+
+    `InvalidArgument`: `tolerance` is negative or NaN
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let img2_dynamic = open("test_data/2.png").unwrap();
+
+    // Replace the white areas, and those close to white
+    img1_dynamic.replace_color(&img2_dynamic, [1., 1., 1.], 0.1).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_replace_color_result.png").unwrap();
+    ```
+    */
+    fn replace_color(
+        &mut self,
+        other: &Self,
+        target: [f64; 3],
+        tolerance: f64,
+    ) -> Result<(), Error>;
+    /**
+    Blend `other` into `self` using the function `op`, skipping the pixels of `other` whose color is `key`, where arg 0 is self and 1 is other.
+
+    For legacy sprites that mark transparency with a magic color, such as pure magenta, instead of an alpha channel. `key` is a normalized rgb color (0.0..1.0), and a pixel of `other` is keyed out if each of its color channels is within half an 8 bit level of `key`, so the key survives conversion between bit depths. Luma pixels are compared as the gray with all three channels equal to their value. Keyed pixels leave `self` untouched, including its alpha, and the rest are blended exactly as by `blend`.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+
+    # Examples
+
+    ```
+    use image::{open, DynamicImage, Rgb, RgbImage};
+    use image_blend::DynamicChops;
+    use image_blend::pixelops::pixel_normal;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+
+    // A sprite of a white square on a magenta background
+    let (width, height) = (img1_dynamic.width(), img1_dynamic.height());
+    let sprite = DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+        if (width / 4..width * 3 / 4).contains(&x) && (height / 4..height * 3 / 4).contains(&y) { Rgb([255, 255, 255]) } else { Rgb([255, 0, 255]) }
+    }));
+    img1_dynamic.blend_color_keyed(&sprite, [1., 0., 1.], pixel_normal, true, false).unwrap();
+    img1_dynamic.save("tests_out/doctest_dynamic_blend_color_keyed_result.png").unwrap();
+    ```
+    */
+    fn blend_color_keyed(
+        &mut self,
+        other: &Self,
+        key: [f64; 3],
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Set an image's alpha channel from another image's alpha channel, resizing `other` to the size of `self` with `filter` first.
+
+    For masks authored at a different resolution to the image they are applied to. Images of the same size are transplanted as by `transplant_alpha`, without resampling.
+
+    # Errors
+    `NoAlphaChannel`: `self` or `other` does not have an alpha channel
+
+    `DimensionMismatch`: `other` is empty, so there is nothing to resize
+
+    # Examples
+
+    ```
+    use image::imageops::FilterType;
+    use image::open;
+    use image_blend::DynamicChops;
+
+    // Load an image that has an alpha channel, at half size
+    let img1_dynamic = open("test_data/1.png").unwrap();
+    let img1_half = img1_dynamic.thumbnail(img1_dynamic.width() / 2, img1_dynamic.height() / 2);
+
+    // Load another image and set its alpha channel to the first image's, scaled back up.
+    let mut img2_dynamic = open("test_data/2.png").unwrap();
+    img2_dynamic.transplant_alpha_scaled(&img1_half, FilterType::Triangle).unwrap();
+    img2_dynamic.save("tests_out/doctest_dynamic_transplant_alpha_scaled_result.png").unwrap();
+    ```
+    */
+    fn transplant_alpha_scaled(
+        &mut self,
+        other: &Self,
+        filter: FilterType,
+    ) -> Result<(), Error>;
+    /**
+    Return a binary `L8` mask of where this frame has changed since `previous`: 255 where the difference in luminance is greater than `threshold`, 0 elsewhere.
+
+    Meant for surveillance and time lapse footage, e.g. to find moving objects or to only blend the parts of a frame that changed. Luminance is computed from the stored values with `LumaCoefficients::REC_709`, and `threshold` is normalized (0.0..1.0), so a `threshold` of 0.0 marks every pixel that changed at all. Alpha is ignored. The frames may have different color types.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `previous` have different dimensions
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::DynamicChops;
+
+    let previous = open("test_data/1.png").unwrap();
+    let current = open("test_data/2.png").unwrap();
+
+    let mask = current.motion_mask(&previous, 0.1).unwrap();
+    mask.save("tests_out/doctest_dynamic_motion_mask_result.png").unwrap();
+    ```
+    */
+    fn motion_mask(
+        &self,
+        previous: &Self,
+        threshold: f64,
+    ) -> Result<Self, Error> where Self: std::marker::Sized;
+}
+impl DynamicChops for DynamicImage {
+    fn blend (
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_op_step_a(self.as_mut_luma8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_op_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_op_step_a(self.as_mut_rgb8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_op_step_a(self.as_mut_rgba8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_op_step_a(self.as_mut_luma16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_op_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_op_step_a(self.as_mut_rgb16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_op_step_a(self.as_mut_rgba16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_op_step_a(self.as_mut_rgb32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_op_step_a(self.as_mut_rgba32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_with_options(
+        &mut self,
+        other: &Self,
+        options: &BlendOptions,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => blend_step_a(self.as_mut_luma8().unwrap(), other, options),
+            ColorType::La8 => blend_step_a(self.as_mut_luma_alpha8().unwrap(), other, options),
+            ColorType::Rgb8 => blend_step_a(self.as_mut_rgb8().unwrap(), other, options),
+            ColorType::Rgba8 => blend_step_a(self.as_mut_rgba8().unwrap(), other, options),
+            ColorType::L16 => blend_step_a(self.as_mut_luma16().unwrap(), other, options),
+            ColorType::La16 => blend_step_a(self.as_mut_luma_alpha16().unwrap(), other, options),
+            ColorType::Rgb16 => blend_step_a(self.as_mut_rgb16().unwrap(), other, options),
+            ColorType::Rgba16 => blend_step_a(self.as_mut_rgba16().unwrap(), other, options),
+            ColorType::Rgb32F => blend_step_a(self.as_mut_rgb32f().unwrap(), other, options),
+            ColorType::Rgba32F => blend_step_a(self.as_mut_rgba32f().unwrap(), other, options),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_linear(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        gamut_clip: GamutClip,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_linear_step_a(self.as_mut_luma8().unwrap(), other, op, gamut_clip, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_linear_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, gamut_clip, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_linear_step_a(self.as_mut_rgb8().unwrap(), other, op, gamut_clip, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_linear_step_a(self.as_mut_rgba8().unwrap(), other, op, gamut_clip, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_linear_step_a(self.as_mut_luma16().unwrap(), other, op, gamut_clip, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_linear_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, gamut_clip, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_linear_step_a(self.as_mut_rgb16().unwrap(), other, op, gamut_clip, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_linear_step_a(self.as_mut_rgba16().unwrap(), other, op, gamut_clip, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_linear_step_a(self.as_mut_rgb32f().unwrap(), other, op, gamut_clip, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_linear_step_a(self.as_mut_rgba32f().unwrap(), other, op, gamut_clip, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn try_blend(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> Result<f64, Error>,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> Result<f64, Error> = &op;
+        match self.color() {
+            ColorType::L8 => try_blend_step_a(self.as_mut_luma8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La8 => try_blend_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => try_blend_step_a(self.as_mut_rgb8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => try_blend_step_a(self.as_mut_rgba8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::L16 => try_blend_step_a(self.as_mut_luma16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La16 => try_blend_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => try_blend_step_a(self.as_mut_rgb16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => try_blend_step_a(self.as_mut_rgba16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => try_blend_step_a(self.as_mut_rgb32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => try_blend_step_a(self.as_mut_rgba32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn get_alpha(
+        &self,
+    ) -> Option<DynamicImage> {
+        let color = self.color();
+        let mut copy = self.clone();
+        match color {
+            ColorType::L8 => get_alpha_step_a(copy.as_mut_luma8().unwrap()),
+            ColorType::La8 => get_alpha_step_a(copy.as_mut_luma_alpha8().unwrap()),
+            ColorType::Rgb8 => get_alpha_step_a(copy.as_mut_rgb8().unwrap()),
+            ColorType::Rgba8 => get_alpha_step_a(copy.as_mut_rgba8().unwrap()),
+            ColorType::L16 => get_alpha_step_a(copy.as_mut_luma16().unwrap()),
+            ColorType::La16 => get_alpha_step_a(copy.as_mut_luma_alpha16().unwrap()),
+            ColorType::Rgb16 => get_alpha_step_a(copy.as_mut_rgb16().unwrap()),
+            ColorType::Rgba16 => get_alpha_step_a(copy.as_mut_rgba16().unwrap()),
+            ColorType::Rgb32F => get_alpha_step_a(copy.as_mut_rgb32f().unwrap()),
+            ColorType::Rgba32F => get_alpha_step_a(copy.as_mut_rgba32f().unwrap()),
+            _ => Err(Error::UnsupportedType),
+        }.ok()?;
+        Some(copy)
+    }
+    fn transplant_alpha(
+            &mut self,
+            other: &Self
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => transplant_alpha_step_a(self.as_mut_luma8().unwrap(), other),
+            ColorType::La8 => transplant_alpha_step_a(self.as_mut_luma_alpha8().unwrap(), other),
+            ColorType::Rgb8 => transplant_alpha_step_a(self.as_mut_rgb8().unwrap(), other),
+            ColorType::Rgba8 => transplant_alpha_step_a(self.as_mut_rgba8().unwrap(), other),
+            ColorType::L16 => transplant_alpha_step_a(self.as_mut_luma16().unwrap(), other),
+            ColorType::La16 => transplant_alpha_step_a(self.as_mut_luma_alpha16().unwrap(), other),
+            ColorType::Rgb16 => transplant_alpha_step_a(self.as_mut_rgb16().unwrap(), other),
+            ColorType::Rgba16 => transplant_alpha_step_a(self.as_mut_rgba16().unwrap(), other),
+            ColorType::Rgb32F => transplant_alpha_step_a(self.as_mut_rgb32f().unwrap(), other),
+            ColorType::Rgba32F => transplant_alpha_step_a(self.as_mut_rgba32f().unwrap(), other),
+            _ => Err(Error::UnsupportedType),
+        }?;
+        Ok(())
+    }
+    fn set_alpha(
+        &mut self,
+        other: &Self
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => set_alpha_step_a(self.as_mut_luma8().unwrap(), other),
+            ColorType::La8 => set_alpha_step_a(self.as_mut_luma_alpha8().unwrap(), other),
+            ColorType::Rgb8 => set_alpha_step_a(self.as_mut_rgb8().unwrap(), other),
+            ColorType::Rgba8 => set_alpha_step_a(self.as_mut_rgba8().unwrap(), other),
+            ColorType::L16 => set_alpha_step_a(self.as_mut_luma16().unwrap(), other),
+            ColorType::La16 => set_alpha_step_a(self.as_mut_luma_alpha16().unwrap(), other),
+            ColorType::Rgb16 => set_alpha_step_a(self.as_mut_rgb16().unwrap(), other),
+            ColorType::Rgba16 => set_alpha_step_a(self.as_mut_rgba16().unwrap(), other),
+            ColorType::Rgb32F => set_alpha_step_a(self.as_mut_rgb32f().unwrap(), other),
+            ColorType::Rgba32F => set_alpha_step_a(self.as_mut_rgba32f().unwrap(), other),
+            _ => Err(Error::UnsupportedType),
+        }?;
+        Ok(())
+    }
+    fn strip_alpha(
+            &mut self
+        ) -> Result<(), Error> where Self: std::marker::Sized {
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().strip_alpha(),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().strip_alpha(),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().strip_alpha(),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().strip_alpha(),
+            ColorType::L16 => self.as_mut_luma16().unwrap().strip_alpha(),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().strip_alpha(),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().strip_alpha(),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().strip_alpha(),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().strip_alpha(),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().strip_alpha(),
+            _ => Err(Error::UnsupportedType),
+        }?;
+        Ok(())
+    }
+    fn blend_channel_role(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        channel: Channel,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_channel_role_step_a(self.as_mut_luma8().unwrap(), other, op, channel),
+            ColorType::La8 => blend_channel_role_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, channel),
+            ColorType::Rgb8 => blend_channel_role_step_a(self.as_mut_rgb8().unwrap(), other, op, channel),
+            ColorType::Rgba8 => blend_channel_role_step_a(self.as_mut_rgba8().unwrap(), other, op, channel),
+            ColorType::L16 => blend_channel_role_step_a(self.as_mut_luma16().unwrap(), other, op, channel),
+            ColorType::La16 => blend_channel_role_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, channel),
+            ColorType::Rgb16 => blend_channel_role_step_a(self.as_mut_rgb16().unwrap(), other, op, channel),
+            ColorType::Rgba16 => blend_channel_role_step_a(self.as_mut_rgba16().unwrap(), other, op, channel),
+            ColorType::Rgb32F => blend_channel_role_step_a(self.as_mut_rgb32f().unwrap(), other, op, channel),
+            ColorType::Rgba32F => blend_channel_role_step_a(self.as_mut_rgba32f().unwrap(), other, op, channel),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn map_pixels<F: Fn(f64) -> f64>(
+        &mut self,
+        f: F,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().map_pixels(&f, apply_to_color, apply_to_alpha),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().map_pixels(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().map_pixels(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().map_pixels(&f, apply_to_color, apply_to_alpha),
+            ColorType::L16 => self.as_mut_luma16().unwrap().map_pixels(&f, apply_to_color, apply_to_alpha),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().map_pixels(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().map_pixels(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().map_pixels(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().map_pixels(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().map_pixels(&f, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn invert_channels(
+        &mut self,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        self.map_pixels(|v| 1. - v, apply_to_color, apply_to_alpha)
+    }
+    fn levels(
+        &mut self,
+        black: f64,
+        white: f64,
+        gamma: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().levels(black, white, gamma, apply_to_color, apply_to_alpha),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().levels(black, white, gamma, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().levels(black, white, gamma, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().levels(black, white, gamma, apply_to_color, apply_to_alpha),
+            ColorType::L16 => self.as_mut_luma16().unwrap().levels(black, white, gamma, apply_to_color, apply_to_alpha),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().levels(black, white, gamma, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().levels(black, white, gamma, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().levels(black, white, gamma, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().levels(black, white, gamma, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().levels(black, white, gamma, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_dyn(
+        &mut self,
+        other: &Self,
+        strategy: &dyn BlendStrategy,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => blend_dyn_step_a(self.as_mut_luma8().unwrap(), other, strategy, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_dyn_step_a(self.as_mut_luma_alpha8().unwrap(), other, strategy, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_dyn_step_a(self.as_mut_rgb8().unwrap(), other, strategy, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_dyn_step_a(self.as_mut_rgba8().unwrap(), other, strategy, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_dyn_step_a(self.as_mut_luma16().unwrap(), other, strategy, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_dyn_step_a(self.as_mut_luma_alpha16().unwrap(), other, strategy, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_dyn_step_a(self.as_mut_rgb16().unwrap(), other, strategy, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_dyn_step_a(self.as_mut_rgba16().unwrap(), other, strategy, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_dyn_step_a(self.as_mut_rgb32f().unwrap(), other, strategy, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_dyn_step_a(self.as_mut_rgba32f().unwrap(), other, strategy, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn clamp_alpha(
+        &mut self,
+        min: f64,
+        max: f64,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().clamp_alpha(min, max),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().clamp_alpha(min, max),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().clamp_alpha(min, max),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().clamp_alpha(min, max),
+            ColorType::L16 => self.as_mut_luma16().unwrap().clamp_alpha(min, max),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().clamp_alpha(min, max),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().clamp_alpha(min, max),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().clamp_alpha(min, max),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().clamp_alpha(min, max),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().clamp_alpha(min, max),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn scale_alpha(
+        &mut self,
+        factor: f64,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().scale_alpha(factor),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().scale_alpha(factor),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().scale_alpha(factor),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().scale_alpha(factor),
+            ColorType::L16 => self.as_mut_luma16().unwrap().scale_alpha(factor),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().scale_alpha(factor),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().scale_alpha(factor),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().scale_alpha(factor),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().scale_alpha(factor),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().scale_alpha(factor),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn multiply_alpha(
+        &mut self,
+        other: &Self
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => multiply_alpha_step_a(self.as_mut_luma8().unwrap(), other),
+            ColorType::La8 => multiply_alpha_step_a(self.as_mut_luma_alpha8().unwrap(), other),
+            ColorType::Rgb8 => multiply_alpha_step_a(self.as_mut_rgb8().unwrap(), other),
+            ColorType::Rgba8 => multiply_alpha_step_a(self.as_mut_rgba8().unwrap(), other),
+            ColorType::L16 => multiply_alpha_step_a(self.as_mut_luma16().unwrap(), other),
+            ColorType::La16 => multiply_alpha_step_a(self.as_mut_luma_alpha16().unwrap(), other),
+            ColorType::Rgb16 => multiply_alpha_step_a(self.as_mut_rgb16().unwrap(), other),
+            ColorType::Rgba16 => multiply_alpha_step_a(self.as_mut_rgba16().unwrap(), other),
+            ColorType::Rgb32F => multiply_alpha_step_a(self.as_mut_rgb32f().unwrap(), other),
+            ColorType::Rgba32F => multiply_alpha_step_a(self.as_mut_rgba32f().unwrap(), other),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_mode(
+        &mut self,
+        other: &Self,
+        mode: BlendMode,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => blend_mode_step_a(self.as_mut_luma8().unwrap(), other, mode, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_mode_step_a(self.as_mut_luma_alpha8().unwrap(), other, mode, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_mode_step_a(self.as_mut_rgb8().unwrap(), other, mode, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_mode_step_a(self.as_mut_rgba8().unwrap(), other, mode, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_mode_step_a(self.as_mut_luma16().unwrap(), other, mode, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_mode_step_a(self.as_mut_luma_alpha16().unwrap(), other, mode, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_mode_step_a(self.as_mut_rgb16().unwrap(), other, mode, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_mode_step_a(self.as_mut_rgba16().unwrap(), other, mode, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_mode_step_a(self.as_mut_rgb32f().unwrap(), other, mode, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_mode_step_a(self.as_mut_rgba32f().unwrap(), other, mode, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn map_pixels_positional<F: Fn(u32, u32, f64) -> f64>(
+        &mut self,
+        f: F,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().map_pixels_positional(&f, apply_to_color, apply_to_alpha),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().map_pixels_positional(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().map_pixels_positional(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().map_pixels_positional(&f, apply_to_color, apply_to_alpha),
+            ColorType::L16 => self.as_mut_luma16().unwrap().map_pixels_positional(&f, apply_to_color, apply_to_alpha),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().map_pixels_positional(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().map_pixels_positional(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().map_pixels_positional(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().map_pixels_positional(&f, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().map_pixels_positional(&f, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn get_alpha_or_opaque(
+        &self,
+    ) -> DynamicImage {
+        match self {
+            DynamicImage::ImageLuma8(buf) => DynamicImage::ImageLuma8(buf.get_alpha_or_opaque()),
+            DynamicImage::ImageLumaA8(buf) => DynamicImage::ImageLumaA8(buf.get_alpha_or_opaque()),
+            DynamicImage::ImageRgb8(buf) => DynamicImage::ImageRgb8(buf.get_alpha_or_opaque()),
+            DynamicImage::ImageRgba8(buf) => DynamicImage::ImageRgba8(buf.get_alpha_or_opaque()),
+            DynamicImage::ImageLuma16(buf) => DynamicImage::ImageLuma16(buf.get_alpha_or_opaque()),
+            DynamicImage::ImageLumaA16(buf) => DynamicImage::ImageLumaA16(buf.get_alpha_or_opaque()),
+            DynamicImage::ImageRgb16(buf) => DynamicImage::ImageRgb16(buf.get_alpha_or_opaque()),
+            DynamicImage::ImageRgba16(buf) => DynamicImage::ImageRgba16(buf.get_alpha_or_opaque()),
+            DynamicImage::ImageRgb32F(buf) => DynamicImage::ImageRgb32F(buf.get_alpha_or_opaque()),
+            DynamicImage::ImageRgba32F(buf) => DynamicImage::ImageRgba32F(buf.get_alpha_or_opaque()),
+            _ => DynamicImage::ImageRgba32F(ImageBuffer::from_pixel(self.width(), self.height(), image::Rgba([1.; 4]))),
+        }
+    }
+    fn blend_indexed(
+        &mut self,
+        other: &Self,
+        op: impl Fn(usize, f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(usize, f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_indexed_step_a(self.as_mut_luma8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_indexed_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_indexed_step_a(self.as_mut_rgb8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_indexed_step_a(self.as_mut_rgba8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_indexed_step_a(self.as_mut_luma16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_indexed_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_indexed_step_a(self.as_mut_rgb16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_indexed_step_a(self.as_mut_rgba16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_indexed_step_a(self.as_mut_rgb32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_indexed_step_a(self.as_mut_rgba32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn convert_to(
+        &self,
+        target: ColorType,
+    ) -> Result<DynamicImage, Error> {
+        into_color_type(self.clone(), target)
+    }
+    fn blend_against_constant(
+        &mut self,
+        value: f64,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().blend_against_constant(value, op, apply_to_color, apply_to_alpha),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().blend_against_constant(value, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().blend_against_constant(value, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().blend_against_constant(value, op, apply_to_color, apply_to_alpha),
+            ColorType::L16 => self.as_mut_luma16().unwrap().blend_against_constant(value, op, apply_to_color, apply_to_alpha),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().blend_against_constant(value, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().blend_against_constant(value, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().blend_against_constant(value, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().blend_against_constant(value, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().blend_against_constant(value, op, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn premultiply(
+        &mut self,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().premultiply(),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().premultiply(),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().premultiply(),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().premultiply(),
+            ColorType::L16 => self.as_mut_luma16().unwrap().premultiply(),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().premultiply(),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().premultiply(),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().premultiply(),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().premultiply(),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().premultiply(),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn into_premultiplied(
+        &self,
+    ) -> DynamicImage {
+        let mut premultiplied = self.clone();
+        // Only fails if there is no alpha channel, in which case the image is already premultiplied
+        premultiplied.premultiply().ok();
+        premultiplied
+    }
+    fn blend_generated<G: Fn(u32, u32) -> [f64; 4]>(
+        &mut self,
+        gen: G,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().blend_generated(&gen, op, apply_to_color, apply_to_alpha),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().blend_generated(&gen, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().blend_generated(&gen, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().blend_generated(&gen, op, apply_to_color, apply_to_alpha),
+            ColorType::L16 => self.as_mut_luma16().unwrap().blend_generated(&gen, op, apply_to_color, apply_to_alpha),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().blend_generated(&gen, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().blend_generated(&gen, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().blend_generated(&gen, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().blend_generated(&gen, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().blend_generated(&gen, op, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn split_alpha(
+        &self,
+    ) -> Result<(DynamicImage, DynamicImage), Error> {
+        let (color_type, alpha_type) = match self.color() {
+            ColorType::La8 => (ColorType::L8, ColorType::L8),
+            ColorType::Rgba8 => (ColorType::Rgb8, ColorType::L8),
+            ColorType::La16 => (ColorType::L16, ColorType::L16),
+            ColorType::Rgba16 => (ColorType::Rgb16, ColorType::L16),
+            ColorType::Rgba32F => (ColorType::Rgb32F, ColorType::L16),
+            ColorType::L8 | ColorType::Rgb8 | ColorType::L16 | ColorType::Rgb16 | ColorType::Rgb32F => Err(Error::NoAlphaChannel)?,
+            _ => Err(Error::UnsupportedType)?,
+        };
+        let alpha = self.get_alpha().ok_or(Error::NoAlphaChannel)?;
+        Ok((into_color_type(self.clone(), color_type)?, into_color_type(alpha, alpha_type)?))
+    }
+    fn to_luminance(
+        &self,
+        coefficients: LumaCoefficients,
+    ) -> DynamicImage {
+        let mut gray = self.clone();
+        // Only fails for unsupported types, which are returned unchanged
+        match &mut gray {
+            DynamicImage::ImageRgb8(buf) => luminance_in_place(buf, coefficients),
+            DynamicImage::ImageRgba8(buf) => luminance_in_place(buf, coefficients),
+            DynamicImage::ImageRgb16(buf) => luminance_in_place(buf, coefficients),
+            DynamicImage::ImageRgba16(buf) => luminance_in_place(buf, coefficients),
+            DynamicImage::ImageRgb32F(buf) => luminance_in_place(buf, coefficients),
+            DynamicImage::ImageRgba32F(buf) => luminance_in_place(buf, coefficients),
+            _ => Ok(()),
+        }.ok();
+        gray
+    }
+    fn blend_padded(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        anchor: Anchor,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_padded_step_a(self.as_mut_luma8().unwrap(), other, op, anchor, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_padded_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, anchor, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_padded_step_a(self.as_mut_rgb8().unwrap(), other, op, anchor, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_padded_step_a(self.as_mut_rgba8().unwrap(), other, op, anchor, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_padded_step_a(self.as_mut_luma16().unwrap(), other, op, anchor, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_padded_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, anchor, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_padded_step_a(self.as_mut_rgb16().unwrap(), other, op, anchor, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_padded_step_a(self.as_mut_rgba16().unwrap(), other, op, anchor, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_padded_step_a(self.as_mut_rgb32f().unwrap(), other, op, anchor, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_padded_step_a(self.as_mut_rgba32f().unwrap(), other, op, anchor, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_stream<I: Iterator<Item = DynamicImage>>(
+        &mut self,
+        frames: I,
+        op: impl Fn(f64, f64) -> f64,
+        on_mismatch: MismatchPolicy,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<usize, Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        let mut blended = 0;
+        for frame in frames {
+            if (frame.width(), frame.height()) != (self.width(), self.height()) {
+                match on_mismatch {
+                    MismatchPolicy::Error => return Err(Error::DimensionMismatch),
+                    MismatchPolicy::Skip => continue,
+                }
+            }
+            self.blend(&frame, op, apply_to_color, apply_to_alpha)?;
+            blended += 1;
+        }
+        Ok(blended)
+    }
+    fn unpremultiply(
+        &mut self,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().unpremultiply(),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().unpremultiply(),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().unpremultiply(),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().unpremultiply(),
+            ColorType::L16 => self.as_mut_luma16().unwrap().unpremultiply(),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().unpremultiply(),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().unpremultiply(),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().unpremultiply(),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().unpremultiply(),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().unpremultiply(),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_components(
+        &mut self,
+        other: &Self,
+        component: ComponentBlend,
+        model: ColorModel,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => blend_components_step_a(self.as_mut_luma8().unwrap(), other, component, model),
+            ColorType::La8 => blend_components_step_a(self.as_mut_luma_alpha8().unwrap(), other, component, model),
+            ColorType::Rgb8 => blend_components_step_a(self.as_mut_rgb8().unwrap(), other, component, model),
+            ColorType::Rgba8 => blend_components_step_a(self.as_mut_rgba8().unwrap(), other, component, model),
+            ColorType::L16 => blend_components_step_a(self.as_mut_luma16().unwrap(), other, component, model),
+            ColorType::La16 => blend_components_step_a(self.as_mut_luma_alpha16().unwrap(), other, component, model),
+            ColorType::Rgb16 => blend_components_step_a(self.as_mut_rgb16().unwrap(), other, component, model),
+            ColorType::Rgba16 => blend_components_step_a(self.as_mut_rgba16().unwrap(), other, component, model),
+            ColorType::Rgb32F => blend_components_step_a(self.as_mut_rgb32f().unwrap(), other, component, model),
+            ColorType::Rgba32F => blend_components_step_a(self.as_mut_rgba32f().unwrap(), other, component, model),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn dissolve(
+        &mut self,
+        other: &Self,
+        seed: u64,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => dissolve_step_a(self.as_mut_luma8().unwrap(), other, seed),
+            ColorType::La8 => dissolve_step_a(self.as_mut_luma_alpha8().unwrap(), other, seed),
+            ColorType::Rgb8 => dissolve_step_a(self.as_mut_rgb8().unwrap(), other, seed),
+            ColorType::Rgba8 => dissolve_step_a(self.as_mut_rgba8().unwrap(), other, seed),
+            ColorType::L16 => dissolve_step_a(self.as_mut_luma16().unwrap(), other, seed),
+            ColorType::La16 => dissolve_step_a(self.as_mut_luma_alpha16().unwrap(), other, seed),
+            ColorType::Rgb16 => dissolve_step_a(self.as_mut_rgb16().unwrap(), other, seed),
+            ColorType::Rgba16 => dissolve_step_a(self.as_mut_rgba16().unwrap(), other, seed),
+            ColorType::Rgb32F => dissolve_step_a(self.as_mut_rgb32f().unwrap(), other, seed),
+            ColorType::Rgba32F => dissolve_step_a(self.as_mut_rgba32f().unwrap(), other, seed),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_weighted(
+        &mut self,
+        others: &[(DynamicImage, f64)],
+        op: impl Fn(f64, f64) -> f64 + Sync,
+    ) -> Result<(), Error> {
+        let op: &(dyn Fn(f64, f64) -> f64 + Sync) = &op;
+        *self = blend_weighted(self, others, op)?;
+        Ok(())
+    }
+    fn blend_as(
+        &self,
+        other: &Self,
+        target: ColorType,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<Self, Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        let mut blended = self.clone();
+        blended.blend(other, op, apply_to_color, apply_to_alpha)?;
+        blended.convert_to(target)
+    }
+    fn flatten_onto_color(
+        &self,
+        bg: [f64; 3],
+    ) -> Self {
+        let bg = bg.map(|v| v.clamp(0., 1.));
+        flatten(self, |_, _| bg)
+    }
+    fn flatten_onto(
+        &self,
+        background: &Self,
+    ) -> Result<Self, Error> {
+        if (self.width(), self.height()) != (background.width(), background.height()) {
+            return Err(Error::DimensionMismatch);
+        }
+        let background = background.to_rgb32f();
+        Ok(flatten(self, |x, y| background.get_pixel(x, y).0.map(f64::from)))
+    }
+    fn threshold(
+        &mut self,
+        cutoff: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        self.map_pixels(|v| if v < cutoff { 0. } else { 1. }, apply_to_color, apply_to_alpha)
+    }
+    fn blend_tile(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        tile: Rect,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_tile_step_a(self.as_mut_luma8().unwrap(), other, op, tile, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_tile_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, tile, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_tile_step_a(self.as_mut_rgb8().unwrap(), other, op, tile, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_tile_step_a(self.as_mut_rgba8().unwrap(), other, op, tile, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_tile_step_a(self.as_mut_luma16().unwrap(), other, op, tile, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_tile_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, tile, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_tile_step_a(self.as_mut_rgb16().unwrap(), other, op, tile, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_tile_step_a(self.as_mut_rgba16().unwrap(), other, op, tile, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_tile_step_a(self.as_mut_rgb32f().unwrap(), other, op, tile, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_tile_step_a(self.as_mut_rgba32f().unwrap(), other, op, tile, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_selection(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        selection: &Self,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        // Only the first channel is used, which converting to rgba keeps for every type
+        let selection = selection.to_rgba32f();
+        match self.color() {
+            ColorType::L8 => blend_selection_step_a(self.as_mut_luma8().unwrap(), other, op, &selection, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_selection_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, &selection, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_selection_step_a(self.as_mut_rgb8().unwrap(), other, op, &selection, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_selection_step_a(self.as_mut_rgba8().unwrap(), other, op, &selection, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_selection_step_a(self.as_mut_luma16().unwrap(), other, op, &selection, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_selection_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, &selection, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_selection_step_a(self.as_mut_rgb16().unwrap(), other, op, &selection, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_selection_step_a(self.as_mut_rgba16().unwrap(), other, op, &selection, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_selection_step_a(self.as_mut_rgb32f().unwrap(), other, op, &selection, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_selection_step_a(self.as_mut_rgba32f().unwrap(), other, op, &selection, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_trimmed(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        x: i64,
+        y: i64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_trimmed_step_a(self.as_mut_luma8().unwrap(), other, op, x, y, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_trimmed_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, x, y, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_trimmed_step_a(self.as_mut_rgb8().unwrap(), other, op, x, y, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_trimmed_step_a(self.as_mut_rgba8().unwrap(), other, op, x, y, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_trimmed_step_a(self.as_mut_luma16().unwrap(), other, op, x, y, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_trimmed_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, x, y, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_trimmed_step_a(self.as_mut_rgb16().unwrap(), other, op, x, y, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_trimmed_step_a(self.as_mut_rgba16().unwrap(), other, op, x, y, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_trimmed_step_a(self.as_mut_rgb32f().unwrap(), other, op, x, y, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_trimmed_step_a(self.as_mut_rgba32f().unwrap(), other, op, x, y, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_threshold_op(
+        &mut self,
+        other: &Self,
+        op_opaque: impl Fn(f64, f64) -> f64,
+        op_transparent: impl Fn(f64, f64) -> f64,
+        alpha_cut: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op_opaque: &dyn Fn(f64, f64) -> f64 = &op_opaque;
+        let op_transparent: &dyn Fn(f64, f64) -> f64 = &op_transparent;
+        match self.color() {
+            ColorType::L8 => blend_threshold_op_step_a(self.as_mut_luma8().unwrap(), other, op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_threshold_op_step_a(self.as_mut_luma_alpha8().unwrap(), other, op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_threshold_op_step_a(self.as_mut_rgb8().unwrap(), other, op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_threshold_op_step_a(self.as_mut_rgba8().unwrap(), other, op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_threshold_op_step_a(self.as_mut_luma16().unwrap(), other, op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_threshold_op_step_a(self.as_mut_luma_alpha16().unwrap(), other, op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_threshold_op_step_a(self.as_mut_rgb16().unwrap(), other, op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_threshold_op_step_a(self.as_mut_rgba16().unwrap(), other, op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_threshold_op_step_a(self.as_mut_rgb32f().unwrap(), other, op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_threshold_op_step_a(self.as_mut_rgba32f().unwrap(), other, op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_with_progress(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        mut progress: impl FnMut(f64),
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        let progress: &mut dyn FnMut(f64) = &mut progress;
+        match self.color() {
+            ColorType::L8 => blend_with_progress_step_a(self.as_mut_luma8().unwrap(), other, op, progress, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_with_progress_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, progress, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_with_progress_step_a(self.as_mut_rgb8().unwrap(), other, op, progress, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_with_progress_step_a(self.as_mut_rgba8().unwrap(), other, op, progress, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_with_progress_step_a(self.as_mut_luma16().unwrap(), other, op, progress, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_with_progress_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, progress, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_with_progress_step_a(self.as_mut_rgb16().unwrap(), other, op, progress, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_with_progress_step_a(self.as_mut_rgba16().unwrap(), other, op, progress, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_with_progress_step_a(self.as_mut_rgb32f().unwrap(), other, op, progress, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_with_progress_step_a(self.as_mut_rgba32f().unwrap(), other, op, progress, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_alpha_only(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_alpha_only_step_a(self.as_mut_luma8().unwrap(), other, op),
+            ColorType::La8 => blend_alpha_only_step_a(self.as_mut_luma_alpha8().unwrap(), other, op),
+            ColorType::Rgb8 => blend_alpha_only_step_a(self.as_mut_rgb8().unwrap(), other, op),
+            ColorType::Rgba8 => blend_alpha_only_step_a(self.as_mut_rgba8().unwrap(), other, op),
+            ColorType::L16 => blend_alpha_only_step_a(self.as_mut_luma16().unwrap(), other, op),
+            ColorType::La16 => blend_alpha_only_step_a(self.as_mut_luma_alpha16().unwrap(), other, op),
+            ColorType::Rgb16 => blend_alpha_only_step_a(self.as_mut_rgb16().unwrap(), other, op),
+            ColorType::Rgba16 => blend_alpha_only_step_a(self.as_mut_rgba16().unwrap(), other, op),
+            ColorType::Rgb32F => blend_alpha_only_step_a(self.as_mut_rgb32f().unwrap(), other, op),
+            ColorType::Rgba32F => blend_alpha_only_step_a(self.as_mut_rgba32f().unwrap(), other, op),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_saturating_int(
+        &mut self,
+        other: &Self,
+        op: IntOp,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => blend_saturating_int_step_a(self.as_mut_luma8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_saturating_int_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_saturating_int_step_a(self.as_mut_rgb8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_saturating_int_step_a(self.as_mut_rgba8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_saturating_int_step_a(self.as_mut_luma16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_saturating_int_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_saturating_int_step_a(self.as_mut_rgb16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_saturating_int_step_a(self.as_mut_rgba16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_saturating_int_step_a(self.as_mut_rgb32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_saturating_int_step_a(self.as_mut_rgba32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_alpha_coverage(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<AlphaCoverage, Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_alpha_coverage_step_a(self.as_mut_luma8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_alpha_coverage_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_alpha_coverage_step_a(self.as_mut_rgb8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_alpha_coverage_step_a(self.as_mut_rgba8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_alpha_coverage_step_a(self.as_mut_luma16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_alpha_coverage_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_alpha_coverage_step_a(self.as_mut_rgb16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_alpha_coverage_step_a(self.as_mut_rgba16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_alpha_coverage_step_a(self.as_mut_rgb32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_alpha_coverage_step_a(self.as_mut_rgba32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_normal_maps(
+        &mut self,
+        detail: &DynamicImage,
+        method: NormalBlend,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => blend_normal_maps_step_a(self.as_mut_luma8().unwrap(), detail, method),
+            ColorType::La8 => blend_normal_maps_step_a(self.as_mut_luma_alpha8().unwrap(), detail, method),
+            ColorType::Rgb8 => blend_normal_maps_step_a(self.as_mut_rgb8().unwrap(), detail, method),
+            ColorType::Rgba8 => blend_normal_maps_step_a(self.as_mut_rgba8().unwrap(), detail, method),
+            ColorType::L16 => blend_normal_maps_step_a(self.as_mut_luma16().unwrap(), detail, method),
+            ColorType::La16 => blend_normal_maps_step_a(self.as_mut_luma_alpha16().unwrap(), detail, method),
+            ColorType::Rgb16 => blend_normal_maps_step_a(self.as_mut_rgb16().unwrap(), detail, method),
+            ColorType::Rgba16 => blend_normal_maps_step_a(self.as_mut_rgba16().unwrap(), detail, method),
+            ColorType::Rgb32F => blend_normal_maps_step_a(self.as_mut_rgb32f().unwrap(), detail, method),
+            ColorType::Rgba32F => blend_normal_maps_step_a(self.as_mut_rgba32f().unwrap(), detail, method),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_opacity_map(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        opacity_map: &Self,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        // Only the first channel is used, which converting to rgba keeps for every type
+        let opacity_map = opacity_map.to_rgba32f();
+        match self.color() {
+            ColorType::L8 => blend_opacity_map_step_a(self.as_mut_luma8().unwrap(), other, op, &opacity_map, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_opacity_map_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, &opacity_map, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_opacity_map_step_a(self.as_mut_rgb8().unwrap(), other, op, &opacity_map, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_opacity_map_step_a(self.as_mut_rgba8().unwrap(), other, op, &opacity_map, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_opacity_map_step_a(self.as_mut_luma16().unwrap(), other, op, &opacity_map, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_opacity_map_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, &opacity_map, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_opacity_map_step_a(self.as_mut_rgb16().unwrap(), other, op, &opacity_map, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_opacity_map_step_a(self.as_mut_rgba16().unwrap(), other, op, &opacity_map, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_opacity_map_step_a(self.as_mut_rgb32f().unwrap(), other, op, &opacity_map, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_opacity_map_step_a(self.as_mut_rgba32f().unwrap(), other, op, &opacity_map, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn apply_lut_1d(
+        &mut self,
+        lut: &[[f64; 3]],
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().apply_lut_1d(lut),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().apply_lut_1d(lut),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().apply_lut_1d(lut),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().apply_lut_1d(lut),
+            ColorType::L16 => self.as_mut_luma16().unwrap().apply_lut_1d(lut),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().apply_lut_1d(lut),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().apply_lut_1d(lut),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().apply_lut_1d(lut),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().apply_lut_1d(lut),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().apply_lut_1d(lut),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn apply_lut_3d(
+        &mut self,
+        lut: &Lut3D,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().apply_lut_3d(lut),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().apply_lut_3d(lut),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().apply_lut_3d(lut),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().apply_lut_3d(lut),
+            ColorType::L16 => self.as_mut_luma16().unwrap().apply_lut_3d(lut),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().apply_lut_3d(lut),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().apply_lut_3d(lut),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().apply_lut_3d(lut),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().apply_lut_3d(lut),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().apply_lut_3d(lut),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn composite_sprites(
+        &mut self,
+        sprites: &[(DynamicImage, i64, i64)],
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => composite_sprites_step_a(self.as_mut_luma8().unwrap(), sprites),
+            ColorType::La8 => composite_sprites_step_a(self.as_mut_luma_alpha8().unwrap(), sprites),
+            ColorType::Rgb8 => composite_sprites_step_a(self.as_mut_rgb8().unwrap(), sprites),
+            ColorType::Rgba8 => composite_sprites_step_a(self.as_mut_rgba8().unwrap(), sprites),
+            ColorType::L16 => composite_sprites_step_a(self.as_mut_luma16().unwrap(), sprites),
+            ColorType::La16 => composite_sprites_step_a(self.as_mut_luma_alpha16().unwrap(), sprites),
+            ColorType::Rgb16 => composite_sprites_step_a(self.as_mut_rgb16().unwrap(), sprites),
+            ColorType::Rgba16 => composite_sprites_step_a(self.as_mut_rgba16().unwrap(), sprites),
+            ColorType::Rgb32F => composite_sprites_step_a(self.as_mut_rgb32f().unwrap(), sprites),
+            ColorType::Rgba32F => composite_sprites_step_a(self.as_mut_rgba32f().unwrap(), sprites),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn alpha_from_edges(
+        &mut self,
+        threshold: f64,
+    ) -> Result<(), Error> {
+        // Checked here too, so an invalid threshold leaves `self` unconverted
+        if !(threshold > 0. && threshold <= 1.) {
+            return Err(Error::InvalidArgument("threshold must be in the range 0.0 < threshold <= 1.0"));
+        }
+        let with_alpha = match self.color() {
+            ColorType::L8 => Some(ColorType::La8),
+            ColorType::Rgb8 => Some(ColorType::Rgba8),
+            ColorType::L16 => Some(ColorType::La16),
+            ColorType::Rgb16 => Some(ColorType::Rgba16),
+            ColorType::Rgb32F => Some(ColorType::Rgba32F),
+            _ => None,
+        };
+        if let Some(color) = with_alpha {
+            *self = into_color_type(self.clone(), color)?;
+        }
+        match self.color() {
+            ColorType::L8 => self.as_mut_luma8().unwrap().alpha_from_edges(threshold),
+            ColorType::La8 => self.as_mut_luma_alpha8().unwrap().alpha_from_edges(threshold),
+            ColorType::Rgb8 => self.as_mut_rgb8().unwrap().alpha_from_edges(threshold),
+            ColorType::Rgba8 => self.as_mut_rgba8().unwrap().alpha_from_edges(threshold),
+            ColorType::L16 => self.as_mut_luma16().unwrap().alpha_from_edges(threshold),
+            ColorType::La16 => self.as_mut_luma_alpha16().unwrap().alpha_from_edges(threshold),
+            ColorType::Rgb16 => self.as_mut_rgb16().unwrap().alpha_from_edges(threshold),
+            ColorType::Rgba16 => self.as_mut_rgba16().unwrap().alpha_from_edges(threshold),
+            ColorType::Rgb32F => self.as_mut_rgb32f().unwrap().alpha_from_edges(threshold),
+            ColorType::Rgba32F => self.as_mut_rgba32f().unwrap().alpha_from_edges(threshold),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn compatibility(
+        &self,
+        other: &Self,
+    ) -> Result<(), Error> {
+        self.compatibility_report(other).result()
+    }
+    fn compatibility_report(
+        &self,
+        other: &Self,
+    ) -> CompatibilityReport {
+        CompatibilityReport::from_color_types((self.width(), self.height()), (other.width(), other.height()), self.color(), other.color())
+    }
+    fn blend_tonal(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        range: TonalRange,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_tonal_step_a(self.as_mut_luma8().unwrap(), other, op, range, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_tonal_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, range, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_tonal_step_a(self.as_mut_rgb8().unwrap(), other, op, range, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_tonal_step_a(self.as_mut_rgba8().unwrap(), other, op, range, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_tonal_step_a(self.as_mut_luma16().unwrap(), other, op, range, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_tonal_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, range, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_tonal_step_a(self.as_mut_rgb16().unwrap(), other, op, range, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_tonal_step_a(self.as_mut_rgba16().unwrap(), other, op, range, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_tonal_step_a(self.as_mut_rgb32f().unwrap(), other, op, range, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_tonal_step_a(self.as_mut_rgba32f().unwrap(), other, op, range, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_under(
+        &mut self,
+        other: &Self,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_under_step_a(self.as_mut_luma8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_under_step_a(self.as_mut_luma_alpha8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_under_step_a(self.as_mut_rgb8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_under_step_a(self.as_mut_rgba8().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_under_step_a(self.as_mut_luma16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_under_step_a(self.as_mut_luma_alpha16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_under_step_a(self.as_mut_rgb16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_under_step_a(self.as_mut_rgba16().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_under_step_a(self.as_mut_rgb32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_under_step_a(self.as_mut_rgba32f().unwrap(), other, op, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn replace_color(
+        &mut self,
+        other: &Self,
+        target: [f64; 3],
+        tolerance: f64,
+    ) -> Result<(), Error> {
+        match self.color() {
+            ColorType::L8 => replace_color_step_a(self.as_mut_luma8().unwrap(), other, target, tolerance),
+            ColorType::La8 => replace_color_step_a(self.as_mut_luma_alpha8().unwrap(), other, target, tolerance),
+            ColorType::Rgb8 => replace_color_step_a(self.as_mut_rgb8().unwrap(), other, target, tolerance),
+            ColorType::Rgba8 => replace_color_step_a(self.as_mut_rgba8().unwrap(), other, target, tolerance),
+            ColorType::L16 => replace_color_step_a(self.as_mut_luma16().unwrap(), other, target, tolerance),
+            ColorType::La16 => replace_color_step_a(self.as_mut_luma_alpha16().unwrap(), other, target, tolerance),
+            ColorType::Rgb16 => replace_color_step_a(self.as_mut_rgb16().unwrap(), other, target, tolerance),
+            ColorType::Rgba16 => replace_color_step_a(self.as_mut_rgba16().unwrap(), other, target, tolerance),
+            ColorType::Rgb32F => replace_color_step_a(self.as_mut_rgb32f().unwrap(), other, target, tolerance),
+            ColorType::Rgba32F => replace_color_step_a(self.as_mut_rgba32f().unwrap(), other, target, tolerance),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn blend_color_keyed(
+        &mut self,
+        other: &Self,
+        key: [f64; 3],
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let op: &dyn Fn(f64, f64) -> f64 = &op;
+        match self.color() {
+            ColorType::L8 => blend_color_keyed_step_a(self.as_mut_luma8().unwrap(), other, key, op, apply_to_color, apply_to_alpha),
+            ColorType::La8 => blend_color_keyed_step_a(self.as_mut_luma_alpha8().unwrap(), other, key, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb8 => blend_color_keyed_step_a(self.as_mut_rgb8().unwrap(), other, key, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba8 => blend_color_keyed_step_a(self.as_mut_rgba8().unwrap(), other, key, op, apply_to_color, apply_to_alpha),
+            ColorType::L16 => blend_color_keyed_step_a(self.as_mut_luma16().unwrap(), other, key, op, apply_to_color, apply_to_alpha),
+            ColorType::La16 => blend_color_keyed_step_a(self.as_mut_luma_alpha16().unwrap(), other, key, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb16 => blend_color_keyed_step_a(self.as_mut_rgb16().unwrap(), other, key, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba16 => blend_color_keyed_step_a(self.as_mut_rgba16().unwrap(), other, key, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgb32F => blend_color_keyed_step_a(self.as_mut_rgb32f().unwrap(), other, key, op, apply_to_color, apply_to_alpha),
+            ColorType::Rgba32F => blend_color_keyed_step_a(self.as_mut_rgba32f().unwrap(), other, key, op, apply_to_color, apply_to_alpha),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+    fn transplant_alpha_scaled(
+        &mut self,
+        other: &Self,
+        filter: FilterType,
+    ) -> Result<(), Error> {
+        if (self.width(), self.height()) == (other.width(), other.height()) {
+            return self.transplant_alpha(other);
+        }
+        if !(self.color().has_alpha() && other.color().has_alpha()) {
+            return Err(Error::NoAlphaChannel);
+        }
+        if other.width() == 0 || other.height() == 0 {
+            return Err(Error::DimensionMismatch);
+        }
+        self.transplant_alpha(&other.resize_exact(self.width(), self.height(), filter))
+    }
+    fn motion_mask(
+        &self,
+        previous: &Self,
+        threshold: f64,
+    ) -> Result<DynamicImage, Error> {
+        if (self.width(), self.height()) != (previous.width(), previous.height()) {
+            return Err(Error::DimensionMismatch);
+        }
+        let current = self.to_luminance(LumaCoefficients::REC_709).to_luma32f();
+        let previous = previous.to_luminance(LumaCoefficients::REC_709).to_luma32f();
+        let mask = GrayImage::from_fn(self.width(), self.height(), |x, y| {
+            let difference = pixel_diff(f64::from(current.get_pixel(x, y).0[0]), f64::from(previous.get_pixel(x, y).0[0]));
+            Luma([if difference > threshold { 255 } else { 0 }])
+        });
+        Ok(DynamicImage::ImageLuma8(mask))
+    }
+}
+fn blend_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, options: &BlendOptions) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_with_options(other.as_luma8().unwrap(), options),
+        ColorType::La8 => subject.blend_with_options(other.as_luma_alpha8().unwrap(), options),
+        ColorType::Rgb8 => subject.blend_with_options(other.as_rgb8().unwrap(), options),
+        ColorType::Rgba8 => subject.blend_with_options(other.as_rgba8().unwrap(), options),
+        ColorType::L16 => subject.blend_with_options(other.as_luma16().unwrap(), options),
+        ColorType::La16 => subject.blend_with_options(other.as_luma_alpha16().unwrap(), options),
+        ColorType::Rgb16 => subject.blend_with_options(other.as_rgb16().unwrap(), options),
+        ColorType::Rgba16 => subject.blend_with_options(other.as_rgba16().unwrap(), options),
+        ColorType::Rgb32F => subject.blend_with_options(other.as_rgb32f().unwrap(), options),
+        ColorType::Rgba32F => subject.blend_with_options(other.as_rgba32f().unwrap(), options),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_linear_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, gamut_clip: GamutClip, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_linear(other.as_luma8().unwrap(), op, gamut_clip, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_linear(other.as_luma_alpha8().unwrap(), op, gamut_clip, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_linear(other.as_rgb8().unwrap(), op, gamut_clip, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_linear(other.as_rgba8().unwrap(), op, gamut_clip, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_linear(other.as_luma16().unwrap(), op, gamut_clip, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_linear(other.as_luma_alpha16().unwrap(), op, gamut_clip, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_linear(other.as_rgb16().unwrap(), op, gamut_clip, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_linear(other.as_rgba16().unwrap(), op, gamut_clip, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_linear(other.as_rgb32f().unwrap(), op, gamut_clip, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_linear(other.as_rgba32f().unwrap(), op, gamut_clip, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn try_blend_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> Result<f64, Error>, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.try_blend(other.as_luma8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.try_blend(other.as_luma_alpha8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.try_blend(other.as_rgb8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.try_blend(other.as_rgba8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.try_blend(other.as_luma16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.try_blend(other.as_luma_alpha16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.try_blend(other.as_rgb16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.try_blend(other.as_rgba16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.try_blend(other.as_rgb32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.try_blend(other.as_rgba32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn get_alpha_step_a<P, Container>(subject: &mut ImageBuffer<P, Container>) -> Result<(), Error>
+where 
+    P: Pixel,
+    Container: DerefMut<Target = [P::Subpixel]> + AsRef<[<P as image::Pixel>::Subpixel]> + Clone,
+{
+    let alpha = subject.get_alpha().ok_or(Error::NoAlphaChannel)?;
+    *subject = alpha;
+    Ok(())
+}
+fn set_alpha_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.set_alpha(other.as_luma8().unwrap()),
+        ColorType::La8 => subject.set_alpha(other.as_luma_alpha8().unwrap()),
+        ColorType::Rgb8 => subject.set_alpha(other.as_rgb8().unwrap()),
+        ColorType::Rgba8 => subject.set_alpha(other.as_rgba8().unwrap()),
+        ColorType::L16 => subject.set_alpha(other.as_luma16().unwrap()),
+        ColorType::La16 => subject.set_alpha(other.as_luma_alpha16().unwrap()),
+        ColorType::Rgb16 => subject.set_alpha(other.as_rgb16().unwrap()),
+        ColorType::Rgba16 => subject.set_alpha(other.as_rgba16().unwrap()),
+        ColorType::Rgb32F => subject.set_alpha(other.as_rgb32f().unwrap()),
+        ColorType::Rgba32F => subject.set_alpha(other.as_rgba32f().unwrap()),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn transplant_alpha_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    match other.color() {
+        ColorType::L8 => subject.transplant_alpha(other.as_luma8().unwrap()),
+        ColorType::La8 => subject.transplant_alpha(other.as_luma_alpha8().unwrap()),
+        ColorType::Rgb8 => subject.transplant_alpha(other.as_rgb8().unwrap()),
+        ColorType::Rgba8 => subject.transplant_alpha(other.as_rgba8().unwrap()),
+        ColorType::L16 => subject.transplant_alpha(other.as_luma16().unwrap()),
+        ColorType::La16 => subject.transplant_alpha(other.as_luma_alpha16().unwrap()),
+        ColorType::Rgb16 => subject.transplant_alpha(other.as_rgb16().unwrap()),
+        ColorType::Rgba16 => subject.transplant_alpha(other.as_rgba16().unwrap()),
+        ColorType::Rgb32F => subject.transplant_alpha(other.as_rgb32f().unwrap()),
+        ColorType::Rgba32F => subject.transplant_alpha(other.as_rgba32f().unwrap()),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+/**
+Combine a color image with a grayscale alpha image, as returned by `DynamicChops::split_alpha`.
+
+The result has the bit depth of `color` with an alpha channel added (e.g. `Rgb16` becomes `Rgba16`). If `color` already has an alpha channel it is replaced. Like `set_alpha`, only the first channel of `alpha` is used.
+
+# Errors
+`DimensionMismatch`: `color` and `alpha` have different dimensions
+
+`UnsupportedType`: `color` or `alpha` is of an unsupported color type
+
+
+# Examples
+
+```
+use image::open;
+use image_blend::{join_alpha, DynamicChops};
+
+let img1_dynamic = open("test_data/1.png").unwrap();
+let (color, alpha) = img1_dynamic.split_alpha().unwrap();
+let joined = join_alpha(&color, &alpha).unwrap();
+assert_eq!(joined.as_bytes(), img1_dynamic.as_bytes());
+```
+*/
+pub fn join_alpha(color: &DynamicImage, alpha: &DynamicImage) -> Result<DynamicImage, Error> {
+    let target = match color.color() {
+        ColorType::L8 | ColorType::La8 => ColorType::La8,
+        ColorType::L16 | ColorType::La16 => ColorType::La16,
+        ColorType::Rgb8 | ColorType::Rgba8 => ColorType::Rgba8,
+        ColorType::Rgb16 | ColorType::Rgba16 => ColorType::Rgba16,
+        ColorType::Rgb32F | ColorType::Rgba32F => ColorType::Rgba32F,
+        _ => Err(Error::UnsupportedType)?,
+    };
+    let mut joined = into_color_type(color.clone(), target)?;
+    joined.set_alpha(alpha)?;
+    Ok(joined)
+}
+/**
+Convert `a` to the color type of `b`, so that a sequence of operations between them works on identical types.
+
+Operations between differing types are already supported, but each one normalizes both images on the fly. Converting once up front avoids repeating that work, and makes copies such as `transplant_alpha` exact. `a` is left untouched if it already has `b`'s color type. See `DynamicChops::convert_to` for how channels are converted.
+
+# Errors
+`UnsupportedType`: `b` is of an unsupported color type
+
+
+# Examples
+
+```
+use image::{open, ColorType};
+use image_blend::{match_types, DynamicChops};
+
+let mut img1_dynamic = open("test_data/1.png").unwrap();
+let img2_dynamic = open("test_data/2.png").unwrap().convert_to(ColorType::Rgba16).unwrap();
+
+match_types(&mut img1_dynamic, &img2_dynamic).unwrap();
+assert_eq!(img1_dynamic.color(), ColorType::Rgba16);
+```
+*/
+pub fn match_types(a: &mut DynamicImage, b: &DynamicImage) -> Result<(), Error> {
+    if a.color() != b.color() {
+        *a = a.convert_to(b.color())?;
+    }
+    Ok(())
+}
+pub(crate) fn into_color_type(image: DynamicImage, color: ColorType) -> Result<DynamicImage, Error> {
+    Ok(match color {
+        ColorType::L8 => DynamicImage::ImageLuma8(image.into_luma8()),
+        ColorType::La8 => DynamicImage::ImageLumaA8(image.into_luma_alpha8()),
+        ColorType::Rgb8 => DynamicImage::ImageRgb8(image.into_rgb8()),
+        ColorType::Rgba8 => DynamicImage::ImageRgba8(image.into_rgba8()),
+        ColorType::L16 => DynamicImage::ImageLuma16(image.into_luma16()),
+        ColorType::La16 => DynamicImage::ImageLumaA16(image.into_luma_alpha16()),
+        ColorType::Rgb16 => DynamicImage::ImageRgb16(image.into_rgb16()),
+        ColorType::Rgba16 => DynamicImage::ImageRgba16(image.into_rgba16()),
+        ColorType::Rgb32F => DynamicImage::ImageRgb32F(image.into_rgb32f()),
+        ColorType::Rgba32F => DynamicImage::ImageRgba32F(image.into_rgba32f()),
+        _ => Err(Error::UnsupportedType)?,
+    })
+}
+fn blend_channel_role_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, channel: Channel) -> Result<(), Error>
+where 
     Pmut: Pixel,
     ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
     + DerefMut<Target = [Pmut::Subpixel]>
     + AsMut<[<Pmut as Pixel>::Subpixel]>
 {
     match other.color() {
-        ColorType::L8 => subject.set_alpha(other.as_luma8().unwrap()),
-        ColorType::La8 => subject.set_alpha(other.as_luma_alpha8().unwrap()),
-        ColorType::Rgb8 => subject.set_alpha(other.as_rgb8().unwrap()),
-        ColorType::Rgba8 => subject.set_alpha(other.as_rgba8().unwrap()),
-        ColorType::L16 => subject.set_alpha(other.as_luma16().unwrap()),
-        ColorType::La16 => subject.set_alpha(other.as_luma_alpha16().unwrap()),
-        ColorType::Rgb16 => subject.set_alpha(other.as_rgb16().unwrap()),
-        ColorType::Rgba16 => subject.set_alpha(other.as_rgba16().unwrap()),
-        ColorType::Rgb32F => subject.set_alpha(other.as_rgb32f().unwrap()),
-        ColorType::Rgba32F => subject.set_alpha(other.as_rgba32f().unwrap()),
+        ColorType::L8 => subject.blend_channel_role(other.as_luma8().unwrap(), op, channel),
+        ColorType::La8 => subject.blend_channel_role(other.as_luma_alpha8().unwrap(), op, channel),
+        ColorType::Rgb8 => subject.blend_channel_role(other.as_rgb8().unwrap(), op, channel),
+        ColorType::Rgba8 => subject.blend_channel_role(other.as_rgba8().unwrap(), op, channel),
+        ColorType::L16 => subject.blend_channel_role(other.as_luma16().unwrap(), op, channel),
+        ColorType::La16 => subject.blend_channel_role(other.as_luma_alpha16().unwrap(), op, channel),
+        ColorType::Rgb16 => subject.blend_channel_role(other.as_rgb16().unwrap(), op, channel),
+        ColorType::Rgba16 => subject.blend_channel_role(other.as_rgba16().unwrap(), op, channel),
+        ColorType::Rgb32F => subject.blend_channel_role(other.as_rgb32f().unwrap(), op, channel),
+        ColorType::Rgba32F => subject.blend_channel_role(other.as_rgba32f().unwrap(), op, channel),
         _ => Err(Error::UnsupportedType),
     }
 }
-fn transplant_alpha_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage) -> Result<(), Error>
+fn blend_dyn_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, strategy: &dyn BlendStrategy, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_dyn(other.as_luma8().unwrap(), strategy, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_dyn(other.as_luma_alpha8().unwrap(), strategy, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_dyn(other.as_rgb8().unwrap(), strategy, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_dyn(other.as_rgba8().unwrap(), strategy, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_dyn(other.as_luma16().unwrap(), strategy, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_dyn(other.as_luma_alpha16().unwrap(), strategy, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_dyn(other.as_rgb16().unwrap(), strategy, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_dyn(other.as_rgba16().unwrap(), strategy, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_dyn(other.as_rgb32f().unwrap(), strategy, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_dyn(other.as_rgba32f().unwrap(), strategy, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn multiply_alpha_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.multiply_alpha(other.as_luma8().unwrap()),
+        ColorType::La8 => subject.multiply_alpha(other.as_luma_alpha8().unwrap()),
+        ColorType::Rgb8 => subject.multiply_alpha(other.as_rgb8().unwrap()),
+        ColorType::Rgba8 => subject.multiply_alpha(other.as_rgba8().unwrap()),
+        ColorType::L16 => subject.multiply_alpha(other.as_luma16().unwrap()),
+        ColorType::La16 => subject.multiply_alpha(other.as_luma_alpha16().unwrap()),
+        ColorType::Rgb16 => subject.multiply_alpha(other.as_rgb16().unwrap()),
+        ColorType::Rgba16 => subject.multiply_alpha(other.as_rgba16().unwrap()),
+        ColorType::Rgb32F => subject.multiply_alpha(other.as_rgb32f().unwrap()),
+        ColorType::Rgba32F => subject.multiply_alpha(other.as_rgba32f().unwrap()),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_mode_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, mode: BlendMode, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_mode(other.as_luma8().unwrap(), mode, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_mode(other.as_luma_alpha8().unwrap(), mode, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_mode(other.as_rgb8().unwrap(), mode, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_mode(other.as_rgba8().unwrap(), mode, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_mode(other.as_luma16().unwrap(), mode, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_mode(other.as_luma_alpha16().unwrap(), mode, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_mode(other.as_rgb16().unwrap(), mode, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_mode(other.as_rgba16().unwrap(), mode, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_mode(other.as_rgb32f().unwrap(), mode, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_mode(other.as_rgba32f().unwrap(), mode, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_indexed_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(usize, f64, f64) -> f64, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_indexed(other.as_luma8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_indexed(other.as_luma_alpha8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_indexed(other.as_rgb8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_indexed(other.as_rgba8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_indexed(other.as_luma16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_indexed(other.as_luma_alpha16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_indexed(other.as_rgb16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_indexed(other.as_rgba16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_indexed(other.as_rgb32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_indexed(other.as_rgba32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_padded_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, anchor: Anchor, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_padded(other.as_luma8().unwrap(), op, anchor, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_padded(other.as_luma_alpha8().unwrap(), op, anchor, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_padded(other.as_rgb8().unwrap(), op, anchor, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_padded(other.as_rgba8().unwrap(), op, anchor, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_padded(other.as_luma16().unwrap(), op, anchor, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_padded(other.as_luma_alpha16().unwrap(), op, anchor, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_padded(other.as_rgb16().unwrap(), op, anchor, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_padded(other.as_rgba16().unwrap(), op, anchor, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_padded(other.as_rgb32f().unwrap(), op, anchor, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_padded(other.as_rgba32f().unwrap(), op, anchor, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_components_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, component: ComponentBlend, model: ColorModel) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_components(other.as_luma8().unwrap(), component, model),
+        ColorType::La8 => subject.blend_components(other.as_luma_alpha8().unwrap(), component, model),
+        ColorType::Rgb8 => subject.blend_components(other.as_rgb8().unwrap(), component, model),
+        ColorType::Rgba8 => subject.blend_components(other.as_rgba8().unwrap(), component, model),
+        ColorType::L16 => subject.blend_components(other.as_luma16().unwrap(), component, model),
+        ColorType::La16 => subject.blend_components(other.as_luma_alpha16().unwrap(), component, model),
+        ColorType::Rgb16 => subject.blend_components(other.as_rgb16().unwrap(), component, model),
+        ColorType::Rgba16 => subject.blend_components(other.as_rgba16().unwrap(), component, model),
+        ColorType::Rgb32F => subject.blend_components(other.as_rgb32f().unwrap(), component, model),
+        ColorType::Rgba32F => subject.blend_components(other.as_rgba32f().unwrap(), component, model),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn dissolve_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, seed: u64) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.dissolve(other.as_luma8().unwrap(), seed),
+        ColorType::La8 => subject.dissolve(other.as_luma_alpha8().unwrap(), seed),
+        ColorType::Rgb8 => subject.dissolve(other.as_rgb8().unwrap(), seed),
+        ColorType::Rgba8 => subject.dissolve(other.as_rgba8().unwrap(), seed),
+        ColorType::L16 => subject.dissolve(other.as_luma16().unwrap(), seed),
+        ColorType::La16 => subject.dissolve(other.as_luma_alpha16().unwrap(), seed),
+        ColorType::Rgb16 => subject.dissolve(other.as_rgb16().unwrap(), seed),
+        ColorType::Rgba16 => subject.dissolve(other.as_rgba16().unwrap(), seed),
+        ColorType::Rgb32F => subject.dissolve(other.as_rgb32f().unwrap(), seed),
+        ColorType::Rgba32F => subject.dissolve(other.as_rgba32f().unwrap(), seed),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_op_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
 where 
     Pmut: Pixel,
     ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
@@ -372,16 +3347,344 @@ where
     + AsMut<[<Pmut as Pixel>::Subpixel]>,
 {
     match other.color() {
-        ColorType::L8 => subject.transplant_alpha(other.as_luma8().unwrap()),
-        ColorType::La8 => subject.transplant_alpha(other.as_luma_alpha8().unwrap()),
-        ColorType::Rgb8 => subject.transplant_alpha(other.as_rgb8().unwrap()),
-        ColorType::Rgba8 => subject.transplant_alpha(other.as_rgba8().unwrap()),
-        ColorType::L16 => subject.transplant_alpha(other.as_luma16().unwrap()),
-        ColorType::La16 => subject.transplant_alpha(other.as_luma_alpha16().unwrap()),
-        ColorType::Rgb16 => subject.transplant_alpha(other.as_rgb16().unwrap()),
-        ColorType::Rgba16 => subject.transplant_alpha(other.as_rgba16().unwrap()),
-        ColorType::Rgb32F => subject.transplant_alpha(other.as_rgb32f().unwrap()),
-        ColorType::Rgba32F => subject.transplant_alpha(other.as_rgba32f().unwrap()),
+        ColorType::L8 => subject.blend(other.as_luma8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend(other.as_luma_alpha8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend(other.as_rgb8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend(other.as_rgba8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend(other.as_luma16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend(other.as_luma_alpha16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend(other.as_rgb16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend(other.as_rgba16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend(other.as_rgb32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend(other.as_rgba32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_tile_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, tile: Rect, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_tile(other.as_luma8().unwrap(), op, tile, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_tile(other.as_luma_alpha8().unwrap(), op, tile, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_tile(other.as_rgb8().unwrap(), op, tile, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_tile(other.as_rgba8().unwrap(), op, tile, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_tile(other.as_luma16().unwrap(), op, tile, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_tile(other.as_luma_alpha16().unwrap(), op, tile, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_tile(other.as_rgb16().unwrap(), op, tile, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_tile(other.as_rgba16().unwrap(), op, tile, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_tile(other.as_rgb32f().unwrap(), op, tile, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_tile(other.as_rgba32f().unwrap(), op, tile, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_selection_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, selection: &Rgba32FImage, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_selection(other.as_luma8().unwrap(), op, selection, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_selection(other.as_luma_alpha8().unwrap(), op, selection, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_selection(other.as_rgb8().unwrap(), op, selection, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_selection(other.as_rgba8().unwrap(), op, selection, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_selection(other.as_luma16().unwrap(), op, selection, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_selection(other.as_luma_alpha16().unwrap(), op, selection, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_selection(other.as_rgb16().unwrap(), op, selection, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_selection(other.as_rgba16().unwrap(), op, selection, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_selection(other.as_rgb32f().unwrap(), op, selection, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_selection(other.as_rgba32f().unwrap(), op, selection, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_trimmed_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, x: i64, y: i64, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_trimmed(other.as_luma8().unwrap(), op, x, y, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_trimmed(other.as_luma_alpha8().unwrap(), op, x, y, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_trimmed(other.as_rgb8().unwrap(), op, x, y, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_trimmed(other.as_rgba8().unwrap(), op, x, y, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_trimmed(other.as_luma16().unwrap(), op, x, y, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_trimmed(other.as_luma_alpha16().unwrap(), op, x, y, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_trimmed(other.as_rgb16().unwrap(), op, x, y, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_trimmed(other.as_rgba16().unwrap(), op, x, y, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_trimmed(other.as_rgb32f().unwrap(), op, x, y, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_trimmed(other.as_rgba32f().unwrap(), op, x, y, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_threshold_op_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op_opaque: &dyn Fn(f64, f64) -> f64, op_transparent: &dyn Fn(f64, f64) -> f64, alpha_cut: f64, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_threshold_op(other.as_luma8().unwrap(), op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_threshold_op(other.as_luma_alpha8().unwrap(), op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_threshold_op(other.as_rgb8().unwrap(), op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_threshold_op(other.as_rgba8().unwrap(), op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_threshold_op(other.as_luma16().unwrap(), op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_threshold_op(other.as_luma_alpha16().unwrap(), op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_threshold_op(other.as_rgb16().unwrap(), op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_threshold_op(other.as_rgba16().unwrap(), op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_threshold_op(other.as_rgb32f().unwrap(), op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_threshold_op(other.as_rgba32f().unwrap(), op_opaque, op_transparent, alpha_cut, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_with_progress_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, progress: &mut dyn FnMut(f64), apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_with_progress(other.as_luma8().unwrap(), op, &mut *progress, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_with_progress(other.as_luma_alpha8().unwrap(), op, &mut *progress, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_with_progress(other.as_rgb8().unwrap(), op, &mut *progress, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_with_progress(other.as_rgba8().unwrap(), op, &mut *progress, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_with_progress(other.as_luma16().unwrap(), op, &mut *progress, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_with_progress(other.as_luma_alpha16().unwrap(), op, &mut *progress, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_with_progress(other.as_rgb16().unwrap(), op, &mut *progress, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_with_progress(other.as_rgba16().unwrap(), op, &mut *progress, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_with_progress(other.as_rgb32f().unwrap(), op, &mut *progress, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_with_progress(other.as_rgba32f().unwrap(), op, &mut *progress, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_alpha_only_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_alpha_only(other.as_luma8().unwrap(), op),
+        ColorType::La8 => subject.blend_alpha_only(other.as_luma_alpha8().unwrap(), op),
+        ColorType::Rgb8 => subject.blend_alpha_only(other.as_rgb8().unwrap(), op),
+        ColorType::Rgba8 => subject.blend_alpha_only(other.as_rgba8().unwrap(), op),
+        ColorType::L16 => subject.blend_alpha_only(other.as_luma16().unwrap(), op),
+        ColorType::La16 => subject.blend_alpha_only(other.as_luma_alpha16().unwrap(), op),
+        ColorType::Rgb16 => subject.blend_alpha_only(other.as_rgb16().unwrap(), op),
+        ColorType::Rgba16 => subject.blend_alpha_only(other.as_rgba16().unwrap(), op),
+        ColorType::Rgb32F => subject.blend_alpha_only(other.as_rgb32f().unwrap(), op),
+        ColorType::Rgba32F => subject.blend_alpha_only(other.as_rgba32f().unwrap(), op),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_saturating_int_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: IntOp, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_saturating_int(other.as_luma8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_saturating_int(other.as_luma_alpha8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_saturating_int(other.as_rgb8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_saturating_int(other.as_rgba8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_saturating_int(other.as_luma16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_saturating_int(other.as_luma_alpha16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_saturating_int(other.as_rgb16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_saturating_int(other.as_rgba16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_saturating_int(other.as_rgb32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_saturating_int(other.as_rgba32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_alpha_coverage_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, apply_to_color: bool, apply_to_alpha: bool) -> Result<AlphaCoverage, Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_alpha_coverage(other.as_luma8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_alpha_coverage(other.as_luma_alpha8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_alpha_coverage(other.as_rgb8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_alpha_coverage(other.as_rgba8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_alpha_coverage(other.as_luma16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_alpha_coverage(other.as_luma_alpha16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_alpha_coverage(other.as_rgb16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_alpha_coverage(other.as_rgba16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_alpha_coverage(other.as_rgb32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_alpha_coverage(other.as_rgba32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+/// Composite `image` source-over onto the opaque color `bg` returns for each pixel, keeping the bit depth of `image`.
+fn flatten(image: &DynamicImage, bg: impl Fn(u32, u32) -> [f64; 3]) -> DynamicImage {
+    let rgba = image.to_rgba32f();
+    let flattened = ImageBuffer::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, alpha] = rgba.get_pixel(x, y).0.map(f64::from);
+        let bg = bg(x, y);
+        #[allow(clippy::cast_possible_truncation)]
+        let flatten = |ch: usize| ([r, g, b][ch] * alpha + bg[ch] * (1. - alpha)) as f32;
+        image::Rgb([flatten(0), flatten(1), flatten(2)])
+    });
+    let flattened = DynamicImage::ImageRgb32F(flattened);
+    let target = match image.color().bytes_per_pixel() / image.color().channel_count() {
+        1 => ColorType::Rgb8,
+        2 => ColorType::Rgb16,
+        _ => ColorType::Rgb32F,
+    };
+    // Rgb types are always supported
+    flattened.convert_to(target).unwrap()
+}
+fn blend_normal_maps_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, method: NormalBlend) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_normal_maps(other.as_luma8().unwrap(), method),
+        ColorType::La8 => subject.blend_normal_maps(other.as_luma_alpha8().unwrap(), method),
+        ColorType::Rgb8 => subject.blend_normal_maps(other.as_rgb8().unwrap(), method),
+        ColorType::Rgba8 => subject.blend_normal_maps(other.as_rgba8().unwrap(), method),
+        ColorType::L16 => subject.blend_normal_maps(other.as_luma16().unwrap(), method),
+        ColorType::La16 => subject.blend_normal_maps(other.as_luma_alpha16().unwrap(), method),
+        ColorType::Rgb16 => subject.blend_normal_maps(other.as_rgb16().unwrap(), method),
+        ColorType::Rgba16 => subject.blend_normal_maps(other.as_rgba16().unwrap(), method),
+        ColorType::Rgb32F => subject.blend_normal_maps(other.as_rgb32f().unwrap(), method),
+        ColorType::Rgba32F => subject.blend_normal_maps(other.as_rgba32f().unwrap(), method),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_opacity_map_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, opacity_map: &Rgba32FImage, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_opacity_map(other.as_luma8().unwrap(), op, opacity_map, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_opacity_map(other.as_luma_alpha8().unwrap(), op, opacity_map, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_opacity_map(other.as_rgb8().unwrap(), op, opacity_map, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_opacity_map(other.as_rgba8().unwrap(), op, opacity_map, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_opacity_map(other.as_luma16().unwrap(), op, opacity_map, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_opacity_map(other.as_luma_alpha16().unwrap(), op, opacity_map, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_opacity_map(other.as_rgb16().unwrap(), op, opacity_map, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_opacity_map(other.as_rgba16().unwrap(), op, opacity_map, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_opacity_map(other.as_rgb32f().unwrap(), op, opacity_map, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_opacity_map(other.as_rgba32f().unwrap(), op, opacity_map, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_tonal_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, range: TonalRange, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_tonal(other.as_luma8().unwrap(), op, range, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_tonal(other.as_luma_alpha8().unwrap(), op, range, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_tonal(other.as_rgb8().unwrap(), op, range, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_tonal(other.as_rgba8().unwrap(), op, range, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_tonal(other.as_luma16().unwrap(), op, range, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_tonal(other.as_luma_alpha16().unwrap(), op, range, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_tonal(other.as_rgb16().unwrap(), op, range, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_tonal(other.as_rgba16().unwrap(), op, range, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_tonal(other.as_rgb32f().unwrap(), op, range, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_tonal(other.as_rgba32f().unwrap(), op, range, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_under_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, op: &dyn Fn(f64, f64) -> f64, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_under(other.as_luma8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_under(other.as_luma_alpha8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_under(other.as_rgb8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_under(other.as_rgba8().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_under(other.as_luma16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_under(other.as_luma_alpha16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_under(other.as_rgb16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_under(other.as_rgba16().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_under(other.as_rgb32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_under(other.as_rgba32f().unwrap(), op, apply_to_color, apply_to_alpha),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn replace_color_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, target: [f64; 3], tolerance: f64) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.replace_color(other.as_luma8().unwrap(), target, tolerance),
+        ColorType::La8 => subject.replace_color(other.as_luma_alpha8().unwrap(), target, tolerance),
+        ColorType::Rgb8 => subject.replace_color(other.as_rgb8().unwrap(), target, tolerance),
+        ColorType::Rgba8 => subject.replace_color(other.as_rgba8().unwrap(), target, tolerance),
+        ColorType::L16 => subject.replace_color(other.as_luma16().unwrap(), target, tolerance),
+        ColorType::La16 => subject.replace_color(other.as_luma_alpha16().unwrap(), target, tolerance),
+        ColorType::Rgb16 => subject.replace_color(other.as_rgb16().unwrap(), target, tolerance),
+        ColorType::Rgba16 => subject.replace_color(other.as_rgba16().unwrap(), target, tolerance),
+        ColorType::Rgb32F => subject.replace_color(other.as_rgb32f().unwrap(), target, tolerance),
+        ColorType::Rgba32F => subject.replace_color(other.as_rgba32f().unwrap(), target, tolerance),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_color_keyed_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, other: &DynamicImage, key: [f64; 3], op: &dyn Fn(f64, f64) -> f64, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    match other.color() {
+        ColorType::L8 => subject.blend_color_keyed(other.as_luma8().unwrap(), key, op, apply_to_color, apply_to_alpha),
+        ColorType::La8 => subject.blend_color_keyed(other.as_luma_alpha8().unwrap(), key, op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb8 => subject.blend_color_keyed(other.as_rgb8().unwrap(), key, op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba8 => subject.blend_color_keyed(other.as_rgba8().unwrap(), key, op, apply_to_color, apply_to_alpha),
+        ColorType::L16 => subject.blend_color_keyed(other.as_luma16().unwrap(), key, op, apply_to_color, apply_to_alpha),
+        ColorType::La16 => subject.blend_color_keyed(other.as_luma_alpha16().unwrap(), key, op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb16 => subject.blend_color_keyed(other.as_rgb16().unwrap(), key, op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba16 => subject.blend_color_keyed(other.as_rgba16().unwrap(), key, op, apply_to_color, apply_to_alpha),
+        ColorType::Rgb32F => subject.blend_color_keyed(other.as_rgb32f().unwrap(), key, op, apply_to_color, apply_to_alpha),
+        ColorType::Rgba32F => subject.blend_color_keyed(other.as_rgba32f().unwrap(), key, op, apply_to_color, apply_to_alpha),
         _ => Err(Error::UnsupportedType),
     }
 }
+fn composite_sprites_step_a<Pmut, ContainerMut>(subject: &mut ImageBuffer<Pmut, ContainerMut>, sprites: &[(DynamicImage, i64, i64)]) -> Result<(), Error>
+where 
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]>
+    + DerefMut<Target = [Pmut::Subpixel]>
+    + AsMut<[<Pmut as Pixel>::Subpixel]>
+{
+    for (sprite, x, y) in sprites {
+        // Color first, as compositing it reads the alpha of `subject` from before this sprite
+        blend_trimmed_step_a(subject, sprite, &pixel_normal, *x, *y, true, false)?;
+        // Screening alphas is source-over: a + b - a * b
+        blend_trimmed_step_a(subject, sprite, &pixel_screen, *x, *y, false, true)?;
+    }
+    Ok(())
+}
@@ -1,7 +1,15 @@
 use image::{flat::SampleLayout, ColorType};
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    pixelops::{
+        pixel_add, pixel_average, pixel_bipolar_add, pixel_darker, pixel_diff, pixel_div, pixel_grain_extract, pixel_grain_merge, pixel_hard_light, pixel_hard_mix,
+        pixel_lighter, pixel_linear_burn, pixel_linear_light, pixel_mult, pixel_normal, pixel_overlay, pixel_phoenix, pixel_pin_light, pixel_screen,
+        pixel_soft_light, pixel_soft_light_pegtop, pixel_sub, pixel_vivid_light,
+    },
+};
 
+#[derive(PartialEq, Eq)]
 pub(crate) enum ColorStructure {
     L,
     La,
@@ -42,6 +50,18 @@ impl ColorStructure {
             ColorStructure::Rgb | ColorStructure::Rgba => true,
         }
     }
+    /// Whether the color of an image with this structure can be blended from one with `other`'s, which holds unless an rgb image would be blended into a luma one.
+    pub(crate) fn accepts(&self, other: &ColorStructure) -> bool {
+        self.rgb() || !other.rgb()
+    }
+    pub(crate) fn channels(&self) -> usize {
+        match self {
+            ColorStructure::L => 1,
+            ColorStructure::La => 2,
+            ColorStructure::Rgb => 3,
+            ColorStructure::Rgba => 4,
+        }
+    }
     pub(crate) fn alpha_channel(&self) -> Option<usize> {
         match self {
             ColorStructure::La => Some(1),
@@ -49,6 +69,15 @@ impl ColorStructure {
             _ => None,
         }
     }
+    pub(crate) fn channel_index(&self, channel: Channel) -> Option<usize> {
+        match (channel, self.rgb()) {
+            (Channel::Alpha, _) => self.alpha_channel(),
+            (Channel::Luma, false) | (Channel::Red, true) => Some(0),
+            (Channel::Green, true) => Some(1),
+            (Channel::Blue, true) => Some(2),
+            _ => None,
+        }
+    }
 }
 pub(crate) trait ColorString {
     fn color_str(&self) -> &'static str;
@@ -81,3 +110,373 @@ impl ColorString for ColorStructure {
         }
     }
 }
+
+/// How out of range colors are brought back into gamut after blending in a working space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GamutClip {
+    /// Clamp each channel to 0.0..1.0 independently. Cheap, but shifts the hue of out of range colors.
+    #[default]
+    Clamp,
+    /// Desaturate towards the gray of the same luminance until every channel is in range, preserving hue.
+    DesatToGray,
+}
+
+/// Which component of `other`'s color a component blend takes, keeping the rest from `self`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentBlend {
+    /// `other`'s hue with `self`'s saturation and lightness or value.
+    Hue,
+    /// `other`'s saturation with `self`'s hue and lightness or value.
+    Saturation,
+    /// `other`'s hue and saturation with `self`'s lightness or value.
+    Color,
+    /// `other`'s lightness (in HSL) or value (in HSV) with `self`'s hue and saturation.
+    Value,
+}
+
+/// How `blend_normal_maps` combines a base normal map with a detail normal map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NormalBlend {
+    /// Reoriented normal mapping (RNM): rotates the detail normal so that its up vector follows the base normal. The most accurate method, and the usual default.
+    #[default]
+    Reoriented,
+    /// Partial derivative blending: adds the slopes of the two surfaces. Cheaper than RNM and close to it for shallow details, but flattens steep ones.
+    PartialDerivative,
+}
+
+/// The cylindrical color model a component blend splits colors into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorModel {
+    /// Hue, saturation, lightness. Lightness is the mean of the largest and smallest channel.
+    #[default]
+    Hsl,
+    /// Hue, saturation, value. Value is the largest channel, so a fully saturated color has a value of 1.0.
+    Hsv,
+}
+
+/// How blended values are rounded when written back to an integer image. Float images are never rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Round towards zero. This was the only behavior before `Rounding` was added, and biases results slightly darker.
+    Truncate,
+    /// Round to the nearest value, with halves rounded up.
+    #[default]
+    Round,
+    /// Round to the nearest value, with halves rounded to the nearest even value.
+    Banker,
+}
+impl Rounding {
+    pub(crate) fn apply(self, value: f64) -> f64 {
+        match self {
+            Rounding::Truncate => value.trunc(),
+            Rounding::Round => value.round(),
+            Rounding::Banker => value.round_ties_even(),
+        }
+    }
+}
+
+/// An exact integer operation for `blend_saturating_int`, where `a` is self and `b` is other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntOp {
+    /// `a + b`, saturating at the maximum value of the type.
+    Add,
+    /// `a - b`, saturating at 0.
+    Sub,
+    /// `a * b / max`, rounded to the nearest integer, where `max` is the maximum value of the type.
+    Mult,
+    /// `max - (max - a) * (max - b) / max`, with the product rounded to the nearest integer.
+    Screen,
+}
+impl IntOp {
+    pub(crate) fn apply(self, a: u128, b: u128, max: u128) -> u128 {
+        match self {
+            IntOp::Add => (a + b).min(max),
+            IntOp::Sub => a.saturating_sub(b),
+            IntOp::Mult => (a * b + max / 2) / max,
+            IntOp::Screen => max - IntOp::Mult.apply(max - a, max - b, max),
+        }
+    }
+}
+
+/// The arithmetic `blend_with_options` blends with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Arithmetic {
+    /// Convert subpixels to 64 bit floats in the range 0.0..1.0, blend with `BlendOptions::op`, and round back.
+    #[default]
+    Float,
+    /// Blend 8 and 16 bit integer images with this `IntOp` directly on the stored values instead of with `BlendOptions::op`, e.g. multiply as `(a * b + 127) / 255` for 8 bit, so results are bit exact and match integer (GPU or SIMD) implementations.
+    FixedPoint(IntOp),
+}
+
+/// The axis a `gradient` runs along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// Left to right.
+    #[default]
+    Horizontal,
+    /// Top to bottom.
+    Vertical,
+}
+
+/// The tonal range of `self` a `blend_tonal` targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TonalRange {
+    /// Dark pixels, with a luminance near 0.0.
+    Shadows,
+    /// Pixels with a luminance near 0.5.
+    Midtones,
+    /// Bright pixels, with a luminance near 1.0.
+    Highlights,
+}
+impl TonalRange {
+    /// Weight of a pixel of luminance `luma` in this range: a Gaussian bell with a standard deviation of 0.25 centered on the range, so 1.0 at its center and about 0.14 half way across the range of luminances.
+    pub(crate) fn weight(self, luma: f64) -> f64 {
+        let center = match self {
+            TonalRange::Shadows => 0.,
+            TonalRange::Midtones => 0.5,
+            TonalRange::Highlights => 1.,
+        };
+        (-(luma.clamp(0., 1.) - center).powi(2) / (2. * 0.25 * 0.25)).exp()
+    }
+}
+
+/// (position in outer, position in inner, size) of an overlapping region.
+pub(crate) type Overlap = ((u32, u32), (u32, u32), (u32, u32));
+
+/// How `blend_with_options` handles an `other` image of a different size than `self`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DimensionPolicy {
+    /// Return `DimensionMismatch`.
+    #[default]
+    Strict,
+    /// Blend only the region both images cover, with their top left corners aligned. The rest of `self` is left untouched.
+    Crop,
+    /// Center `other` within `self`, as if padded with transparent pixels, as by `blend_padded` with `Anchor::Center`. The rest of `self` is left untouched, and parts of `other` outside of `self` are ignored.
+    Pad,
+    /// Stretch `other` to the size of `self` with bilinear filtering before blending.
+    Scale,
+}
+
+/// Where a smaller image is placed within a larger one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+impl Anchor {
+    /// Position of the top left corner of an `inner` sized image anchored within an `outer` sized one. Negative if `inner` is larger.
+    pub(crate) fn offset(self, outer: (u32, u32), inner: (u32, u32)) -> (i64, i64) {
+        let free_x = i64::from(outer.0) - i64::from(inner.0);
+        let free_y = i64::from(outer.1) - i64::from(inner.1);
+        let x = match self {
+            Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => 0,
+            Anchor::Top | Anchor::Center | Anchor::Bottom => free_x.div_euclid(2),
+            Anchor::TopRight | Anchor::Right | Anchor::BottomRight => free_x,
+        };
+        let y = match self {
+            Anchor::TopLeft | Anchor::Top | Anchor::TopRight => 0,
+            Anchor::Left | Anchor::Center | Anchor::Right => free_y.div_euclid(2),
+            Anchor::BottomLeft | Anchor::Bottom | Anchor::BottomRight => free_y,
+        };
+        (x, y)
+    }
+    /// The region where an `inner` sized image anchored within an `outer` sized one overlaps it, as (position in outer, position in inner, size). `None` if they do not overlap.
+    pub(crate) fn overlap(self, outer: (u32, u32), inner: (u32, u32)) -> Option<Overlap> {
+        let region = Rect { x: 0, y: 0, width: inner.0, height: inner.1 };
+        region.overlap_at(self.offset(outer, inner), outer)
+    }
+}
+
+/// What `pixel_div_policy` returns when dividing by zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DivideZero {
+    /// Return 1.0, as `pixel_div` does.
+    #[default]
+    White,
+    /// Return 0.0.
+    Black,
+    /// Return the numerator unchanged, so dividing by zero leaves `self` as it was.
+    PassThrough,
+}
+
+/// How many pixels of an image are fully transparent or fully opaque, as returned by `blend_alpha_coverage`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AlphaCoverage {
+    /// Pixels with an alpha of 0.
+    pub transparent: usize,
+    /// Pixels with the maximum alpha. Every pixel of an image without an alpha channel.
+    pub opaque: usize,
+}
+
+/// A rectangular region of an image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    /// Column of the left edge.
+    pub x: u32,
+    /// Row of the top edge.
+    pub y: u32,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+impl Rect {
+    /// Whether this region lies entirely within an image of `dimensions`.
+    pub(crate) fn fits_within(self, dimensions: (u32, u32)) -> bool {
+        u64::from(self.x) + u64::from(self.width) <= u64::from(dimensions.0)
+            && u64::from(self.y) + u64::from(self.height) <= u64::from(dimensions.1)
+    }
+    /// The region where this region of an inner image overlaps an `outer` sized image, when the inner image's top left corner is placed at `offset` within `outer`. `None` if they do not overlap.
+    pub(crate) fn overlap_at(self, offset: (i64, i64), outer: (u32, u32)) -> Option<Overlap> {
+        let x = offset.0 + i64::from(self.x);
+        let y = offset.1 + i64::from(self.y);
+        let (x0, y0) = (x.max(0), y.max(0));
+        let x1 = (x + i64::from(self.width)).min(i64::from(outer.0));
+        let y1 = (y + i64::from(self.height)).min(i64::from(outer.1));
+        if x1 <= x0 || y1 <= y0 {
+            return None;
+        }
+        // All of these are within 0..=u32::MAX after the clamping above
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let to_u32 = |v: i64| v as u32;
+        Some((
+            (to_u32(x0), to_u32(y0)),
+            (to_u32(x0 - x + i64::from(self.x)), to_u32(y0 - y + i64::from(self.y))),
+            (to_u32(x1 - x0), to_u32(y1 - y0)),
+        ))
+    }
+}
+
+/// What to do with an image whose dimensions don't match the image it is blended into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MismatchPolicy {
+    /// Stop and return `Error::DimensionMismatch`.
+    #[default]
+    Error,
+    /// Ignore the image and carry on.
+    Skip,
+}
+
+/// How the color channels of an image relate to its alpha channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+    /// Color is stored independently of alpha. This is what the blend operations in this crate expect.
+    #[default]
+    Straight,
+    /// Color has already been multiplied by alpha.
+    Premultiplied,
+}
+
+/// A channel identified by its role rather than its index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    Alpha,
+    Luma,
+}
+
+/// The built in blend modes, each corresponding to a function in `pixelops`.
+///
+/// `PartialEq` compares `Custom` by function pointer, so two `Custom` modes wrapping the same function may still compare unequal if the compiler duplicated or merged it.
+#[allow(unpredictable_function_pointer_comparisons)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    /// `pixel_normal`
+    Normal,
+    /// `pixel_add`
+    Add,
+    /// `pixel_sub`
+    Subtract,
+    /// `pixel_div`
+    Divide,
+    /// `pixel_darker`
+    Darker,
+    /// `pixel_lighter`
+    Lighter,
+    /// `pixel_diff`
+    Difference,
+    /// `pixel_mult`
+    Multiply,
+    /// `pixel_screen`
+    Screen,
+    /// `pixel_overlay`
+    Overlay,
+    /// `pixel_hard_light`
+    HardLight,
+    /// `pixel_soft_light`, the W3C formula used by browsers.
+    SoftLight,
+    /// `pixel_soft_light_pegtop`
+    SoftLightPegtop,
+    /// `pixel_grain_extract`
+    GrainExtract,
+    /// `pixel_grain_merge`
+    GrainMerge,
+    /// `pixel_bipolar_add`
+    BipolarAdd,
+    /// `pixel_phoenix`
+    Phoenix,
+    /// `pixel_linear_burn`
+    LinearBurn,
+    /// `pixel_add`, linear dodge is the same as adding.
+    LinearDodge,
+    /// `pixel_linear_light`
+    LinearLight,
+    /// `pixel_vivid_light`
+    VividLight,
+    /// `pixel_pin_light`
+    PinLight,
+    /// `pixel_hard_mix`
+    HardMix,
+    /// `pixel_average`
+    Average,
+    /// Randomly shows either pixel, picking `other`'s with a probability of its alpha, seeded by the given value. See `dissolve`.
+    ///
+    /// This chooses whole pixels, so it is not a per channel function: `op` returns `pixel_normal`, and `blend_mode` calls `dissolve` ignoring `apply_to_color` and `apply_to_alpha`.
+    Dissolve(u64),
+    /// A user supplied blend function.
+    Custom(fn(f64, f64) -> f64),
+}
+impl BlendMode {
+    /// The function implementing this blend mode.
+    ///
+    /// Modes are chosen at runtime, so this is a function pointer and is not inlined when blending. Pass the `pixelops` function to `blend` directly in hot loops where the mode is known in advance.
+    #[must_use]
+    pub fn op(&self) -> fn(f64, f64) -> f64 {
+        match self {
+            BlendMode::Normal | BlendMode::Dissolve(_) => pixel_normal,
+            BlendMode::Add | BlendMode::LinearDodge => pixel_add,
+            BlendMode::Subtract => pixel_sub,
+            BlendMode::Divide => pixel_div,
+            BlendMode::Darker => pixel_darker,
+            BlendMode::Lighter => pixel_lighter,
+            BlendMode::Difference => pixel_diff,
+            BlendMode::Multiply => pixel_mult,
+            BlendMode::Screen => pixel_screen,
+            BlendMode::Overlay => pixel_overlay,
+            BlendMode::HardLight => pixel_hard_light,
+            BlendMode::SoftLight => pixel_soft_light,
+            BlendMode::SoftLightPegtop => pixel_soft_light_pegtop,
+            BlendMode::GrainExtract => pixel_grain_extract,
+            BlendMode::GrainMerge => pixel_grain_merge,
+            BlendMode::BipolarAdd => pixel_bipolar_add,
+            BlendMode::Phoenix => pixel_phoenix,
+            BlendMode::LinearBurn => pixel_linear_burn,
+            BlendMode::LinearLight => pixel_linear_light,
+            BlendMode::VividLight => pixel_vivid_light,
+            BlendMode::PinLight => pixel_pin_light,
+            BlendMode::HardMix => pixel_hard_mix,
+            BlendMode::Average => pixel_average,
+            BlendMode::Custom(op) => *op,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::enums::Channel;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("Image dimensions do not match")]
@@ -6,9 +8,60 @@ pub enum Error {
     #[error("Attempted to blend images with an unsupported color type")]
     UnsupportedType,
 
-    #[error("Image 'a' of type {0} cannot accept blends from image 'b' of type {1}")]
+    #[error("{}", unsupported_blend_message(.0, .1))]
     UnsupportedBlend(&'static str, &'static str),
 
     #[error("Cannot access alpha channel as image does not have an alpha channel")]
     NoAlphaChannel,
+
+    #[error("No images were provided")]
+    NoImages,
+
+    #[error("Image of type {1} has no {0:?} channel")]
+    MissingChannel(Channel, &'static str),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(&'static str),
+
+    #[error("Blend operation failed: {0}")]
+    BlendOp(String),
+
+    #[error("No blend mode is registered as {0:?}")]
+    UnknownBlendMode(String),
+
+    #[error("Failed to load or save image: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("Failed to access file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Number of channels of a color type or structure, from its name as given by `ColorString`.
+fn channel_count(color: &str) -> Option<usize> {
+    if color.starts_with("Rgba") {
+        Some(4)
+    } else if color.starts_with("Rgb") {
+        Some(3)
+    } else if color.starts_with("La") {
+        Some(2)
+    } else if color.starts_with('L') {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// Explain why an `UnsupportedBlend` happened, and what to do about it where possible.
+fn unsupported_blend_message(a: &str, b: &str) -> String {
+    let message = format!("Image 'a' of type {a} cannot accept blends from image 'b' of type {b}");
+    let (Some(channels_a), Some(channels_b)) = (channel_count(a), channel_count(b)) else {
+        return message;
+    };
+    if channels_a <= 2 && channels_b > 2 {
+        format!("{message}: cannot blend a {channels_b}-channel rgb source into a {channels_a}-channel luma destination. Convert 'a' to an rgb type first (e.g. with `convert_to`), or 'b' to luma if its color is not needed (e.g. with `BlendOptions::rgb_into_luma`)")
+    } else if channels_a != channels_b {
+        format!("{message}: a {channels_a}-channel image and a {channels_b}-channel image have different channel layouts. Convert one to the layout of the other first (e.g. with `convert_to`)")
+    } else {
+        message
+    }
 }
@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use image::{ColorType, DynamicImage};
+
+use crate::{dynamic_blend::DynamicChops, enums::AlphaMode, error::Error, options::BlendOptions};
+
+/**
+Load `bottom` and `top` from disk, blend `top` into `bottom` and save the result to `output`.
+
+Each input declares whether its color is straight or premultiplied. Premultiplied inputs are unpremultiplied at full precision before blending, so the blend sees the straight alpha it expects and the output is always written with straight alpha, in the color type of `bottom`. Images without an alpha channel are the same either way.
+
+# Errors
+
+`Image`: an input could not be loaded, or the output could not be saved
+
+Any error returned by `blend_with_options`, e.g. `DimensionMismatch` if the images are different sizes
+
+# Examples
+
+```
+use image_blend::{blend_files_premul_aware, AlphaMode, BlendOptions};
+use image_blend::pixelops::pixel_mult;
+
+blend_files_premul_aware(
+    "test_data/1.png",
+    AlphaMode::Straight,
+    "test_data/2.png",
+    AlphaMode::Premultiplied,
+    "tests_out/doctest_blend_files_premul_aware_result.png",
+    &BlendOptions::new(pixel_mult),
+).unwrap();
+```
+*/
+pub fn blend_files_premul_aware(
+    bottom: impl AsRef<Path>,
+    bottom_alpha: AlphaMode,
+    top: impl AsRef<Path>,
+    top_alpha: AlphaMode,
+    output: impl AsRef<Path>,
+    options: &BlendOptions,
+) -> Result<(), Error> {
+    let bottom = image::open(bottom)?;
+    let top = image::open(top)?;
+    let color_type = bottom.color();
+    let mut bottom = to_straight(bottom, bottom_alpha)?;
+    let top = to_straight(top, top_alpha)?;
+    bottom.blend_with_options(&top, options)?;
+    bottom.convert_to(color_type)?.save(output)?;
+    Ok(())
+}
+
+/// Convert a loaded image to straight alpha, widening it first so little precision is lost dividing by alpha.
+///
+/// Luma images stay luma, as `La16` is the widest luma type with alpha, so they can still be blended with luma images.
+fn to_straight(image: DynamicImage, alpha: AlphaMode) -> Result<DynamicImage, Error> {
+    if alpha == AlphaMode::Straight || !image.color().has_alpha() {
+        return Ok(image);
+    }
+    let wide = if image.color().has_color() { ColorType::Rgba32F } else { ColorType::La16 };
+    let mut straight = image.convert_to(wide)?;
+    straight.unpremultiply()?;
+    Ok(straight)
+}
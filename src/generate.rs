@@ -0,0 +1,182 @@
+use std::{iter::zip, ops::DerefMut};
+
+use image::{ColorType, DynamicImage, ImageBuffer, Pixel};
+
+use crate::{
+    blend_ops::{denormalize, type_max},
+    enums::{ColorStructure, Direction},
+    error::Error,
+};
+
+/**
+Create an image of `color_type` where every pixel is `color`.
+
+`color` holds one normalized value (0.0..1.0) per channel of `color_type`, e.g. 4 values `[r, g, b, a]` for an rgba image or 1 value for a luma image.
+
+# Errors
+
+`InvalidArgument`: `color` does not have one value per channel of `color_type`
+
+`UnsupportedType`: `color_type` is not supported
+
+# Examples
+
+```
+use image::ColorType;
+use image_blend::{gradient, solid_color, Direction, DynamicChops};
+use image_blend::pixelops::pixel_mult;
+
+let mut img1_dynamic = gradient(64, 64, Direction::Horizontal, ColorType::Rgba8, &[0., 0., 0., 1.], &[1., 1., 1., 1.]).unwrap();
+
+// Tint the image magenta
+let magenta = solid_color(img1_dynamic.width(), img1_dynamic.height(), ColorType::Rgba16, &[1., 0., 1., 1.]).unwrap();
+img1_dynamic.blend(&magenta, pixel_mult, true, false).unwrap();
+img1_dynamic.save("tests_out/doctest_solid_color_result.png").unwrap();
+```
+*/
+pub fn solid_color(width: u32, height: u32, color_type: ColorType, color: &[f64]) -> Result<DynamicImage, Error> {
+    check_color(color_type, color)?;
+    let mut image = DynamicImage::new(width, height, color_type);
+    match &mut image {
+        DynamicImage::ImageLuma8(buf) => fill(buf, color),
+        DynamicImage::ImageLumaA8(buf) => fill(buf, color),
+        DynamicImage::ImageRgb8(buf) => fill(buf, color),
+        DynamicImage::ImageRgba8(buf) => fill(buf, color),
+        DynamicImage::ImageLuma16(buf) => fill(buf, color),
+        DynamicImage::ImageLumaA16(buf) => fill(buf, color),
+        DynamicImage::ImageRgb16(buf) => fill(buf, color),
+        DynamicImage::ImageRgba16(buf) => fill(buf, color),
+        DynamicImage::ImageRgb32F(buf) => fill(buf, color),
+        DynamicImage::ImageRgba32F(buf) => fill(buf, color),
+        _ => return Err(Error::UnsupportedType),
+    }
+    Ok(image)
+}
+fn fill<P, Container>(subject: &mut ImageBuffer<P, Container>, color: &[f64])
+where
+    P: Pixel,
+    Container: DerefMut<Target = [P::Subpixel]>,
+{
+    let max = type_max::<P>();
+    let values: Vec<P::Subpixel> = color.iter().map(|&v| denormalize::<P>(v, max)).collect();
+    subject.pixels_mut().for_each(|px| {
+        px.channels_mut().copy_from_slice(&values);
+    });
+}
+
+/**
+Create an image of `color_type` filled with a checkerboard of `cell` x `cell` squares, alternating between `a` and `b`.
+
+The top left square is `a`. `a` and `b` hold one normalized value (0.0..1.0) per channel of `color_type`, as for `solid_color`. Useful for deterministic test inputs, or as a backdrop that shows transparency.
+
+# Errors
+
+`InvalidArgument`: `cell` is 0, or `a` or `b` does not have one value per channel of `color_type`
+
+`UnsupportedType`: `color_type` is not supported
+
+# Examples
+
+```
+use image::ColorType;
+use image_blend::{checkerboard, gradient, Direction, DynamicChops};
+use image_blend::pixelops::pixel_overlay;
+
+let mut img = gradient(64, 64, Direction::Vertical, ColorType::Rgb8, &[1., 0., 0.], &[0., 0., 1.]).unwrap();
+let checker = checkerboard(64, 64, 8, ColorType::Rgb8, &[0.25, 0.25, 0.25], &[0.75, 0.75, 0.75]).unwrap();
+img.blend(&checker, pixel_overlay, true, false).unwrap();
+img.save("tests_out/doctest_checkerboard_result.png").unwrap();
+```
+*/
+pub fn checkerboard(width: u32, height: u32, cell: u32, color_type: ColorType, a: &[f64], b: &[f64]) -> Result<DynamicImage, Error> {
+    if cell == 0 {
+        return Err(Error::InvalidArgument("cell must be at least 1"));
+    }
+    check_color(color_type, a)?;
+    check_color(color_type, b)?;
+    let mut image = DynamicImage::new(width, height, color_type);
+    paint(&mut image, |x, y| if (x / cell + y / cell).is_multiple_of(2) { a.to_vec() } else { b.to_vec() })?;
+    Ok(image)
+}
+
+/**
+Create an image of `color_type` fading linearly from `from` to `to` along `direction`.
+
+The first column (or row) is exactly `from` and the last exactly `to`. `from` and `to` hold one normalized value (0.0..1.0) per channel of `color_type`, as for `solid_color`, so alpha can be faded too.
+
+# Errors
+
+`InvalidArgument`: `from` or `to` does not have one value per channel of `color_type`
+
+`UnsupportedType`: `color_type` is not supported
+
+# Examples
+
+```
+use image::ColorType;
+use image_blend::{gradient, Direction};
+
+// A black to white ramp, e.g. as a test input for a blend mode
+let ramp = gradient(256, 16, Direction::Horizontal, ColorType::L8, &[0.], &[1.]).unwrap();
+assert_eq!(ramp.as_luma8().unwrap().get_pixel(255, 0).0, [255]);
+```
+*/
+pub fn gradient(width: u32, height: u32, direction: Direction, color_type: ColorType, from: &[f64], to: &[f64]) -> Result<DynamicImage, Error> {
+    check_color(color_type, from)?;
+    check_color(color_type, to)?;
+    let steps = match direction {
+        Direction::Horizontal => width,
+        Direction::Vertical => height,
+    }
+    .saturating_sub(1)
+    .max(1);
+    let mut image = DynamicImage::new(width, height, color_type);
+    paint(&mut image, |x, y| {
+        let position = match direction {
+            Direction::Horizontal => x,
+            Direction::Vertical => y,
+        };
+        let t = <f64 as From<u32>>::from(position) / <f64 as From<u32>>::from(steps);
+        zip(from, to).map(|(from, to)| from + (to - from) * t).collect()
+    })?;
+    Ok(image)
+}
+
+fn check_color(color_type: ColorType, color: &[f64]) -> Result<(), Error> {
+    let structure: ColorStructure = match color_type {
+        ColorType::L8 | ColorType::La8 | ColorType::Rgb8 | ColorType::Rgba8 | ColorType::L16 | ColorType::La16
+        | ColorType::Rgb16 | ColorType::Rgba16 | ColorType::Rgb32F | ColorType::Rgba32F => color_type.into(),
+        _ => return Err(Error::UnsupportedType),
+    };
+    if color.len() != structure.channels() {
+        return Err(Error::InvalidArgument("color must have one value per channel"));
+    }
+    Ok(())
+}
+/// Set every pixel of `image` to the normalized color `color` returns for its position.
+fn paint(image: &mut DynamicImage, color: impl Fn(u32, u32) -> Vec<f64>) -> Result<(), Error> {
+    match image {
+        DynamicImage::ImageLuma8(buf) => paint_buffer(buf, color),
+        DynamicImage::ImageLumaA8(buf) => paint_buffer(buf, color),
+        DynamicImage::ImageRgb8(buf) => paint_buffer(buf, color),
+        DynamicImage::ImageRgba8(buf) => paint_buffer(buf, color),
+        DynamicImage::ImageLuma16(buf) => paint_buffer(buf, color),
+        DynamicImage::ImageLumaA16(buf) => paint_buffer(buf, color),
+        DynamicImage::ImageRgb16(buf) => paint_buffer(buf, color),
+        DynamicImage::ImageRgba16(buf) => paint_buffer(buf, color),
+        DynamicImage::ImageRgb32F(buf) => paint_buffer(buf, color),
+        DynamicImage::ImageRgba32F(buf) => paint_buffer(buf, color),
+        _ => return Err(Error::UnsupportedType),
+    }
+    Ok(())
+}
+fn paint_buffer<P, Container>(subject: &mut ImageBuffer<P, Container>, color: impl Fn(u32, u32) -> Vec<f64>)
+where
+    P: Pixel,
+    Container: DerefMut<Target = [P::Subpixel]>,
+{
+    let max = type_max::<P>();
+    subject.enumerate_pixels_mut().for_each(|(x, y, px)| {
+        zip(px.channels_mut(), color(x, y)).for_each(|(v, c)| *v = denormalize::<P>(c, max));
+    });
+}
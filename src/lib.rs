@@ -12,6 +12,8 @@ Also provide support for getting alpha channnels as grayscale images, setting al
 
 The only limitation to this is that you cannot blend an Rgb/Rgba image into a Luma image.
 
+Subpixels can be any type image supports: `u8`, `u16` and `f32`, and also `f64` in an `ImageBuffer`. Float subpixels are normalized against 1.0. Half floats (`half::f16`) are not supported, as image does not implement `Primitive` for them and its rgb pixels can't hold a wrapper type, so convert them to `f32` first.
+
 ## Usage:
 
 Syntax is the same when working with Dynamic and Imagebuffer.
@@ -161,19 +163,95 @@ img1_dynamic.blend(&img2_dynamic, closest_to_gray, true, false).unwrap();
 img1_dynamic.save("tests_out/doctest_dynamic_custom_result.png").unwrap();
 
 ```
+
+## Features
+
+`rayon`: parallelise reductions across multiple images (e.g. `blend_all_average`). Output is deterministic regardless of thread count, as every reduction uses a fixed pairwise association.
+
+`log`: emit a `debug!` record after each blend with how many blended subpixels hit (or were clamped to) 0 or 1, to help diagnose blown out composites.
+
+`registry`: a process wide registry of named blend functions (`register_blend_mode`, `blend_named`), so host applications can let plugins add blend modes at runtime.
+
+`mmap`: blend raw images stored in files through memory maps (`blend_mapped_files`), for images too large to load into memory.
 */
+pub(crate) mod accumulate;
 pub(crate) mod blend_ops;
+pub(crate) mod color;
+pub(crate) mod compat;
 pub(crate) mod dynamic_blend;
+pub(crate) mod files;
+pub(crate) mod generate;
+pub(crate) mod lut;
 pub(crate) mod alpha_ops;
+pub(crate) mod map_ops;
+pub(crate) mod metrics;
+#[cfg(feature = "mmap")]
+pub(crate) mod mmap;
+pub(crate) mod options;
+pub(crate) mod premul;
+pub(crate) mod reduce;
+#[cfg(feature = "registry")]
+pub(crate) mod registry;
+pub(crate) mod slices;
+pub(crate) mod strategy;
 
 mod enums;
 mod error;
 mod tests;
 
+pub use accumulate::BlendAccumulator;
+pub use color::LumaCoefficients;
+pub use compat::can_blend;
+pub use compat::supported_combinations;
+pub use compat::CompatibilityReport;
+pub use enums::AlphaCoverage;
+pub use enums::AlphaMode;
+pub use enums::Anchor;
+pub use enums::Arithmetic;
+pub use enums::BlendMode;
+pub use enums::Channel;
+pub use enums::DimensionPolicy;
+pub use enums::Direction;
+pub use enums::DivideZero;
+pub use enums::ColorModel;
+pub use enums::ComponentBlend;
+pub use enums::GamutClip;
+pub use enums::IntOp;
+pub use enums::MismatchPolicy;
+pub use enums::NormalBlend;
+pub use enums::Rect;
+pub use enums::Rounding;
+pub use enums::TonalRange;
 pub use error::Error;
+pub mod normalize;
 pub mod pixelops;
+pub use alpha_ops::BufferAdjustAlpha;
 pub use alpha_ops::BufferGetAlpha;
 pub use alpha_ops::BufferSetAlpha;
 pub use alpha_ops::BufferStripAlpha;
 pub use blend_ops::BufferBlend;
+pub use blend_ops::blend_generic;
+pub use map_ops::BufferMapPixels;
+pub use metrics::mse;
+pub use metrics::psnr;
+pub use metrics::verify_blend;
+#[cfg(feature = "mmap")]
+pub use mmap::blend_mapped_files;
+pub use options::BlendOptions;
+pub use premul::Premultiplied;
+pub use premul::Straight;
 pub use dynamic_blend::DynamicChops;
+pub use dynamic_blend::join_alpha;
+pub use dynamic_blend::match_types;
+pub use files::blend_files_premul_aware;
+pub use generate::checkerboard;
+pub use generate::gradient;
+pub use generate::solid_color;
+pub use lut::Lut3D;
+pub use reduce::blend_all_average;
+#[cfg(feature = "registry")]
+pub use registry::{blend_named, named_blend_mode, register_blend_mode};
+pub use slices::blend_chunk;
+pub use slices::blend_slices;
+pub use slices::SliceLayout;
+pub use strategy::BlendStrategy;
@@ -0,0 +1,97 @@
+use crate::error::Error;
+
+/**
+A 3D color lookup table, mapping rgb colors to rgb colors, as used for color grading (e.g. loaded from a `.cube` file).
+
+The table samples the rgb cube on a `size` x `size` x `size` grid, and colors between grid points are interpolated trilinearly. Entries are ordered as in `.cube` files, with red changing fastest and blue slowest: the entry for grid point (`r`, `g`, `b`) is at index `r + g * size + b * size * size`. All values are normalized (0.0..1.0).
+
+# Examples
+
+```
+use image_blend::Lut3D;
+
+// A 2x2x2 table that swaps the red and blue channels
+let table = (0..8).map(|i| [(i >> 2 & 1) as f64, (i >> 1 & 1) as f64, (i & 1) as f64]).collect();
+let swap = Lut3D::new(2, table).unwrap();
+assert_eq!(swap.sample([1., 0.5, 0.]), [0., 0.5, 1.]);
+```
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut3D {
+    size: usize,
+    table: Vec<[f64; 3]>,
+}
+impl Lut3D {
+    /**
+    Create a table with `size` grid points along each axis from its entries.
+
+    # Errors
+
+    `InvalidArgument`: `size` is less than 2, or `table` does not have `size * size * size` entries
+    */
+    pub fn new(size: usize, table: Vec<[f64; 3]>) -> Result<Self, Error> {
+        if size < 2 {
+            return Err(Error::InvalidArgument("size must be at least 2"));
+        }
+        if size.checked_pow(3) != Some(table.len()) {
+            return Err(Error::InvalidArgument("table must have size * size * size entries"));
+        }
+        Ok(Self { size, table })
+    }
+    /**
+    A table that maps every color to itself, as a starting point for building one.
+
+    # Panics
+
+    If `size` is less than 2.
+    */
+    #[must_use]
+    pub fn identity(size: usize) -> Self {
+        assert!(size >= 2, "size must be at least 2");
+        #[allow(clippy::cast_precision_loss)]
+        let level = |i: usize| i as f64 / (size - 1) as f64;
+        let table = (0..size.pow(3)).map(|i| [level(i % size), level(i / size % size), level(i / (size * size))]).collect();
+        Self { size, table }
+    }
+    /// Number of grid points along each axis.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    /// The table's entries, in the order described for `Lut3D`.
+    #[must_use]
+    pub fn table(&self) -> &[[f64; 3]] {
+        &self.table
+    }
+    /// Look up `rgb`, interpolating trilinearly between the surrounding grid points. Inputs are clamped to 0.0..1.0.
+    #[must_use]
+    pub fn sample(&self, rgb: [f64; 3]) -> [f64; 3] {
+        let [(r0, r1, rt), (g0, g1, gt), (b0, b1, bt)] = rgb.map(|v| grid_position(v, self.size));
+        let at = |r: usize, g: usize, b: usize| self.table[r + g * self.size + b * self.size * self.size];
+        let lerp = |a: [f64; 3], b: [f64; 3], t: f64| [0, 1, 2].map(|ch| a[ch] + (b[ch] - a[ch]) * t);
+        let g0_plane = lerp(lerp(at(r0, g0, b0), at(r1, g0, b0), rt), lerp(at(r0, g1, b0), at(r1, g1, b0), rt), gt);
+        let g1_plane = lerp(lerp(at(r0, g0, b1), at(r1, g0, b1), rt), lerp(at(r0, g1, b1), at(r1, g1, b1), rt), gt);
+        lerp(g0_plane, g1_plane, bt)
+    }
+}
+
+/// Look up `v` in a 1D table, interpolating linearly between its entries for each channel independently. The entries are spread evenly over 0.0..1.0.
+pub(crate) fn sample_1d(lut: &[[f64; 3]], rgb: [f64; 3]) -> [f64; 3] {
+    let mut out = [0.; 3];
+    for ch in 0..3 {
+        let (i0, i1, t) = grid_position(rgb[ch], lut.len());
+        out[ch] = lut[i0][ch] + (lut[i1][ch] - lut[i0][ch]) * t;
+    }
+    out
+}
+
+/// The grid points either side of the normalized value `v` on a grid of `size` points, and how far `v` is between them.
+fn grid_position(v: f64, size: usize) -> (usize, usize, f64) {
+    #[allow(clippy::cast_precision_loss)]
+    let position = v.clamp(0., 1.) * (size - 1) as f64;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let i0 = (position.floor() as usize).min(size - 1);
+    #[allow(clippy::cast_precision_loss)]
+    let t = position - i0 as f64;
+    (i0, (i0 + 1).min(size - 1), t)
+}
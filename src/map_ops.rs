@@ -0,0 +1,487 @@
+use std::ops::DerefMut;
+
+use image::{ImageBuffer, Pixel};
+use num_traits::NumCast;
+
+use crate::{blend_ops::{denormalize, type_max}, color::{luminance, LumaCoefficients}, enums::ColorStructure, error::Error, lut::{sample_1d, Lut3D}};
+
+pub trait BufferMapPixels<Pmut, ContainerMut>
+where
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    /**
+    Apply the function `f` to every channel of every pixel of this image.
+
+    Values are normalized to 0.0..1.0 before being passed to `f`, and the output of `f` is clamped to 0.0..1.0 and rounded to the nearest value of the image's type.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected. If `apply_to_alpha` is true but `self` does not have an alpha channel, this option has no effect.
+
+    # Errors
+
+    `UnsupportedType`: `self` has an unsupported number of channels
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferMapPixels;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    // Darken the image
+    img1_buffer.map_pixels(|v| v * 0.5, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_map_pixels_result.png").unwrap();
+    ```
+    */
+    fn map_pixels<F: Fn(f64) -> f64>(
+        &mut self,
+        f: F,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Apply the function `f` to every channel of every pixel of this image, passing the pixel's coordinates as well as its value (`f(x, y, value)`).
+
+    Useful for procedural effects such as vignettes, gradients and noise that depend on position rather than on a second image.
+
+    Values are normalized to 0.0..1.0 before being passed to `f`, and the output of `f` is clamped to 0.0..1.0 and rounded to the nearest value of the image's type.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected. If `apply_to_alpha` is true but `self` does not have an alpha channel, this option has no effect.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferMapPixels;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let (width, height) = (img1_dynamic.width(), img1_dynamic.height());
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    // Darken towards the right hand side of the image
+    img1_buffer.map_pixels_positional(|x, _y, v| v * (1. - f64::from(x) / f64::from(width)), true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_map_pixels_positional_result.png").unwrap();
+    ```
+    */
+    fn map_pixels_positional<F: Fn(u32, u32, f64) -> f64>(
+        &mut self,
+        f: F,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+    /**
+    Invert this image, replacing every value `v` with `1 - v`.
+
+    Unlike `image::imageops::invert` (and `DynamicImage::invert`), the alpha channel can be inverted too.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected.
+
+    # Errors
+
+    `UnsupportedType`: `self` has an unsupported number of channels
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferMapPixels;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    img1_buffer.invert_channels(true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_invert_channels_result.png").unwrap();
+    ```
+    */
+    fn invert_channels(
+        &mut self,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Apply a levels adjustment, remapping every value `v` to `((v - black) / (white - black)).clamp(0, 1).powf(1 / gamma)`.
+
+    Values at or below `black` become 0, values at or above `white` become 1. A `gamma` above 1 brightens the midtones, below 1 darkens them.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected.
+
+    # Errors
+
+    `InvalidArgument`: `black`, `white` or `gamma` is not finite, `white` is not greater than `black`, or `gamma` is not positive
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferMapPixels;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    img1_buffer.levels(0.1, 0.9, 1.2, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_levels_result.png").unwrap();
+    ```
+    */
+    fn levels(
+        &mut self,
+        black: f64,
+        white: f64,
+        gamma: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend a constant value into every channel of this image, as if blending with a solid image where every channel is `value`, where arg 0 of `op` is self and 1 is `value`.
+
+    `value` is normalized (0.0..1.0). This is cheaper than building a solid image to blend with, and useful for operations against a fixed pivot such as a contrast boost around 0.5.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected. If `apply_to_alpha` is true but `self` does not have an alpha channel, this option has no effect.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferMapPixels;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    // Boost contrast around the midtones
+    img1_buffer.blend_against_constant(0.5, |a, pivot| pivot + (a - pivot) * 1.5, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_against_constant_result.png").unwrap();
+    ```
+    */
+    fn blend_against_constant(
+        &mut self,
+        value: f64,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Blend a virtual image generated by `gen` into `self`, where `gen(x, y)` returns the normalized rgba value of the virtual image at each pixel, and arg 0 of `op` is self and 1 is the generated value.
+
+    Behaves like `blend` with the generated image as `other`, including weighting by its alpha, but without allocating a second image. Useful for procedural overlays such as gradients and noise. Luma images use the red channel of the generated value.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferMapPixels;
+    use image_blend::pixelops::pixel_screen;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let width = f64::from(img1_dynamic.width());
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    // Screen a horizontal gradient that fades out towards the right
+    let gradient = |x: u32, _y: u32| [1., 0.5, 0., 1. - f64::from(x) / width];
+    img1_buffer.blend_generated(gradient, pixel_screen, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_blend_generated_result.png").unwrap();
+    ```
+    */
+    fn blend_generated<G: Fn(u32, u32) -> [f64; 4]>(
+        &mut self,
+        gen: G,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Threshold this image, snapping every value to 0 if it is below `cutoff` and to the maximum otherwise.
+
+    `cutoff` is normalized (0.0..1.0). Each channel is thresholded on its own, so an rgb image ends up with at most 8 colors, and a luma image is converted to pure black and white.
+
+    Use `apply_to_color` and `apply_to_alpha` to control which channels are affected.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferMapPixels;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+
+    img1_buffer.threshold(0.5, true, false).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_threshold_result.png").unwrap();
+    ```
+    */
+    fn threshold(
+        &mut self,
+        cutoff: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error>;
+
+    /**
+    Map the color of every pixel of this image through a 1D lookup table, such as a set of curves.
+
+    Each channel is looked up in its own column of `lut`: the entries are spread evenly over 0.0..1.0, so the first entry is the output for 0.0 and the last for 1.0, and values in between are interpolated linearly. All values are normalized. Luma images are treated as gray, and take the luminance (Rec. 709) of the looked up color. The alpha channel is left untouched.
+
+    # Errors
+
+    `InvalidArgument`: `lut` is empty
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::BufferMapPixels;
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+    // Warm the image: lift red, lower blue
+    let lut = [[0.1, 0., 0.], [0.65, 0.5, 0.4], [1., 1., 0.9]];
+    img1_buffer.apply_lut_1d(&lut).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_apply_lut_1d_result.png").unwrap();
+    ```
+    */
+    fn apply_lut_1d(
+        &mut self,
+        lut: &[[f64; 3]],
+    ) -> Result<(), Error>;
+
+    /**
+    Map the color of every pixel of this image through the 3D lookup table `lut`, e.g. a color grade loaded from a `.cube` file.
+
+    Colors between the table's grid points are interpolated trilinearly. Luma images are treated as gray, and take the luminance (Rec. 709) of the looked up color. The alpha channel is left untouched.
+
+    # Errors
+
+    `UnsupportedType`: `self` is of an unsupported color type
+
+    # Examples
+
+    ```
+    use image::open;
+    use image_blend::{BufferMapPixels, Lut3D};
+
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    let mut img1_buffer = img1_dynamic.as_mut_rgba8().unwrap();
+    // Desaturate halfway by mixing each color with its gray
+    let mut table = Lut3D::identity(17).table().to_vec();
+    for color in &mut table {
+        let gray = (color[0] + color[1] + color[2]) / 3.;
+        *color = color.map(|v| (v + gray) / 2.);
+    }
+    let lut = Lut3D::new(17, table).unwrap();
+    img1_buffer.apply_lut_3d(&lut).unwrap();
+    img1_buffer.save("tests_out/doctest_buffer_apply_lut_3d_result.png").unwrap();
+    ```
+    */
+    fn apply_lut_3d(
+        &mut self,
+        lut: &Lut3D,
+    ) -> Result<(), Error>;
+}
+impl<Pmut, ContainerMut> BufferMapPixels<Pmut, ContainerMut> for ImageBuffer<Pmut, ContainerMut>
+where
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    fn map_pixels<F: Fn(f64) -> f64>(
+        &mut self,
+        f: F,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        self.map_pixels_positional(|_, _, v| f(v), apply_to_color, apply_to_alpha)
+    }
+    fn map_pixels_positional<F: Fn(u32, u32, f64) -> f64>(
+        &mut self,
+        f: F,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let structure: ColorStructure = self.sample_layout().try_into()?;
+        let alpha_channel = structure.alpha_channel();
+        let channels: Vec<usize> = (0..structure.channels())
+            .filter(|ch| {
+                if Some(*ch) == alpha_channel {
+                    apply_to_alpha
+                } else {
+                    apply_to_color
+                }
+            })
+            .collect();
+
+        let max = type_max::<Pmut>();
+
+        self.enumerate_pixels_mut().for_each(|(x, y, px)| {
+            let px_channels = px.channels_mut();
+            for &ch in &channels {
+                let v_f64: f64 = <f64 as NumCast>::from(px_channels[ch]).unwrap() / max;
+                px_channels[ch] = denormalize::<Pmut>(f(x, y, v_f64), max);
+            }
+        });
+        Ok(())
+    }
+    fn invert_channels(
+        &mut self,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        self.map_pixels(|v| 1. - v, apply_to_color, apply_to_alpha)
+    }
+    fn levels(
+        &mut self,
+        black: f64,
+        white: f64,
+        gamma: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        if !(black.is_finite() && white.is_finite() && gamma.is_finite()) {
+            return Err(Error::InvalidArgument("black, white and gamma must be finite"));
+        }
+        if white <= black {
+            return Err(Error::InvalidArgument("white must be greater than black"));
+        }
+        if gamma <= 0. {
+            return Err(Error::InvalidArgument("gamma must be positive"));
+        }
+        self.map_pixels(
+            |v| ((v - black) / (white - black)).clamp(0., 1.).powf(1. / gamma),
+            apply_to_color,
+            apply_to_alpha,
+        )
+    }
+    fn blend_against_constant(
+        &mut self,
+        value: f64,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        self.map_pixels(|v| op(v, value), apply_to_color, apply_to_alpha)
+    }
+    fn blend_generated<G: Fn(u32, u32) -> [f64; 4]>(
+        &mut self,
+        gen: G,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        let structure: ColorStructure = self.sample_layout().try_into()?;
+        let alpha_channel = structure.alpha_channel();
+        // Pairs of (channel in self, channel in the generated rgba value)
+        let color_channels: Vec<(usize, usize)> = if structure.rgb() {
+            vec![(0, 0), (1, 1), (2, 2)]
+        } else {
+            vec![(0, 0)]
+        };
+
+        let max = type_max::<Pmut>();
+
+        self.enumerate_pixels_mut().for_each(|(x, y, px)| {
+            let generated = gen(x, y);
+            let px_channels = px.channels_mut();
+            let alpha_weight = generated[3].clamp(0., 1.);
+            if apply_to_color && alpha_weight != 0. {
+                for &(ch_a, ch_b) in &color_channels {
+                    let a_f64: f64 = <f64 as NumCast>::from(px_channels[ch_a]).unwrap() / max;
+                    let new_64 = op(a_f64, generated[ch_b]) * alpha_weight + a_f64 * (1. - alpha_weight);
+                    px_channels[ch_a] = denormalize::<Pmut>(new_64, max);
+                }
+            }
+            if let (true, Some(alpha_a)) = (apply_to_alpha, alpha_channel) {
+                let a_f64: f64 = <f64 as NumCast>::from(px_channels[alpha_a]).unwrap() / max;
+                px_channels[alpha_a] = denormalize::<Pmut>(op(a_f64, generated[3]), max);
+            }
+        });
+        Ok(())
+    }
+    fn threshold(
+        &mut self,
+        cutoff: f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        self.map_pixels(|v| if v < cutoff { 0. } else { 1. }, apply_to_color, apply_to_alpha)
+    }
+    fn apply_lut_1d(
+        &mut self,
+        lut: &[[f64; 3]],
+    ) -> Result<(), Error> {
+        if lut.is_empty() {
+            return Err(Error::InvalidArgument("lut must not be empty"));
+        }
+        map_color(self, |rgb| sample_1d(lut, rgb))
+    }
+    fn apply_lut_3d(
+        &mut self,
+        lut: &Lut3D,
+    ) -> Result<(), Error> {
+        map_color(self, |rgb| lut.sample(rgb))
+    }
+}
+/// Replace the color channels of an rgb image with their luminance. Luma images are left unchanged.
+pub(crate) fn luminance_in_place<Pmut, ContainerMut>(image: &mut ImageBuffer<Pmut, ContainerMut>, coefficients: LumaCoefficients) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    let structure: ColorStructure = image.sample_layout().try_into()?;
+    if !structure.rgb() {
+        return Ok(());
+    }
+    let max = type_max::<Pmut>();
+    image.pixels_mut().for_each(|px| {
+        let channels = px.channels_mut();
+        let rgb = [0, 1, 2].map(|ch| <f64 as NumCast>::from(channels[ch]).unwrap() / max);
+        let luma = denormalize::<Pmut>(coefficients.luminance(rgb), max);
+        channels[..3].fill(luma);
+    });
+    Ok(())
+}
+/// Replace the color of every pixel with `f` of it. Luma images are passed to `f` as gray, and take the luminance of its result.
+fn map_color<Pmut, ContainerMut>(image: &mut ImageBuffer<Pmut, ContainerMut>, f: impl Fn([f64; 3]) -> [f64; 3]) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    ContainerMut: DerefMut<Target = [Pmut::Subpixel]> + AsMut<[<Pmut as Pixel>::Subpixel]>,
+{
+    let structure: ColorStructure = image.sample_layout().try_into()?;
+    let max = type_max::<Pmut>();
+    image.pixels_mut().for_each(|px| {
+        let channels = px.channels_mut();
+        if structure.rgb() {
+            let rgb = [0, 1, 2].map(|ch| <f64 as NumCast>::from(channels[ch]).unwrap() / max);
+            let mapped = f(rgb);
+            for ch in 0..3 {
+                channels[ch] = denormalize::<Pmut>(mapped[ch], max);
+            }
+        } else {
+            let gray = <f64 as NumCast>::from(channels[0]).unwrap() / max;
+            channels[0] = denormalize::<Pmut>(luminance(f([gray; 3])), max);
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use image::{ColorType, DynamicImage, GenericImageView, ImageBuffer, Pixel};
+
+use crate::{
+    blend_ops::{is_float, type_max, BufferBlend},
+    enums::{ColorString, ColorStructure},
+    error::Error,
+};
+
+/**
+Mean squared error between two images, over values normalized to 0.0..1.0.
+
+The images may have different bit depths, but must have the same channel layout (e.g. both Rgba). Every channel, including alpha, contributes equally.
+
+# Errors
+
+`DimensionMismatch`: `a` and `b` have different dimensions
+
+`UnsupportedBlend`: `a` and `b` have different channel layouts
+
+`UnsupportedType`: `a` or `b` is of an unsupported color type
+
+# Examples
+
+```
+use image::open;
+use image_blend::mse;
+
+let img1_dynamic = open("test_data/1.png").unwrap();
+let img2_dynamic = open("test_data/2.png").unwrap();
+let error = mse(&img1_dynamic, &img2_dynamic).unwrap();
+```
+*/
+pub fn mse(a: &DynamicImage, b: &DynamicImage) -> Result<f64, Error> {
+    if a.dimensions() != b.dimensions() {
+        return Err(Error::DimensionMismatch);
+    }
+    let structure_a = color_structure(a)?;
+    let structure_b = color_structure(b)?;
+    if structure_a != structure_b {
+        return Err(Error::UnsupportedBlend(structure_a.color_str(), structure_b.color_str()));
+    }
+    let channels: Vec<usize> = match structure_a {
+        ColorStructure::L => vec![0],
+        ColorStructure::La => vec![0, 3],
+        ColorStructure::Rgb => vec![0, 1, 2],
+        ColorStructure::Rgba => vec![0, 1, 2, 3],
+    };
+    // Convert to a common normalized type. Luma is replicated across rgb, so only the first channel is read.
+    let (float_a, float_b) = (a.to_rgba32f(), b.to_rgba32f());
+    let (sum, count) = float_a.pixels().zip(float_b.pixels()).fold((0., 0u64), |(sum, count), (px_a, px_b)| {
+        let px_sum: f64 = channels
+            .iter()
+            .map(|&ch| (f64::from(px_a.0[ch]) - f64::from(px_b.0[ch])).powi(2))
+            .sum();
+        (sum + px_sum, count + channels.len() as u64)
+    });
+    if count == 0 {
+        return Ok(0.);
+    }
+    #[allow(clippy::cast_precision_loss)]
+    Ok(sum / count as f64)
+}
+
+/**
+Peak signal to noise ratio between two images in decibels, using a peak value of 1.0 over normalized values.
+
+Identical images return `f64::INFINITY`. Higher values mean the images are more similar.
+
+# Errors
+
+See `mse`.
+
+# Examples
+
+```
+use image::open;
+use image_blend::{psnr, DynamicChops};
+use image_blend::pixelops::pixel_screen;
+
+let img1_dynamic = open("test_data/1.png").unwrap();
+let img2_dynamic = open("test_data/2.png").unwrap();
+
+let mut blended = img1_dynamic.clone();
+blended.blend(&img2_dynamic, pixel_screen, true, false).unwrap();
+let change = psnr(&img1_dynamic, &blended).unwrap();
+```
+*/
+pub fn psnr(a: &DynamicImage, b: &DynamicImage) -> Result<f64, Error> {
+    let mse = mse(a, b)?;
+    if mse == 0. {
+        return Ok(f64::INFINITY);
+    }
+    Ok(-10. * mse.log10())
+}
+
+fn color_structure(image: &DynamicImage) -> Result<ColorStructure, Error> {
+    match image.color() {
+        ColorType::L8 | ColorType::L16 => Ok(ColorStructure::L),
+        ColorType::La8 | ColorType::La16 => Ok(ColorStructure::La),
+        ColorType::Rgb8 | ColorType::Rgb16 | ColorType::Rgb32F => Ok(ColorStructure::Rgb),
+        ColorType::Rgba8 | ColorType::Rgba16 | ColorType::Rgba32F => Ok(ColorStructure::Rgba),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+
+/**
+Check that `a.blend(b, op, true, false)` matches a plain scalar reference implementation of the same blend, panicking if any color subpixel differs by more than `tolerance`.
+
+The reference normalizes every subpixel to 0.0..1.0, applies `op`, weights by `b`'s alpha (compositing source-over when both images have alpha, as `blend` documents), and clamps, all in `f64`. `tolerance` is in normalized units, on top of the half step that rounding to an integer type can add. Meant for downstream crates testing their own ops against this crate's quantization; keep the inputs small, as the check is slow.
+
+# Panics
+
+If a subpixel differs from the reference by more than `tolerance`, or the blend itself fails.
+
+# Examples
+
+```
+use image::{ImageBuffer, Rgba};
+use image_blend::verify_blend;
+use image_blend::pixelops::pixel_overlay;
+
+let a: ImageBuffer<Rgba<u16>, Vec<u16>> = ImageBuffer::from_fn(4, 4, |x, y| Rgba([x as u16 * 16000, y as u16 * 16000, 30000, 65535]));
+let b: ImageBuffer<Rgba<u16>, Vec<u16>> = ImageBuffer::from_fn(4, 4, |x, y| Rgba([y as u16 * 20000, 50000, x as u16 * 20000, 40000]));
+verify_blend(&a, &b, pixel_overlay, 1e-9);
+```
+*/
+pub fn verify_blend<P>(a: &ImageBuffer<P, Vec<P::Subpixel>>, b: &ImageBuffer<P, Vec<P::Subpixel>>, op: impl Fn(f64, f64) -> f64, tolerance: f64)
+where
+    P: Pixel,
+{
+    let mut actual = a.clone();
+    actual.blend(b, &op, true, false).expect("blend failed");
+    let max = type_max::<P>();
+    let has_alpha = matches!(P::CHANNEL_COUNT, 2 | 4);
+    let color_channels = if has_alpha { P::CHANNEL_COUNT - 1 } else { P::CHANNEL_COUNT } as usize;
+    let alpha_channel = P::CHANNEL_COUNT as usize - 1;
+    // Float images are not rounded
+    let quantization = if is_float::<P>() { 0. } else { 0.5 / max };
+    let normalize = |v: P::Subpixel| <f64 as num_traits::NumCast>::from(v).unwrap() / max;
+    for ((x, y, px_a), (px_b, px_actual)) in a.enumerate_pixels().zip(b.pixels().zip(actual.pixels())) {
+        let (px_a, px_b, px_actual) = (px_a.channels(), px_b.channels(), px_actual.channels());
+        let alpha_a = has_alpha.then(|| normalize(px_a[alpha_channel]));
+        let alpha_b = if has_alpha { normalize(px_b[alpha_channel]) } else { 1. };
+        for ch in 0..color_channels {
+            let (value_a, value_b) = (normalize(px_a[ch]), normalize(px_b[ch]));
+            let blended = op(value_a.clamp(0., 1.), value_b.clamp(0., 1.));
+            let expected = match alpha_a {
+                _ if alpha_b == 0. => value_a,
+                None => blended * alpha_b + value_a * (1. - alpha_b),
+                Some(alpha_a) => {
+                    let alpha_out = alpha_b + alpha_a * (1. - alpha_b);
+                    if alpha_out == 0. {
+                        value_a
+                    } else {
+                        (alpha_b * ((1. - alpha_a) * value_b + alpha_a * blended) + alpha_a * value_a * (1. - alpha_b)) / alpha_out
+                    }
+                }
+            }
+            .clamp(0., 1.);
+            let got = normalize(px_actual[ch]);
+            assert!(
+                (got - expected).abs() <= tolerance + quantization,
+                "blend of ({x}, {y}) channel {ch} gave {got}, but the reference gives {expected}"
+            );
+        }
+    }
+}
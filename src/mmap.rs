@@ -0,0 +1,90 @@
+use std::{
+    fs::{File, OpenOptions},
+    path::Path,
+};
+
+use memmap2::{Mmap, MmapMut};
+
+use crate::{
+    error::Error,
+    slices::{blend_slices, SliceLayout},
+};
+
+/**
+Blend the raw image in the file `src` into the raw image in the file `dst` in place, where both files hold interleaved 8 bit subpixels laid out as described by their layouts, and arg 0 of `op` is dst and 1 is src.
+
+Both files are memory mapped and blended with `blend_slices`, so neither image is ever read into memory as a whole: the operating system pages rows in and writes them back as the blend passes over them. This allows blending images larger than the available RAM, e.g. for gigapixel stitching. The files are raw pixel data with no header, starting at their first byte.
+
+The changes are flushed to `dst` before returning.
+
+# Errors
+
+`Io`: a file could not be opened or mapped, or the changes could not be flushed
+
+`InvalidArgument`: `dst` and `src` are the same file, which can't be mapped mutably and immutably at once
+
+Any error returned by `blend_slices`, e.g. `InvalidArgument` if a file is too short for its layout
+
+# Examples
+
+```
+use image_blend::{blend_mapped_files, SliceLayout};
+use image_blend::pixelops::pixel_mult;
+
+// Write a raw 4x4 rgba image, and a raw 4x4 rgb image
+std::fs::write("tests_out/doctest_blend_mapped_files_dst.raw", [200u8; 4 * 4 * 4]).unwrap();
+std::fs::write("tests_out/doctest_blend_mapped_files_src.raw", [128u8; 4 * 4 * 3]).unwrap();
+
+let dst_layout = SliceLayout::packed(4, 4, 4);
+let src_layout = SliceLayout::packed(4, 4, 3);
+blend_mapped_files(
+    "tests_out/doctest_blend_mapped_files_dst.raw",
+    dst_layout,
+    "tests_out/doctest_blend_mapped_files_src.raw",
+    src_layout,
+    pixel_mult,
+    true,
+    false,
+).unwrap();
+
+let result = std::fs::read("tests_out/doctest_blend_mapped_files_dst.raw").unwrap();
+assert_eq!(&result[..4], &[100, 100, 100, 200]);
+```
+*/
+pub fn blend_mapped_files(
+    dst: impl AsRef<Path>,
+    dst_layout: SliceLayout,
+    src: impl AsRef<Path>,
+    src_layout: SliceLayout,
+    op: impl Fn(f64, f64) -> f64,
+    apply_to_color: bool,
+    apply_to_alpha: bool,
+) -> Result<(), Error> {
+    let dst_file = OpenOptions::new().read(true).write(true).open(&dst)?;
+    let src_file = File::open(&src)?;
+    if same_file(&dst_file, dst.as_ref(), &src_file, src.as_ref())? {
+        return Err(Error::InvalidArgument("dst and src must be different files"));
+    }
+    // SAFETY: dst and src were checked to be different files above, so the mutable map does not alias the other one. The maps are only
+    // valid while no other process truncates or writes to the files. That can't be prevented from here, so like any memory mapped API
+    // this relies on callers not modifying the files while the blend runs.
+    let mut dst_map = unsafe { MmapMut::map_mut(&dst_file)? };
+    let src_map = unsafe { Mmap::map(&src_file)? };
+    blend_slices(&mut dst_map[..], dst_layout, &src_map[..], src_layout, op, apply_to_color, apply_to_alpha)?;
+    dst_map.flush()?;
+    Ok(())
+}
+
+/// Whether the two opened files are the same file, even when reached through different paths such as hard links.
+#[cfg(unix)]
+fn same_file(a: &File, _: &Path, b: &File, _: &Path) -> Result<bool, Error> {
+    use std::os::unix::fs::MetadataExt;
+    let (a, b) = (a.metadata()?, b.metadata()?);
+    Ok((a.dev(), a.ino()) == (b.dev(), b.ino()))
+}
+
+/// Whether the two opened files are the same file, compared by their canonical paths.
+#[cfg(not(unix))]
+fn same_file(_: &File, a: &Path, _: &File, b: &Path) -> Result<bool, Error> {
+    Ok(a.canonicalize()? == b.canonicalize()?)
+}
@@ -0,0 +1,66 @@
+/*!
+This module contains the conversions between subpixels and normalized f64 values that the blend traits use internally.
+
+Every blend normalizes subpixels to 0.0..1.0 by dividing by the maximum value of their type (1.0 for float types), calls its op, then clamps the result and scales it back, rounding to the nearest value for integer types. Custom ops and code working on raw pixels can use these to match the crate's quantization exactly.
+
+# Examples
+
+```
+use image::Rgba;
+use image_blend::normalize::{from_f64_normalized, max_value, to_f64_normalized};
+
+let max = max_value::<Rgba<u8>>();
+let half = to_f64_normalized::<Rgba<u8>>(128, max);
+assert_eq!(from_f64_normalized::<Rgba<u16>>(half, max_value::<Rgba<u16>>()), 32896);
+
+// Out of range values are clamped
+assert_eq!(from_f64_normalized::<Rgba<u8>>(1.5, max), 255);
+```
+*/
+use image::Pixel;
+use num_traits::NumCast;
+
+use crate::blend_ops::{denormalize, type_max};
+
+/// The maximum value of `P`'s subpixels as an f64: 1.0 for float types, and the largest value for integer types.
+#[must_use]
+pub fn max_value<P>() -> f64
+where
+    P: Pixel,
+{
+    type_max::<P>()
+}
+
+/// Normalize a subpixel of `P` to 0.0..1.0, where `max` is `max_value::<P>()`.
+///
+/// Float subpixels outside of 0.0..1.0 are not clamped.
+#[must_use]
+// Every subpixel type converts to f64, so this can't panic
+#[allow(clippy::missing_panics_doc)]
+pub fn to_f64_normalized<P>(value: P::Subpixel, max: f64) -> f64
+where
+    P: Pixel,
+{
+    <f64 as NumCast>::from(value).unwrap() / max
+}
+
+/// Clamp a normalized value to 0.0..1.0 and scale it to a subpixel of `P`, where `max` is `max_value::<P>()`.
+///
+/// Integer types are rounded to the nearest value, as by `BufferBlend::blend` with the default `Rounding`.
+///
+/// # Panics
+///
+/// If `value` is NaN and `P` has integer subpixels.
+#[must_use]
+pub fn from_f64_normalized<P>(value: f64, max: f64) -> P::Subpixel
+where
+    P: Pixel,
+{
+    denormalize::<P>(value, max)
+}
+
+/// Clamp `value` to 0.0..1.0, as is done to the result of every op before it is converted back to a subpixel.
+#[must_use]
+pub fn clamp01(value: f64) -> f64 {
+    value.clamp(0., 1.)
+}
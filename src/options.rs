@@ -0,0 +1,158 @@
+use crate::{
+    color::LumaCoefficients,
+    enums::{Arithmetic, DimensionPolicy, Rounding},
+    pixelops::{pixel_add, pixel_darker, pixel_diff, pixel_lighter, pixel_mult, pixel_normal, pixel_overlay, pixel_screen, pixel_sub},
+};
+
+/**
+Options controlling a blend performed with `blend_with_options`.
+
+Construct with `BlendOptions::new` or a preset such as `BlendOptions::multiply`, and override fields with struct update syntax.
+
+# Examples
+
+```
+use image_blend::BlendOptions;
+use image_blend::pixelops::pixel_add;
+
+let options = BlendOptions {
+    min_delta: 0.01,
+    ..BlendOptions::new(pixel_add)
+};
+```
+
+`PartialEq` compares `op` by function pointer, which is not guaranteed to be unique per function. Options built from the same function compare equal in practice, but do not rely on it across crates.
+*/
+#[allow(unpredictable_function_pointer_comparisons, clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlendOptions {
+    /// The blend function, where arg 0 is self and 1 is other.
+    ///
+    /// Stored as a function pointer so options can be copied and kept around, at the cost of an indirect call per subpixel. `blend` takes the function generically and can inline it.
+    pub op: fn(f64, f64) -> f64,
+    /// Blend the color channels. Defaults to true.
+    pub apply_to_color: bool,
+    /// Blend the alpha channel, if both images have one. Defaults to false.
+    pub apply_to_alpha: bool,
+    /// Only write a channel if the blended value differs from the original by at least this much (in the range 0.0..1.0). Defaults to 0.0 (always write).
+    ///
+    /// Useful to avoid quantization churn when repeatedly applying small blends.
+    pub min_delta: f64,
+    /// How blended values are rounded when written back to an integer image. Defaults to `Rounding::Round`.
+    pub rounding: Rounding,
+    /// Leave `self`'s alpha untouched where `other` is fully transparent, instead of blending the alpha channel with `op`. Defaults to false.
+    ///
+    /// Only matters with `apply_to_alpha`, as color is never blended from fully transparent pixels. Meant for min/max stacking with `pixel_darker` or `pixel_lighter`, where it makes transparent (masked out) pixels count as infinitely light or dark rather than pulling the alpha of the stack down to zero.
+    pub skip_transparent: bool,
+    /// Strength of the blend, multiplied with `other`'s alpha when weighting the color channels (in the range 0.0..1.0). Defaults to 1.0.
+    pub opacity: f64,
+    /// Strength of the blend of the color channels alone (in the range 0.0..1.0), multiplied with `opacity`. Defaults to 1.0.
+    ///
+    /// Together with `alpha_opacity`, lets color and coverage be blended at different strengths, e.g. to tint strongly while barely changing alpha.
+    pub color_opacity: f64,
+    /// Strength of the blend of the alpha channel (in the range 0.0..1.0), mixing the result of `op` with `self`'s alpha. Defaults to 1.0.
+    ///
+    /// Only matters with `apply_to_alpha`. At 0.0 the alpha channel is left exactly as it was.
+    pub alpha_opacity: f64,
+    /// Call `op` as `op(other, self)` instead of `op(self, other)`, still writing the result into `self`. Defaults to false.
+    ///
+    /// Gives `other`-as-base semantics for asymmetric operations such as `pixel_sub` or `pixel_div` without cloning either image to swap them. Weighting by `other`'s alpha is unchanged.
+    pub reverse_operands: bool,
+    /// Clamp the normalized inputs of `op` to 0.0..1.0 before calling it. Defaults to true.
+    ///
+    /// Only float images can hold values outside of this range. Out of range inputs can push formulas such as `pixel_soft_light` far outside of their intended range, and make operations that take a square root or fractional power return NaN. Set to false to pass HDR values to `op` unchanged.
+    pub clamp_inputs: bool,
+    /// Allow blending an rgb image into a luma image by first reducing `other` to its luminance with these weights. Defaults to `None`, which returns `UnsupportedBlend` for that combination as `blend` does.
+    ///
+    /// Luminance is computed from the stored (usually sRGB encoded) values, and `other`'s alpha channel is kept.
+    pub rgb_into_luma: Option<LumaCoefficients>,
+    /// Treat the 4th channel of a 4 channel image as alpha. Defaults to true.
+    ///
+    /// Set to false for data where all four channels are color, such as CMYK loaded into an `Rgba` buffer. All four channels are then blended as color with `apply_to_color`, without weighting by the 4th, and `apply_to_alpha` does nothing. Both images must have 4 channels, otherwise `UnsupportedBlend` is returned.
+    pub fourth_channel_is_alpha: bool,
+    /// What to do when `other` is a different size than `self`. Defaults to `DimensionPolicy::Strict`, which returns `DimensionMismatch` as `blend` does.
+    pub dimension_policy: DimensionPolicy,
+    /// Whether to blend in float or in integer-exact fixed point. Defaults to `Arithmetic::Float`.
+    ///
+    /// `Arithmetic::FixedPoint` requires `self` and `other` to be integer images of the same bit depth, and blends with its `IntOp` rather than `op`. The result of the `IntOp` is exact; where either pixel is translucent it is weighted by the alphas like `blend` and rounded with `rounding`. It can't honour `min_delta`, `skip_transparent`, the opacities, `rgb_into_luma` or `linear_alpha_weighting`, and returns `InvalidArgument` if any of them is changed from its default.
+    pub arithmetic: Arithmetic,
+    /// Weight the color channels by `other`'s alpha in linear light, while still calling `op` on the stored (sRGB encoded) values. Defaults to false.
+    ///
+    /// Mixing by alpha is a physical blend of light, so doing it on gamma encoded values makes partially covered pixels too dark, e.g. midgray at 50% alpha over black comes out at 64 instead of 93 (in 8 bit). This is how many compositors treat coverage. Unlike `blend_linear`, the result of `op` itself is unchanged for fully opaque pixels.
+    pub linear_alpha_weighting: bool,
+}
+impl BlendOptions {
+    /// Create options blending the color channels with `op`, all other options at their defaults.
+    #[must_use]
+    pub fn new(op: fn(f64, f64) -> f64) -> Self {
+        Self {
+            op,
+            apply_to_color: true,
+            apply_to_alpha: false,
+            min_delta: 0.,
+            rounding: Rounding::Round,
+            skip_transparent: false,
+            opacity: 1.,
+            color_opacity: 1.,
+            alpha_opacity: 1.,
+            reverse_operands: false,
+            clamp_inputs: true,
+            rgb_into_luma: None,
+            fourth_channel_is_alpha: true,
+            dimension_policy: DimensionPolicy::Strict,
+            arithmetic: Arithmetic::Float,
+            linear_alpha_weighting: false,
+        }
+    }
+    /// Options for a `pixel_normal` blend.
+    #[must_use]
+    pub fn normal() -> Self {
+        Self::new(pixel_normal)
+    }
+    /// Options for a `pixel_mult` blend.
+    #[must_use]
+    pub fn multiply() -> Self {
+        Self::new(pixel_mult)
+    }
+    /// Options for a `pixel_screen` blend.
+    #[must_use]
+    pub fn screen() -> Self {
+        Self::new(pixel_screen)
+    }
+    /// Options for a `pixel_overlay` blend.
+    #[must_use]
+    pub fn overlay() -> Self {
+        Self::new(pixel_overlay)
+    }
+    /// Options for a `pixel_add` blend.
+    #[must_use]
+    pub fn add() -> Self {
+        Self::new(pixel_add)
+    }
+    /// Options for a `pixel_sub` blend.
+    #[must_use]
+    pub fn subtract() -> Self {
+        Self::new(pixel_sub)
+    }
+    /// Options for a `pixel_darker` blend.
+    #[must_use]
+    pub fn darker() -> Self {
+        Self::new(pixel_darker)
+    }
+    /// Options for a `pixel_lighter` blend.
+    #[must_use]
+    pub fn lighter() -> Self {
+        Self::new(pixel_lighter)
+    }
+    /// Options for a `pixel_diff` blend.
+    #[must_use]
+    pub fn difference() -> Self {
+        Self::new(pixel_diff)
+    }
+}
+impl Default for BlendOptions {
+    /// A `pixel_normal` blend of the color channels at full opacity, the same as `BlendOptions::normal`.
+    fn default() -> Self {
+        Self::normal()
+    }
+}
@@ -11,6 +11,12 @@ Formulas taken from [Wikipedia](https://en.wikipedia.org/wiki/Blend_modes).
 
 Analagous blend modes of the same name in Photoshop.
 
+Modes meant for signed overlays, such as detail, emboss or high pass layers, treat 0.5 as neutral: a `b` of 0.5 leaves `a` unchanged, darker values of `b` push `a` down and lighter values push it up. `pixel_bipolar_add` is the plain form of this convention, and `pixel_grain_merge`, `pixel_linear_light` and `pixel_overlay` (for `a`) follow it too.
+
+Some modes are the same operation with the layers swapped. `pixel_overlay` branches on `a` and `pixel_hard_light` on `b`, so `pixel_overlay(a, b) == pixel_hard_light(b, a)` exactly for all inputs, including at the 0.5 boundary where both branches agree.
+
+The functions in this module only use `+`, `-`, `*`, `/` and `sqrt`, which IEEE 754 requires to be correctly rounded, so blending with them gives bit identical output on every platform and is safe to checksum. Operations that go through `powf` (`BufferBlend::blend_linear`, `BufferMapPixels::levels`, and custom ops that call `powf`, `exp` or similar) depend on the platform's math library, which may differ in the last bit of an f64. After quantizing to 8 or 16 bits this can move a result by at most one step, and only for values that land almost exactly halfway between two steps.
+
 # Examples
 
 ```
@@ -33,7 +39,11 @@ img1_buffer.save("tests_out/doctest_buffer_blend_result.png").unwrap();
 ```
 */
 
+use crate::{color::luminance, enums::DivideZero};
+
 /// Adds `a` to `b`.
+///
+/// This is the linear dodge blend mode.
 #[must_use]
 pub fn pixel_add(a: f64, b: f64) -> f64 {
     a + b
@@ -45,7 +55,7 @@ pub fn pixel_sub(a: f64, b: f64) -> f64 {
     a - b
 }
 
-/// Divides `a` by `b`. If `b` is 0, returns 1.
+/// Divides `a` by `b`. If `b` is 0, returns 1. Use `pixel_div_policy` for a different result.
 #[must_use]
 pub fn pixel_div(a: f64, b: f64) -> f64 {
     if b == 0. {
@@ -54,6 +64,29 @@ pub fn pixel_div(a: f64, b: f64) -> f64 {
     a / b
 }
 
+/**
+Returns a division function like `pixel_div` that returns the result chosen by `policy` when `b` is 0.
+
+# Examples
+
+```
+use image_blend::DivideZero;
+use image_blend::pixelops::pixel_div_policy;
+
+let div = pixel_div_policy(DivideZero::PassThrough);
+assert_eq!(div(0.25, 0.), 0.25);
+assert_eq!(div(0.25, 0.5), 0.5);
+```
+*/
+#[must_use]
+pub fn pixel_div_policy(policy: DivideZero) -> fn(f64, f64) -> f64 {
+    match policy {
+        DivideZero::White => pixel_div,
+        DivideZero::Black => |a, b| if b == 0. { 0. } else { a / b },
+        DivideZero::PassThrough => |a, b| if b == 0. { a } else { a / b },
+    }
+}
+
 /// Returns the darker value between `a` and `b`.
 #[must_use]
 pub fn pixel_darker(a: f64, b: f64) -> f64 {
@@ -85,6 +118,8 @@ pub fn pixel_screen(a: f64, b: f64) -> f64 {
 }
 
 /// Applies the overlay blend mode to `a` and `b`.
+///
+/// The transpose of `pixel_hard_light`: `pixel_overlay(a, b) == pixel_hard_light(b, a)`.
 #[must_use]
 pub fn pixel_overlay(a: f64, b: f64) -> f64 {
     if a < 0.5 {
@@ -95,6 +130,8 @@ pub fn pixel_overlay(a: f64, b: f64) -> f64 {
 }
 
 /// Applies the hard light blend mode to `a` and `b`.
+///
+/// The transpose of `pixel_overlay`: `pixel_hard_light(a, b) == pixel_overlay(b, a)`.
 #[must_use]
 pub fn pixel_hard_light(a: f64, b: f64) -> f64 {
     if b < 0.5 {
@@ -104,9 +141,17 @@ pub fn pixel_hard_light(a: f64, b: f64) -> f64 {
     }
 }
 
-/// Applies the soft light blend mode to `a` and `b`. Uses W3C formula.
+/// Applies the soft light blend mode to `a` and `b`. Uses W3C formula, see `pixel_soft_light_w3c`.
 #[must_use]
 pub fn pixel_soft_light(a: f64, b: f64) -> f64 {
+    pixel_soft_light_w3c(a, b)
+}
+
+/// Applies the soft light blend mode from the W3C compositing spec to `a` and `b`, as used by browsers for `mix-blend-mode: soft-light`.
+///
+/// Matches `pixel_soft_light_pegtop` for `b <= 0.5`. Above that it lightens with a square root curve (and a cubic below `a = 0.25`), so a white `b` gives `sqrt(a)` rather than `2a - a²`.
+#[must_use]
+pub fn pixel_soft_light_w3c(a: f64, b: f64) -> f64 {
     if b <= 0.5 {
         a - (1.0 - 2.0 * b) * a * (1.0 - a)
     } else {
@@ -119,8 +164,160 @@ pub fn pixel_soft_light(a: f64, b: f64) -> f64 {
     }
 }
 
+/// Applies the Pegtop soft light blend mode to `a` and `b` (`(1 - 2b)a² + 2ba`), close to the soft light of most image editors.
+///
+/// A single polynomial with no branch, so it is smooth everywhere. It matches `pixel_soft_light_w3c` for `b <= 0.5`, and diverges above that.
+#[must_use]
+pub fn pixel_soft_light_pegtop(a: f64, b: f64) -> f64 {
+    (1.0 - 2.0 * b) * a * a + 2.0 * b * a
+}
+
 /// Returns `b`. Basically paste/overwrite.
 #[must_use]
 pub fn pixel_normal(_a: f64, b: f64) -> f64 {
     b
 }
+
+/// Applies the GIMP grain extract blend mode to `a` and `b` (`a - b + 0.5`).
+#[must_use]
+pub fn pixel_grain_extract(a: f64, b: f64) -> f64 {
+    a - b + 0.5
+}
+
+/// Applies the GIMP grain merge blend mode to `a` and `b` (`a + b - 0.5`).
+///
+/// Grain merging the result of `pixel_grain_extract` with the same `b` restores `a` (before clamping).
+#[must_use]
+pub fn pixel_grain_merge(a: f64, b: f64) -> f64 {
+    a + b - 0.5
+}
+
+/// Adds the signed offset of `b` from neutral gray to `a` (`a + (b - 0.5)`), so a `b` of 0.5 leaves `a` unchanged.
+///
+/// For detail and emboss overlays stored around 0.5, where `b` below 0.5 darkens `a` and above it lightens it by up to half the range. Numerically the same as `pixel_grain_merge`, named for this convention.
+#[must_use]
+pub fn pixel_bipolar_add(a: f64, b: f64) -> f64 {
+    a + (b - 0.5)
+}
+
+/// Applies the phoenix blend mode to `a` and `b` (`min(a, b) - max(a, b) + 1`).
+#[must_use]
+pub fn pixel_phoenix(a: f64, b: f64) -> f64 {
+    a.min(b) - a.max(b) + 1.
+}
+
+/// Applies the linear burn blend mode to `a` and `b` (`a + b - 1`).
+#[must_use]
+pub fn pixel_linear_burn(a: f64, b: f64) -> f64 {
+    a + b - 1.
+}
+
+/// Applies the linear light blend mode to `a` and `b` (`a + 2b - 1`): linear burn where `b` is dark and linear dodge where it is light.
+///
+/// Ranges from -1 to 2 before clamping.
+#[must_use]
+pub fn pixel_linear_light(a: f64, b: f64) -> f64 {
+    a + 2. * b - 1.
+}
+
+/// Applies the vivid light blend mode to `a` and `b`: a color burn by `2b` where `b <= 0.5`, and a color dodge by `2b - 1` otherwise.
+///
+/// Unbounded before clamping. Where the burn or dodge would divide by zero, the result is 0 or 1 unless `a` is already at the other extreme.
+#[must_use]
+#[allow(clippy::float_cmp)]
+pub fn pixel_vivid_light(a: f64, b: f64) -> f64 {
+    if b <= 0.5 {
+        if b == 0. {
+            return if a == 1. { 1. } else { 0. };
+        }
+        1. - (1. - a) / (2. * b)
+    } else {
+        if b == 1. {
+            return if a == 0. { 0. } else { 1. };
+        }
+        a / (2. * (1. - b))
+    }
+}
+
+/// Applies the pin light blend mode to `a` and `b`: `min(a, 2b)` where `b <= 0.5`, and `max(a, 2b - 1)` otherwise.
+///
+/// Always within 0.0..1.0 for inputs in that range.
+#[must_use]
+pub fn pixel_pin_light(a: f64, b: f64) -> f64 {
+    if b <= 0.5 {
+        a.min(2. * b)
+    } else {
+        a.max(2. * b - 1.)
+    }
+}
+
+/// Applies the hard mix blend mode to `a` and `b`, thresholding each channel to 0 or 1 (`1` if `a + b >= 1`, otherwise `0`).
+#[must_use]
+pub fn pixel_hard_mix(a: f64, b: f64) -> f64 {
+    if a + b < 1. {
+        0.
+    } else {
+        1.
+    }
+}
+
+/// Averages `a` and `b`. Applied repeatedly, this gives an exponential moving average weighted towards recent values.
+#[must_use]
+pub fn pixel_average(a: f64, b: f64) -> f64 {
+    f64::midpoint(a, b)
+}
+
+/**
+Sample `op` over an evenly spaced `samples` x `samples` grid covering `0.0..=1.0` for both arguments, and return the smallest and largest output seen.
+
+Useful to check whether a custom operation stays in range, or how much of its output will be clamped when blending. Both ends of the range are always sampled, so `samples` is at least 2. NaN outputs are ignored.
+
+# Examples
+
+```
+use image_blend::pixelops::{op_range, pixel_add};
+
+assert_eq!(op_range(pixel_add, 11), (0.0, 2.0));
+```
+*/
+#[must_use]
+pub fn op_range(op: impl Fn(f64, f64) -> f64, samples: usize) -> (f64, f64) {
+    let samples = samples.max(2);
+    #[allow(clippy::cast_precision_loss)]
+    let point = |i: usize| i as f64 / (samples - 1) as f64;
+    (0..samples)
+        .flat_map(|i| (0..samples).map(move |j| (point(i), point(j))))
+        .map(|(a, b)| op(a, b))
+        .filter(|v| !v.is_nan())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(v), max.max(v)))
+}
+
+/**
+Bring an out of range rgb triplet back into 0.0..1.0 while keeping its luminance, as the W3C `ClipColor` step of the non-separable blend modes does.
+
+Each channel is moved towards the luminance of the triplet just far enough that the furthest channel lands on 0.0 or 1.0, so hue is preserved. Useful for custom whole-pixel operations, whose results can otherwise only be clamped channel by channel. Luminance uses Rec. 709 weights, and is itself clamped to 0.0..1.0 first if it is out of range.
+
+# Examples
+
+```
+use image_blend::pixelops::clip_color;
+
+let [r, g, b] = clip_color([1.2, 0.5, -0.1]);
+assert!([r, g, b].iter().all(|v| (0.0..=1.0).contains(v)));
+```
+*/
+#[must_use]
+pub fn clip_color(rgb: [f64; 3]) -> [f64; 3] {
+    let l = luminance(rgb).clamp(0., 1.);
+    let min = rgb.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = rgb.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mut out = rgb;
+    if min < 0. {
+        out = out.map(|v| l + (v - l) * l / (l - min));
+    }
+    if max > 1. {
+        let max = out.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        out = out.map(|v| l + (v - l) * (1. - l) / (max - l));
+    }
+    out.map(|v| v.clamp(0., 1.))
+}
@@ -0,0 +1,169 @@
+use image::{DynamicImage, Pixel};
+
+use crate::{
+    dynamic_blend::{into_color_type, DynamicChops},
+    enums::ColorString,
+    error::Error,
+};
+
+/**
+An image whose color is stored independently of its alpha, which is what the blend operations in this crate expect.
+
+`Straight` and `Premultiplied` record an image's alpha state in its type, so compositing functions can only be handed data with the alpha they expect. Converting between them is always explicit, with `premultiply` and `unpremultiply`, which rules out fringing from mixing the two up. Images without an alpha channel are the same in both states.
+
+# Examples
+
+```
+use image::open;
+use image_blend::Straight;
+use image_blend::pixelops::pixel_mult;
+
+let mut img1 = Straight::new(open("test_data/1.png").unwrap());
+let img2 = Straight::new(open("test_data/2.png").unwrap());
+
+img1.blend(&img2, pixel_mult, true, false).unwrap();
+img1.image().save("tests_out/doctest_straight_blend_result.png").unwrap();
+```
+
+A premultiplied image has to be converted before it can be blended with straight alpha math:
+
+```compile_fail
+use image::open;
+use image_blend::{Premultiplied, Straight};
+use image_blend::pixelops::pixel_mult;
+
+let mut img1 = Straight::new(open("test_data/1.png").unwrap());
+let img2 = Premultiplied::new(open("test_data/2.png").unwrap());
+
+img1.blend(&img2, pixel_mult, true, false).unwrap();
+```
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Straight(DynamicImage);
+
+/**
+An image whose color channels have been multiplied by its alpha, as most GPU blending pipelines expect.
+
+See `Straight` for why the alpha state is part of the type. Only `composite_over` works on premultiplied images directly; convert to `Straight` with `unpremultiply` for any other blend.
+
+# Examples
+
+```
+use image::open;
+use image_blend::Straight;
+
+let mut img1 = Straight::new(open("test_data/1.png").unwrap()).premultiply();
+let img2 = Straight::new(open("test_data/2.png").unwrap()).premultiply();
+
+img1.composite_over(&img2).unwrap();
+img1.unpremultiply().image().save("tests_out/doctest_premultiplied_composite_over_result.png").unwrap();
+```
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct Premultiplied(DynamicImage);
+
+impl Straight {
+    /// Declare that `image` has straight alpha, e.g. because it was just loaded from a PNG.
+    #[must_use]
+    pub fn new(image: DynamicImage) -> Self {
+        Self(image)
+    }
+    /// The wrapped image.
+    #[must_use]
+    pub fn image(&self) -> &DynamicImage {
+        &self.0
+    }
+    /// Unwrap the image, giving up the record of its alpha state.
+    #[must_use]
+    pub fn into_inner(self) -> DynamicImage {
+        self.0
+    }
+    /// Multiply the color channels by alpha, keeping the color type.
+    #[must_use]
+    pub fn premultiply(self) -> Premultiplied {
+        let mut image = self.0;
+        // Only fails if there is no alpha channel, in which case the image is already premultiplied
+        image.premultiply().ok();
+        Premultiplied(image)
+    }
+    /**
+    Blend `other` into `self` using the function `op`, where arg 0 is self and 1 is other, exactly as `DynamicChops::blend` does.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+    */
+    pub fn blend(
+        &mut self,
+        other: &Straight,
+        op: impl Fn(f64, f64) -> f64,
+        apply_to_color: bool,
+        apply_to_alpha: bool,
+    ) -> Result<(), Error> {
+        self.0.blend(&other.0, op, apply_to_color, apply_to_alpha)
+    }
+}
+
+impl Premultiplied {
+    /// Declare that `image` already has its color multiplied by alpha, e.g. because it was read back from a GPU.
+    #[must_use]
+    pub fn new(image: DynamicImage) -> Self {
+        Self(image)
+    }
+    /// The wrapped image.
+    #[must_use]
+    pub fn image(&self) -> &DynamicImage {
+        &self.0
+    }
+    /// Unwrap the image, giving up the record of its alpha state.
+    #[must_use]
+    pub fn into_inner(self) -> DynamicImage {
+        self.0
+    }
+    /// Divide the color channels by alpha, keeping the color type.
+    ///
+    /// The division is done in 32 bit float and rounded once, so no more precision is lost than storing the result requires. Fully transparent pixels have no recoverable color and become black.
+    #[must_use]
+    // The color type comes from a `DynamicImage`, and every one of those is supported, so this can't panic
+    #[allow(clippy::missing_panics_doc)]
+    pub fn unpremultiply(self) -> Straight {
+        let color_type = self.0.color();
+        if !color_type.has_alpha() {
+            return Straight(self.0);
+        }
+        let mut straight = DynamicImage::ImageRgba32F(self.0.into_rgba32f());
+        // Rgba32F always has an alpha channel
+        straight.unpremultiply().ok();
+        Straight(into_color_type(straight, color_type).unwrap())
+    }
+    /**
+    Composite `other` over `self` with the Porter-Duff source-over operator in premultiplied space: each channel, alpha included, becomes `other + self * (1 - other_alpha)`.
+
+    This is the one composite that is exact on premultiplied data. Other blend modes need straight alpha, so `unpremultiply` first. The result keeps `self`'s color type.
+
+    # Errors
+
+    `DimensionMismatch`: `self` and `other` have different dimensions
+
+    `UnsupportedBlend`: `self` is a luma image and `other` is an rgb image
+    */
+    pub fn composite_over(&mut self, other: &Premultiplied) -> Result<(), Error> {
+        if (self.0.width(), self.0.height()) != (other.0.width(), other.0.height()) {
+            return Err(Error::DimensionMismatch);
+        }
+        let color_type = self.0.color();
+        if !color_type.has_color() && other.0.color().has_color() {
+            return Err(Error::UnsupportedBlend(color_type.color_str(), other.0.color().color_str()));
+        }
+        let mut bottom = self.0.to_rgba32f();
+        let top = other.0.to_rgba32f();
+        bottom.pixels_mut().zip(top.pixels()).for_each(|(px_a, px_b)| {
+            let coverage = 1. - px_b.0[3];
+            px_a.apply2(px_b, |a, b| b + a * coverage);
+        });
+        self.0 = into_color_type(DynamicImage::ImageRgba32F(bottom), color_type)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use image::{DynamicImage, GenericImageView, Rgba32FImage};
+#[cfg(feature = "rayon")]
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::{dynamic_blend::into_color_type, enums::ColorString, error::Error};
+
+/**
+Sum `values` using a pairwise (tree) reduction with a fixed association.
+
+The split points depend only on the number of values, never on how work is scheduled, so the result is bit-identical regardless of thread count.
+*/
+pub(crate) fn pairwise_sum(values: &[f64]) -> f64 {
+    match values.len() {
+        0 => 0.,
+        1 => values[0],
+        n => {
+            let (left, right) = values.split_at(n / 2);
+            pairwise_sum(left) + pairwise_sum(right)
+        }
+    }
+}
+
+/**
+Average every image in `images` together, returning an image of the same type as the first image.
+
+Each subpixel is normalized to 0.0..1.0, summed across all images using a fixed pairwise reduction and divided by the number of images.
+
+With the `rayon` feature enabled the work is split across threads, but each output subpixel is still reduced by a single thread in the same order, so the output is deterministic across runs and thread counts.
+
+# Errors
+
+`NoImages`: `images` is empty
+
+`DimensionMismatch`: the images do not all have the same dimensions
+
+`UnsupportedType`: the first image is of an unsupported color type
+
+# Examples
+
+```
+use image::open;
+use image_blend::blend_all_average;
+
+let img1_dynamic = open("test_data/1.png").unwrap();
+let img2_dynamic = open("test_data/2.png").unwrap();
+
+let averaged = blend_all_average(&[img1_dynamic, img2_dynamic]).unwrap();
+averaged.save("tests_out/doctest_blend_all_average_result.png").unwrap();
+```
+*/
+pub fn blend_all_average(images: &[DynamicImage]) -> Result<DynamicImage, Error> {
+    let first = images.first().ok_or(Error::NoImages)?;
+    if images.iter().any(|img| img.dimensions() != first.dimensions()) {
+        return Err(Error::DimensionMismatch);
+    }
+    let buffers: Vec<Rgba32FImage> = images.iter().map(DynamicImage::to_rgba32f).collect();
+    #[allow(clippy::cast_precision_loss)]
+    let count = buffers.len() as f64;
+    let (width, height) = first.dimensions();
+    let mut out = Rgba32FImage::new(width, height);
+
+    // `values` is scratch space reused across subpixels, one per thread
+    let average = |values: &mut Vec<f64>, (i, subpixel): (usize, &mut f32)| {
+        values.clear();
+        values.extend(buffers.iter().map(|buf| <f64 as From<f32>>::from(buf.as_raw()[i])));
+        #[allow(clippy::cast_possible_truncation)]
+        let mean = (pairwise_sum(values) / count) as f32;
+        *subpixel = mean;
+    };
+    #[cfg(feature = "rayon")]
+    out.par_iter_mut().enumerate().for_each_init(|| Vec::with_capacity(buffers.len()), average);
+    #[cfg(not(feature = "rayon"))]
+    {
+        let mut values = Vec::with_capacity(buffers.len());
+        out.iter_mut().enumerate().for_each(|item| average(&mut values, item));
+    }
+
+    into_color_type(DynamicImage::ImageRgba32F(out), first.color())
+}
+
+/// Blend each of `others` into a copy of `base` with `op`, and take the average of the results weighted by each image's weight and alpha. See `DynamicChops::blend_weighted`.
+pub(crate) fn blend_weighted(base: &DynamicImage, others: &[(DynamicImage, f64)], op: &(dyn Fn(f64, f64) -> f64 + Sync)) -> Result<DynamicImage, Error> {
+    if others.is_empty() {
+        return Err(Error::NoImages);
+    }
+    if others.iter().any(|(img, _)| img.dimensions() != base.dimensions()) {
+        return Err(Error::DimensionMismatch);
+    }
+    if others.iter().any(|(_, weight)| !weight.is_finite() || *weight < 0.) {
+        return Err(Error::InvalidArgument("weights must be finite and not negative"));
+    }
+    if others.iter().all(|(_, weight)| *weight == 0.) {
+        return Err(Error::InvalidArgument("weights must not all be zero"));
+    }
+    if let Some((other, _)) = others.iter().find(|(img, _)| img.color().has_color() && !base.color().has_color()) {
+        return Err(Error::UnsupportedBlend(base.color().color_str(), other.color().color_str()));
+    }
+    let buffers: Vec<(Rgba32FImage, f64)> = others.iter().map(|(img, weight)| (img.to_rgba32f(), *weight)).collect();
+    let mut out = base.to_rgba32f();
+
+    // `terms` and `weights` are scratch space reused across subpixels, one pair per thread
+    let blend = |(terms, weights): &mut (Vec<f64>, Vec<f64>), (i, subpixel): (usize, &mut f32)| {
+        // Alpha is left as it is in `base`
+        if i % 4 == 3 {
+            return;
+        }
+        let a = <f64 as From<f32>>::from(*subpixel);
+        terms.clear();
+        weights.clear();
+        for (buf, weight) in &buffers {
+            let raw = buf.as_raw();
+            let weight = weight * <f64 as From<f32>>::from(raw[i - i % 4 + 3]);
+            terms.push(op(a, <f64 as From<f32>>::from(raw[i])).clamp(0., 1.) * weight);
+            weights.push(weight);
+        }
+        let total = pairwise_sum(weights);
+        if total > 0. {
+            #[allow(clippy::cast_possible_truncation)]
+            let mean = (pairwise_sum(terms) / total) as f32;
+            *subpixel = mean;
+        }
+    };
+    let scratch = || (Vec::with_capacity(buffers.len()), Vec::with_capacity(buffers.len()));
+    #[cfg(feature = "rayon")]
+    out.par_iter_mut().enumerate().for_each_init(scratch, blend);
+    #[cfg(not(feature = "rayon"))]
+    {
+        let mut scratch = scratch();
+        out.iter_mut().enumerate().for_each(|item| blend(&mut scratch, item));
+    }
+
+    into_color_type(DynamicImage::ImageRgba32F(out), base.color())
+}
@@ -0,0 +1,68 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use image::DynamicImage;
+
+use crate::{dynamic_blend::DynamicChops, error::Error};
+
+type BlendFn = fn(f64, f64) -> f64;
+
+fn registry() -> &'static Mutex<HashMap<String, BlendFn>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, BlendFn>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/**
+Register `op` under `name` for the whole process, so it can be looked up with `named_blend_mode` or used by `blend_named`. Registering a name again replaces the previous function.
+
+Meant for host applications where plugins or scripts add their own blend modes at runtime, and layers refer to them by name.
+
+# Examples
+
+```
+use image_blend::{named_blend_mode, register_blend_mode};
+
+register_blend_mode("invert_mult", |a, b| a * (1. - b));
+assert_eq!(named_blend_mode("invert_mult").unwrap()(0.5, 0.25), 0.375);
+```
+*/
+pub fn register_blend_mode(name: &str, op: BlendFn) {
+    // A panic while holding the lock cannot leave the map half updated, so a poisoned lock is still usable
+    registry().lock().unwrap_or_else(std::sync::PoisonError::into_inner).insert(name.to_owned(), op);
+}
+
+/// The function registered under `name` with `register_blend_mode`, if any.
+#[must_use]
+pub fn named_blend_mode(name: &str) -> Option<BlendFn> {
+    registry().lock().unwrap_or_else(std::sync::PoisonError::into_inner).get(name).copied()
+}
+
+/**
+Blend `other` into `dst` using the blend mode registered as `name`, where arg 0 is dst and 1 is other. Otherwise the same as `DynamicChops::blend`.
+
+# Errors
+
+`UnknownBlendMode`: no blend mode is registered as `name`
+
+Any error returned by `DynamicChops::blend`, e.g. `DimensionMismatch` if the images are different sizes
+
+# Examples
+
+```
+use image::open;
+use image_blend::{blend_named, register_blend_mode};
+
+let mut img1_dynamic = open("test_data/1.png").unwrap();
+let img2_dynamic = open("test_data/2.png").unwrap();
+
+register_blend_mode("invert_mult", |a, b| a * (1. - b));
+blend_named(&mut img1_dynamic, &img2_dynamic, "invert_mult", true, false).unwrap();
+img1_dynamic.save("tests_out/doctest_blend_named_result.png").unwrap();
+```
+*/
+pub fn blend_named(dst: &mut DynamicImage, other: &DynamicImage, name: &str, apply_to_color: bool, apply_to_alpha: bool) -> Result<(), Error> {
+    let op = named_blend_mode(name).ok_or_else(|| Error::UnknownBlendMode(name.to_owned()))?;
+    dst.blend(other, op, apply_to_color, apply_to_alpha)
+}
@@ -0,0 +1,226 @@
+use image::{ImageBuffer, Luma, LumaA, Pixel, Primitive, Rgb, Rgba};
+
+use crate::{blend_ops::blend_impl, error::Error, options::BlendOptions, pixelops::pixel_normal};
+
+/// Describes how an image is laid out in a plain slice of subpixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SliceLayout {
+    /// Width of the image in pixels.
+    pub width: u32,
+    /// Height of the image in pixels.
+    pub height: u32,
+    /// Number of interleaved channels per pixel. 1 is luma, 2 luma alpha, 3 rgb and 4 rgba.
+    pub channels: u8,
+    /// Number of subpixels from the start of one row to the start of the next. At least `width * channels`.
+    pub row_stride: usize,
+}
+impl SliceLayout {
+    /// A tightly packed layout, where each row starts directly after the previous one.
+    #[must_use]
+    pub fn packed(width: u32, height: u32, channels: u8) -> Self {
+        Self {
+            width,
+            height,
+            channels,
+            row_stride: width as usize * channels as usize,
+        }
+    }
+    fn row_len(&self) -> usize {
+        self.width as usize * self.channels as usize
+    }
+    /// Check the layout against a slice of `len` subpixels. Once this passes, `row_len` and every row's range can't overflow.
+    fn check(&self, len: usize) -> Result<(), Error> {
+        if !(1..=4).contains(&self.channels) {
+            return Err(Error::UnsupportedType);
+        }
+        let too_short = || Error::InvalidArgument("slice is too short for its layout");
+        let row_len = (self.width as usize).checked_mul(self.channels as usize).ok_or_else(too_short)?;
+        if self.row_stride < row_len {
+            return Err(Error::InvalidArgument("row_stride must be at least width * channels"));
+        }
+        if self.height > 0 {
+            // Layout fields come from the caller, e.g. over FFI, so a huge `row_stride` must not overflow
+            let required = self.row_stride.checked_mul(self.height as usize - 1).and_then(|rows| rows.checked_add(row_len));
+            if required.is_none_or(|required| len < required) {
+                return Err(too_short());
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+Blend `src` into `dst` where both are raw slices of interleaved subpixels, where arg 0 of `op` is dst and 1 is src.
+
+Behaves exactly like `BufferBlend::blend`, for callers such as FFI bindings that hold contiguous buffers rather than `ImageBuffer`s. The layout of each slice is given explicitly, so rows may be padded. Padding between rows is never read or written.
+
+# Errors
+
+`DimensionMismatch`: `dst_layout` and `src_layout` have different dimensions
+
+`UnsupportedType`: a layout does not have between 1 and 4 channels
+
+`InvalidArgument`: a layout's `row_stride` is less than `width * channels`, or its slice is too short for it
+
+`UnsupportedBlend`: `dst` is a luma image and `src` is an rgb image
+
+# Examples
+
+```
+use image_blend::{blend_slices, SliceLayout};
+use image_blend::pixelops::pixel_mult;
+
+// A 2x2 rgba image with padded rows, and a packed 2x2 rgb image
+let mut dst: Vec<u8> = vec![255; 2 * 10];
+let src: Vec<u16> = vec![32768; 2 * 2 * 3];
+
+let dst_layout = SliceLayout { width: 2, height: 2, channels: 4, row_stride: 10 };
+let src_layout = SliceLayout::packed(2, 2, 3);
+blend_slices(&mut dst, dst_layout, &src, src_layout, pixel_mult, true, false).unwrap();
+assert_eq!(&dst[..8], &[128, 128, 128, 255, 128, 128, 128, 255]);
+```
+*/
+pub fn blend_slices<T, U>(
+    dst: &mut [T],
+    dst_layout: SliceLayout,
+    src: &[U],
+    src_layout: SliceLayout,
+    op: impl Fn(f64, f64) -> f64,
+    apply_to_color: bool,
+    apply_to_alpha: bool,
+) -> Result<(), Error>
+where
+    T: Primitive,
+    U: Primitive,
+    Luma<T>: Pixel<Subpixel = T>,
+    LumaA<T>: Pixel<Subpixel = T>,
+    Rgb<T>: Pixel<Subpixel = T>,
+    Rgba<T>: Pixel<Subpixel = T>,
+    Luma<U>: Pixel<Subpixel = U>,
+    LumaA<U>: Pixel<Subpixel = U>,
+    Rgb<U>: Pixel<Subpixel = U>,
+    Rgba<U>: Pixel<Subpixel = U>,
+{
+    if (dst_layout.width, dst_layout.height) != (src_layout.width, src_layout.height) {
+        return Err(Error::DimensionMismatch);
+    }
+    dst_layout.check(dst.len())?;
+    src_layout.check(src.len())?;
+    let options = BlendOptions {
+        apply_to_color,
+        apply_to_alpha,
+        ..BlendOptions::new(pixel_normal)
+    };
+    // Called through a pointer, so the 16 layout pairs share one copy of the loop per type
+    let op: &dyn Fn(f64, f64) -> f64 = &op;
+    match dst_layout.channels {
+        1 => blend_slices_step_a::<Luma<T>, U>(dst, dst_layout, src, src_layout, op, &options),
+        2 => blend_slices_step_a::<LumaA<T>, U>(dst, dst_layout, src, src_layout, op, &options),
+        3 => blend_slices_step_a::<Rgb<T>, U>(dst, dst_layout, src, src_layout, op, &options),
+        4 => blend_slices_step_a::<Rgba<T>, U>(dst, dst_layout, src, src_layout, op, &options),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+fn blend_slices_step_a<Pmut, U>(dst: &mut [Pmut::Subpixel], dst_layout: SliceLayout, src: &[U], src_layout: SliceLayout, op: &dyn Fn(f64, f64) -> f64, options: &BlendOptions) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    U: Primitive,
+    Luma<U>: Pixel<Subpixel = U>,
+    LumaA<U>: Pixel<Subpixel = U>,
+    Rgb<U>: Pixel<Subpixel = U>,
+    Rgba<U>: Pixel<Subpixel = U>,
+{
+    match src_layout.channels {
+        1 => blend_rows::<Pmut, Luma<U>>(dst, dst_layout, src, src_layout, op, options),
+        2 => blend_rows::<Pmut, LumaA<U>>(dst, dst_layout, src, src_layout, op, options),
+        3 => blend_rows::<Pmut, Rgb<U>>(dst, dst_layout, src, src_layout, op, options),
+        4 => blend_rows::<Pmut, Rgba<U>>(dst, dst_layout, src, src_layout, op, options),
+        _ => Err(Error::UnsupportedType),
+    }
+}
+/// Blend row by row, viewing each row as a one pixel high `ImageBuffer` so strided slices reuse the normal blend loop.
+fn blend_rows<Pmut, P>(dst: &mut [Pmut::Subpixel], dst_layout: SliceLayout, src: &[P::Subpixel], src_layout: SliceLayout, op: &dyn Fn(f64, f64) -> f64, options: &BlendOptions) -> Result<(), Error>
+where
+    Pmut: Pixel,
+    P: Pixel,
+{
+    for y in 0..dst_layout.height as usize {
+        let dst_start = y * dst_layout.row_stride;
+        let src_start = y * src_layout.row_stride;
+        let dst_row = &mut dst[dst_start..dst_start + dst_layout.row_len()];
+        let src_row = &src[src_start..src_start + src_layout.row_len()];
+        // Lengths were checked against the layouts, so these cannot fail
+        let mut dst_buffer: ImageBuffer<Pmut, &mut [Pmut::Subpixel]> = ImageBuffer::from_raw(dst_layout.width, 1, dst_row).unwrap();
+        let src_buffer: ImageBuffer<P, &[P::Subpixel]> = ImageBuffer::from_raw(src_layout.width, 1, src_row).unwrap();
+        blend_impl(&mut dst_buffer, &src_buffer, |_, a, b| op(a, b), options)?;
+    }
+    Ok(())
+}
+
+/**
+Blend the packed pixels in `src` into those in `dst`, where both are contiguous runs of subpixels with `channels` interleaved channels, and arg 0 of `op` is dst and 1 is src.
+
+This is `blend_slices` for a single run of pixels with no layout to describe, meant for callers that split buffers into aligned chunks themselves (e.g. to process them with SIMD or on several threads). A chunk may start at any pixel boundary of an image, and blending every chunk of an image gives the same result as blending the whole image at once.
+
+Both chunks must hold the same number of whole pixels: their lengths must be equal multiples of `channels`.
+
+# Errors
+
+`UnsupportedType`: `channels` is not between 1 and 4
+
+`InvalidArgument`: a chunk's length is not a multiple of `channels`
+
+`DimensionMismatch`: the chunks hold different numbers of pixels
+
+`UnsupportedBlend`: `channels` is 1 or 2 (luma) and `src` holds rgb pixels
+
+# Examples
+
+```
+use image_blend::blend_chunk;
+use image_blend::pixelops::pixel_mult;
+
+let mut image: Vec<u8> = vec![255; 64 * 4];
+let other: Vec<u8> = [128, 128, 128, 255].repeat(64);
+
+// Blend the pixels in aligned chunks of 16
+for (dst, src) in image.chunks_mut(16 * 4).zip(other.chunks(16 * 4)) {
+    blend_chunk(dst, src, 4, pixel_mult, true, false).unwrap();
+}
+assert_eq!(&image[..4], &[128, 128, 128, 255]);
+```
+*/
+pub fn blend_chunk<T, U>(
+    dst_chunk: &mut [T],
+    src_chunk: &[U],
+    channels: u8,
+    op: impl Fn(f64, f64) -> f64,
+    apply_to_color: bool,
+    apply_to_alpha: bool,
+) -> Result<(), Error>
+where
+    T: Primitive,
+    U: Primitive,
+    Luma<T>: Pixel<Subpixel = T>,
+    LumaA<T>: Pixel<Subpixel = T>,
+    Rgb<T>: Pixel<Subpixel = T>,
+    Rgba<T>: Pixel<Subpixel = T>,
+    Luma<U>: Pixel<Subpixel = U>,
+    LumaA<U>: Pixel<Subpixel = U>,
+    Rgb<U>: Pixel<Subpixel = U>,
+    Rgba<U>: Pixel<Subpixel = U>,
+{
+    if !(1..=4).contains(&channels) {
+        return Err(Error::UnsupportedType);
+    }
+    let channels_len = usize::from(channels);
+    if !dst_chunk.len().is_multiple_of(channels_len) || !src_chunk.len().is_multiple_of(channels_len) {
+        return Err(Error::InvalidArgument("chunk length must be a multiple of channels"));
+    }
+    if dst_chunk.len() != src_chunk.len() {
+        return Err(Error::DimensionMismatch);
+    }
+    let width = u32::try_from(dst_chunk.len() / channels_len).map_err(|_| Error::InvalidArgument("chunk holds too many pixels"))?;
+    let layout = SliceLayout::packed(width, 1, channels);
+    blend_slices(dst_chunk, layout, src_chunk, layout, op, apply_to_color, apply_to_alpha)
+}
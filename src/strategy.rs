@@ -0,0 +1,42 @@
+use crate::enums::BlendMode;
+
+/**
+An object safe blend operation, for selecting blend modes at runtime through trait objects (e.g. `Box<dyn BlendStrategy>`).
+
+Implemented for every `BlendMode`, and for any function or closure taking two f64 values and returning a f64 value.
+
+# Examples
+
+```
+use image::open;
+use image_blend::{BlendMode, BlendStrategy, DynamicChops};
+
+let strategies: Vec<Box<dyn BlendStrategy>> = vec![
+    Box::new(BlendMode::Multiply),
+    Box::new(|a: f64, b: f64| a.max(b) * 0.5),
+];
+
+let img2_dynamic = open("test_data/2.png").unwrap();
+for strategy in &strategies {
+    let mut img1_dynamic = open("test_data/1.png").unwrap();
+    img1_dynamic.blend_dyn(&img2_dynamic, strategy.as_ref(), true, false).unwrap();
+}
+```
+*/
+pub trait BlendStrategy {
+    /// Blend `a` (self) with `b` (other). See `pixelops` for the conventions.
+    fn apply(&self, a: f64, b: f64) -> f64;
+}
+impl BlendStrategy for BlendMode {
+    fn apply(&self, a: f64, b: f64) -> f64 {
+        self.op()(a, b)
+    }
+}
+impl<F> BlendStrategy for F
+where
+    F: Fn(f64, f64) -> f64,
+{
+    fn apply(&self, a: f64, b: f64) -> f64 {
+        self(a, b)
+    }
+}
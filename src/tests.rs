@@ -1,15 +1,16 @@
 // Tests
 #[cfg(test)]
+#[allow(clippy::float_cmp)]
 mod test {
     use std::iter;
 
     use crate::{
         enums::{ColorString, ColorStructure}, pixelops::{
-            pixel_add, pixel_darker, pixel_diff, pixel_div, pixel_hard_light, pixel_lighter, pixel_mult, pixel_normal, pixel_overlay, pixel_screen, pixel_soft_light, pixel_sub
-        }, DynamicChops
+            clip_color, op_range, pixel_add, pixel_average, pixel_bipolar_add, pixel_darker, pixel_diff, pixel_div, pixel_div_policy, pixel_grain_extract, pixel_grain_merge, pixel_hard_light, pixel_hard_mix, pixel_lighter, pixel_linear_burn, pixel_linear_light, pixel_mult, pixel_normal, pixel_overlay, pixel_phoenix, pixel_pin_light, pixel_screen, pixel_soft_light, pixel_soft_light_pegtop, pixel_soft_light_w3c, pixel_sub, pixel_vivid_light
+        }, blend_all_average, blend_chunk, can_blend, blend_files_premul_aware, blend_generic, blend_slices, checkerboard, color::srgb_to_linear, gradient, join_alpha, match_types, mse, psnr, solid_color, supported_combinations, verify_blend, AlphaCoverage, AlphaMode, Anchor, Arithmetic, BlendAccumulator, BlendMode, BlendOptions, BlendStrategy, BufferAdjustAlpha, BufferBlend, BufferSetAlpha, Channel, ColorModel, ComponentBlend, DimensionPolicy, Direction, DivideZero, DynamicChops, Error, GamutClip, IntOp, LumaCoefficients, Lut3D, MismatchPolicy, NormalBlend, Premultiplied, Rect, Rounding, SliceLayout, Straight, TonalRange
     };
     const EXPORT_ALL: bool = false;
-    use image::{open, DynamicImage};
+    use image::{imageops::FilterType, open, DynamicImage, GrayImage, ImageBuffer, Luma, Rgba, Rgba32FImage, RgbaImage};
     use rayon::prelude::{ParallelBridge, ParallelIterator};
     fn as_all_types(img: &DynamicImage) -> impl Iterator<Item = DynamicImage> {
         iter::once(DynamicImage::ImageLuma8(img.clone().into_luma8()))
@@ -53,7 +54,15 @@ mod test {
             ("overlay", pixel_overlay),
             ("hard_light", pixel_hard_light),
             ("soft_light", pixel_soft_light),
+            ("soft_light_pegtop", pixel_soft_light_pegtop),
             ("overwrite", pixel_normal),
+            ("grain_extract", pixel_grain_extract),
+            ("grain_merge", pixel_grain_merge),
+            ("bipolar_add", pixel_bipolar_add),
+            ("phoenix", pixel_phoenix),
+            ("linear_burn", pixel_linear_burn),
+            ("hard_mix", pixel_hard_mix),
+            ("average", pixel_average),
         ]
     }
     #[test]
@@ -84,7 +93,7 @@ mod test {
                         // Should only error if a is L or La and b is Rgb or Rgba
                         assert!(!structure_a.rgb() && structure_b.rgb(), "{}", e);
                     }
-                };
+                }
             });
         });
     }
@@ -222,4 +231,2419 @@ mod test {
             )).unwrap();
         });
     }
+    #[test]
+    fn test_blend_all_average() {
+        let images = [0, 255, 100].map(|v| DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, image::Rgba([v, v, 255 - v, 255]))));
+        let averaged = blend_all_average(&images).unwrap();
+        assert_eq!(averaged.as_rgba8().unwrap().get_pixel(1, 1).0, [118, 118, 137, 255]);
+        assert!(matches!(blend_all_average(&[]), Err(Error::NoImages)));
+    }
+    // Only the rayon path splits work across threads, so only it can vary between runs. Plain `cargo test` skips this, so CI must run
+    // `cargo test --features rayon` (or `--all-features`) for the guarantee to be checked.
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_blend_all_average_deterministic() {
+        let images: Vec<DynamicImage> = (0..7u32)
+            .map(|i| {
+                DynamicImage::ImageRgba8(RgbaImage::from_fn(64, 64, |x, y| {
+                    let v = u8::try_from((x * 7 + y * 3 + i * 37) % 256).unwrap();
+                    image::Rgba([v, v.wrapping_mul(3), 255 - v, v | 1])
+                }))
+            })
+            .collect();
+        let reference = blend_all_average(&images).unwrap();
+        for run in 0..10 {
+            let res = blend_all_average(&images).unwrap();
+            assert_eq!(reference.as_bytes(), res.as_bytes(), "run {run}");
+        }
+        for threads in [1, 2, 3, 8] {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            let res = pool.install(|| blend_all_average(&images)).unwrap();
+            assert_eq!(reference.as_bytes(), res.as_bytes(), "{threads} threads");
+        }
+    }
+    fn hue(rgb: [f64; 3]) -> f64 {
+        let max = rgb[0].max(rgb[1]).max(rgb[2]);
+        let min = rgb[0].min(rgb[1]).min(rgb[2]);
+        let chroma = max - min;
+        let hue = if max == rgb[0] {
+            (rgb[1] - rgb[2]) / chroma
+        } else if max == rgb[1] {
+            (rgb[2] - rgb[0]) / chroma + 2.
+        } else {
+            (rgb[0] - rgb[1]) / chroma + 4.
+        };
+        (hue * 60.).rem_euclid(360.)
+    }
+    #[test]
+    fn test_blend_linear_gamut_clip() {
+        let blue = DynamicImage::ImageRgb32F(image::Rgb32FImage::from_pixel(2, 2, image::Rgb([0.2, 0.3, 0.9])));
+        let unclipped = [0.2, 0.3, 0.9].map(|v| srgb_to_linear(v) * 2.);
+        let hue_error = |clip: GamutClip| {
+            let mut img = blue.clone();
+            img.blend_linear(&blue, pixel_add, clip, true, false).unwrap();
+            let px = img.as_rgb32f().unwrap().get_pixel(0, 0).0.map(|v| srgb_to_linear(f64::from(v)));
+            assert!(px.iter().all(|v| (0. ..=1.).contains(v)));
+            (hue(px) - hue(unclipped)).abs()
+        };
+        let clamp_error = hue_error(GamutClip::Clamp);
+        let desat_error = hue_error(GamutClip::DesatToGray);
+        assert!(desat_error < clamp_error, "{desat_error} >= {clamp_error}");
+        assert!(desat_error < 0.01, "{desat_error}");
+
+        // In range results blend exactly like linear_alpha_weighting
+        let img1 = open("test_data/1.png").unwrap();
+        let img2 = open("test_data/2.png").unwrap();
+        let mut linear = img1.clone();
+        linear.blend_linear(&img2, pixel_normal, GamutClip::Clamp, true, true).unwrap();
+        let mut weighted = img1.clone();
+        weighted.blend_with_options(&img2, &BlendOptions { apply_to_alpha: true, linear_alpha_weighting: true, ..BlendOptions::new(pixel_normal) }).unwrap();
+        assert!(linear.as_bytes().iter().zip(weighted.as_bytes()).all(|(a, b)| a.abs_diff(*b) <= 1));
+    }
+    #[test]
+    fn test_try_blend_propagates_error() {
+        let a = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(4, 4, image::Rgb([10, 20, 30])));
+        let mut b = image::RgbImage::from_pixel(4, 4, image::Rgb([100, 100, 100]));
+        b.put_pixel(2, 1, image::Rgb([100, 240, 100]));
+        let b = DynamicImage::ImageRgb8(b);
+        let strict = |a: f64, b: f64| {
+            if b > 0.9 {
+                return Err(Error::BlendOp(format!("b out of range: {b}")));
+            }
+            Ok(a + b)
+        };
+        let mut a_copy = a.clone();
+        let res = a_copy.try_blend(&b, strict, true, false);
+        assert!(matches!(res, Err(Error::BlendOp(_))));
+        // Pixels before the failing one were written, pixels after were not
+        assert_eq!(a_copy.as_rgb8().unwrap().get_pixel(0, 0).0, [110, 120, 130]);
+        assert_eq!(a_copy.as_rgb8().unwrap().get_pixel(3, 3).0, [10, 20, 30]);
+
+        let mut a_copy = a.clone();
+        a_copy.try_blend(&a, strict, true, false).unwrap();
+        assert_eq!(a_copy.as_rgb8().unwrap().get_pixel(0, 0).0, [20, 40, 60]);
+    }
+    #[test]
+    fn test_grain_ops() {
+        assert_eq!(pixel_grain_extract(0.5, 0.5), 0.5);
+        assert_eq!(pixel_grain_merge(0.5, 0.5), 0.5);
+        assert_eq!(pixel_grain_extract(0.75, 0.25), 1.0);
+        assert_eq!(pixel_grain_merge(0.25, 0.25), 0.0);
+        assert!((pixel_grain_merge(pixel_grain_extract(0.3, 0.6), 0.6) - 0.3).abs() < 1e-12);
+    }
+    #[test]
+    fn test_bipolar_add() {
+        // Neutral gray leaves a unchanged
+        for a in [0., 0.2, 0.5, 0.9, 1.] {
+            assert_eq!(pixel_bipolar_add(a, 0.5), a);
+        }
+        assert_eq!(pixel_bipolar_add(0.5, 0.75), 0.75);
+        assert_eq!(pixel_bipolar_add(0.5, 0.25), 0.25);
+        assert_eq!(BlendMode::BipolarAdd.op()(0.3, 0.6), pixel_bipolar_add(0.3, 0.6));
+
+        let img1 = open("test_data/1.png").unwrap();
+        let mut blended = img1.clone();
+        // 0.5 can only be stored exactly in a float image
+        let neutral = solid_color(img1.width(), img1.height(), image::ColorType::Rgb32F, &[0.5, 0.5, 0.5]).unwrap();
+        blended.blend_mode(&neutral, BlendMode::BipolarAdd, true, false).unwrap();
+        assert!(blended.as_bytes() == img1.as_bytes());
+    }
+    #[test]
+    fn test_replace_color() {
+        // A dark square on a slightly noisy green screen
+        let mut subject: RgbaImage = ImageBuffer::from_fn(16, 16, |x, y| {
+            if (4..12).contains(&x) && (4..12).contains(&y) {
+                Rgba([40, 30, 20, 255])
+            } else {
+                Rgba([u8::try_from((x + y) % 3).unwrap(), 250, 2, 255])
+            }
+        });
+        let original = subject.clone();
+        let background: ImageBuffer<image::Rgba<u16>, Vec<u16>> = ImageBuffer::from_fn(16, 16, |x, _| image::Rgba([0, 0, u16::try_from(x * 4000).unwrap(), 32768]));
+        subject.replace_color(&background, [0., 1., 0.], 0.05).unwrap();
+        for (x, y, px) in subject.enumerate_pixels() {
+            if (4..12).contains(&x) && (4..12).contains(&y) {
+                assert_eq!(px, original.get_pixel(x, y));
+            } else {
+                let expected = (f64::from(x * 4000) / 65535. * 255.).round();
+                assert_eq!((px.0[0], px.0[1], f64::from(px.0[2]), px.0[3]), (0, 0, expected, 128));
+            }
+        }
+
+        // A tolerance of 0 only matches exactly
+        let mut exact = original.clone();
+        exact.replace_color(&background, [40. / 255., 30. / 255., 20. / 255.], 0.).unwrap();
+        assert_eq!(exact.get_pixel(4, 4).0, [0, 0, 62, 128]);
+        assert_eq!(exact.get_pixel(0, 0), original.get_pixel(0, 0));
+
+        assert!(matches!(exact.replace_color(&background, [0., 1., 0.], -1.), Err(Error::InvalidArgument(..))));
+    }
+    #[test]
+    fn test_min_delta() {
+        let base = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(4, 4, image::Rgba([100, 150, 200, 128])));
+        let nudge = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(4, 4, image::Rgba([2, 2, 2, 2])));
+
+        let mut skipped = base.clone();
+        let options = BlendOptions {
+            min_delta: 0.01,
+            apply_to_alpha: true,
+            ..BlendOptions::new(pixel_add)
+        };
+        skipped.blend_with_options(&nudge, &options).unwrap();
+        assert_eq!(skipped.as_bytes(), base.as_bytes());
+
+        let mut written = base.clone();
+        let options = BlendOptions {
+            min_delta: 0.,
+            ..options
+        };
+        written.blend_with_options(&nudge, &options).unwrap();
+        assert_ne!(written.as_bytes(), base.as_bytes());
+    }
+    #[test]
+    fn test_mixed_depth_alpha_normalization() {
+        let half = 128. / 255.;
+        let close = |a: f32, b: f64| (f64::from(a) - b).abs() <= 1. / 255.;
+        let dst = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 0, 255])));
+        let src = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 255, 255, 128])));
+        as_all_types(&dst).for_each(|a| {
+            let structure_a: ColorStructure = a.color().into();
+            as_all_types(&src).for_each(|b| {
+                let structure_b: ColorStructure = b.color().into();
+                if !structure_a.rgb() && structure_b.rgb() {
+                    return;
+                }
+                let types = format!("{} <- {}", a.color().color_str(), b.color().color_str());
+
+                // Color weighting uses other's alpha normalized by other's max
+                let mut blended = a.clone();
+                blended.blend(&b, pixel_normal, true, false).unwrap();
+                let expected = if structure_b.alpha() { half } else { 1. };
+                let px = blended.to_rgba32f().get_pixel(0, 0).0;
+                assert!(close(px[0], expected), "blend {types}: {px:?}");
+
+                if !structure_a.alpha() || !structure_b.alpha() {
+                    return;
+                }
+                // Alpha values are rescaled from other's max to self's max
+                let mut transplanted = a.clone();
+                transplanted.transplant_alpha(&b).unwrap();
+                let px = transplanted.to_rgba32f().get_pixel(0, 0).0;
+                assert!(close(px[3], half), "transplant_alpha {types}: {px:?}");
+
+                let mut set = a.clone();
+                set.set_alpha(&b.get_alpha().unwrap()).unwrap();
+                let px = set.to_rgba32f().get_pixel(0, 0).0;
+                assert!(close(px[3], half), "set_alpha {types}: {px:?}");
+
+                let mut alpha_blended = a.clone();
+                alpha_blended.blend(&b, pixel_mult, false, true).unwrap();
+                let px = alpha_blended.to_rgba32f().get_pixel(0, 0).0;
+                assert!(close(px[3], half), "blend alpha {types}: {px:?}");
+            });
+            if structure_a.alpha() {
+                let mut stripped = a.clone();
+                stripped.set_alpha(&src).unwrap();
+                stripped.strip_alpha().unwrap();
+                let px = stripped.to_rgba32f().get_pixel(0, 0).0;
+                assert_eq!(px[3], 1., "strip_alpha {}", a.color().color_str());
+            }
+        });
+    }
+    #[test]
+    fn test_set_alpha_out_of_range_float() {
+        let mut a = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 0, 0])));
+        let b = DynamicImage::ImageRgba32F(image::Rgba32FImage::from_pixel(2, 2, image::Rgba([1.5, 1.5, 1.5, 1.5])));
+        a.set_alpha(&b).unwrap();
+        assert_eq!(a.as_rgba8().unwrap().get_pixel(0, 0).0[3], 255);
+        a.transplant_alpha(&b).unwrap();
+        assert_eq!(a.as_rgba8().unwrap().get_pixel(0, 0).0[3], 255);
+    }
+    #[test]
+    fn test_blend_channel_role() {
+        let a = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(2, 2, image::Rgba([10, 20, 30, 200])));
+        let b = DynamicImage::ImageRgba16(image::ImageBuffer::from_pixel(2, 2, image::Rgba([0u16, 0, 0, 100 * 257])));
+
+        let mut alpha_blended = a.clone();
+        alpha_blended.blend_channel_role(&b, pixel_normal, Channel::Alpha).unwrap();
+        assert_eq!(alpha_blended.as_rgba8().unwrap().get_pixel(0, 0).0, [10, 20, 30, 100]);
+
+        // Color roles are composited by both alphas and clamp their inputs like `blend`
+        let mut green_blended = a.clone();
+        green_blended.blend_channel_role(&a, pixel_add, Channel::Green).unwrap();
+        let mut expected = a.clone();
+        expected.blend(&a, pixel_add, true, false).unwrap();
+        assert_eq!(green_blended.as_rgba8().unwrap().get_pixel(0, 0).0, [10, expected.as_rgba8().unwrap().get_pixel(0, 0).0[1], 30, 200]);
+
+        let translucent = DynamicImage::ImageRgba32F(image::Rgba32FImage::from_pixel(2, 2, image::Rgba([0., 1.5, 0., 0.5])));
+        let mut green_blended = a.clone();
+        green_blended.blend_channel_role(&translucent, pixel_normal, Channel::Green).unwrap();
+        let mut expected = a.clone();
+        expected.blend(&translucent, pixel_normal, true, false).unwrap();
+        assert_eq!(green_blended.as_rgba8().unwrap().get_pixel(0, 0).0, [10, expected.as_rgba8().unwrap().get_pixel(0, 0).0[1], 30, 200]);
+
+        let mut luma = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([10])));
+        let res = luma.blend_channel_role(&a, pixel_add, Channel::Red);
+        assert!(matches!(res, Err(Error::MissingChannel(Channel::Red, "L"))));
+        let res = luma.blend_channel_role(&a, pixel_add, Channel::Alpha);
+        assert!(matches!(res, Err(Error::MissingChannel(Channel::Alpha, "L"))));
+    }
+    #[test]
+    fn test_invert_channels() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(16, 16, |x, y| {
+            let v = u8::try_from(x * 16 + y).unwrap();
+            image::Rgb([v, 255 - v, v / 2])
+        }));
+        let mut inverted = img.clone();
+        inverted.invert_channels(true, true).unwrap();
+        assert_eq!(inverted.as_rgb8().unwrap().get_pixel(3, 5).0, [255 - 53, 53, 255 - 26]);
+        inverted.invert_channels(true, true).unwrap();
+        assert_eq!(inverted.as_bytes(), img.as_bytes());
+
+        let img = DynamicImage::ImageRgba16(image::ImageBuffer::from_pixel(4, 4, image::Rgba([0u16, 1000, 65535, 1234])));
+        let mut inverted = img.clone();
+        inverted.invert_channels(true, false).unwrap();
+        assert_eq!(inverted.as_rgba16().unwrap().get_pixel(0, 0).0, [65535, 64535, 0, 1234]);
+    }
+    #[test]
+    fn test_levels() {
+        let gradient = DynamicImage::ImageLuma8(image::GrayImage::from_fn(256, 1, |x, _| {
+            // Low contrast gradient in 0.2..0.8
+            image::Luma([u8::try_from(51 + x * 153 / 255).unwrap()])
+        }));
+        let mut stretched = gradient.clone();
+        stretched.levels(0.2, 0.8, 1., true, false).unwrap();
+        let values = stretched.as_luma8().unwrap().as_raw();
+        assert_eq!(*values.iter().min().unwrap(), 0);
+        assert_eq!(*values.iter().max().unwrap(), 255);
+
+        let mut invalid = gradient.clone();
+        assert!(matches!(invalid.levels(0.8, 0.2, 1., true, false), Err(Error::InvalidArgument(_))));
+        assert!(matches!(invalid.levels(0.2, 0.8, 0., true, false), Err(Error::InvalidArgument(_))));
+        // NaN passes both comparisons above, so needs its own check
+        assert!(matches!(invalid.levels(f64::NAN, 0.8, 1., true, false), Err(Error::InvalidArgument(_))));
+        assert!(matches!(invalid.levels(0.2, f64::NAN, 1., true, false), Err(Error::InvalidArgument(_))));
+        assert!(matches!(invalid.levels(0.2, 0.8, f64::NAN, true, false), Err(Error::InvalidArgument(_))));
+        assert!(matches!(invalid.levels(0.2, f64::INFINITY, 1., true, false), Err(Error::InvalidArgument(_))));
+        assert_eq!(invalid, gradient);
+    }
+    #[test]
+    fn test_blend_dyn() {
+        type StrategyVec = Vec<(Box<dyn BlendStrategy>, fn(f64, f64) -> f64)>;
+        let img1 = open("test_data/1_solid.png").unwrap();
+        let img2 = open("test_data/2_solid.png").unwrap();
+        let strategies: StrategyVec = vec![
+            (Box::new(BlendMode::Multiply), pixel_mult),
+            (Box::new(BlendMode::Screen), pixel_screen),
+            (Box::new(BlendMode::Custom(pixel_sub)), pixel_sub),
+            (Box::new(f64::midpoint), f64::midpoint),
+        ];
+        for (strategy, op) in &strategies {
+            let mut dyn_blended = img1.clone();
+            dyn_blended.blend_dyn(&img2, strategy.as_ref(), true, false).unwrap();
+            let mut blended = img1.clone();
+            blended.blend(&img2, *op, true, false).unwrap();
+            assert_eq!(dyn_blended.as_bytes(), blended.as_bytes());
+        }
+    }
+    #[test]
+    fn test_clamp_alpha() {
+        let img = open("test_data/1.png").unwrap();
+        as_all_types(&img).for_each(|a| {
+            let structure: ColorStructure = a.color().into();
+            let mut clamped = a.clone();
+            let res = clamped.clamp_alpha(0., 0.3);
+            if !structure.alpha() {
+                assert!(matches!(res, Err(Error::NoAlphaChannel)));
+                return;
+            }
+            res.unwrap();
+            let max_alpha = clamped.to_rgba32f().pixels().map(|px| px.0[3]).fold(0f32, f32::max);
+            assert!(max_alpha <= 0.3, "{}: {max_alpha}", a.color().color_str());
+        });
+        let mut invalid = img.clone();
+        assert!(matches!(invalid.clamp_alpha(0.5, 0.3), Err(Error::InvalidArgument(_))));
+        assert!(matches!(invalid.clamp_alpha(f64::NAN, 0.3), Err(Error::InvalidArgument(_))));
+        assert!(matches!(invalid.clamp_alpha(0., f64::NAN), Err(Error::InvalidArgument(_))));
+        assert_eq!(invalid, img);
+
+        // 0.3 is 76.5 in 8 bit, between two values, so every alpha goes to the nearest one
+        let mut narrow = RgbaImage::from_fn(256, 1, |x, _| Rgba([0, 0, 0, u8::try_from(x).unwrap()]));
+        narrow.clamp_alpha(0.3, 0.3).unwrap();
+        assert!(narrow.pixels().all(|px| px.0[3] == 77));
+        let mut between = RgbaImage::from_fn(256, 1, |x, _| Rgba([0, 0, 0, u8::try_from(x).unwrap()]));
+        between.clamp_alpha(0.3005, 0.3015).unwrap();
+        assert!(between.pixels().all(|px| px.0[3] == 77));
+    }
+    #[test]
+    fn test_scale_alpha() {
+        let img = open("test_data/1.png").unwrap();
+        as_all_types(&img).for_each(|a| {
+            let structure: ColorStructure = a.color().into();
+            let mut scaled = a.clone();
+            let res = scaled.scale_alpha(0.5);
+            if !structure.alpha() {
+                assert!(matches!(res, Err(Error::NoAlphaChannel)));
+                return;
+            }
+            res.unwrap();
+            // Rounding to 8 bits is the worst case
+            let tolerance = 0.5 / 255. + f32::EPSILON;
+            a.to_rgba32f().pixels().zip(scaled.to_rgba32f().pixels()).for_each(|(before, after)| {
+                assert_eq!(before.0[..3], after.0[..3]);
+                assert!((before.0[3] * 0.5 - after.0[3]).abs() <= tolerance, "{}: {} -> {}", a.color().color_str(), before.0[3], after.0[3]);
+            });
+        });
+        let mut saturated = DynamicImage::ImageLumaA8(image::ImageBuffer::from_pixel(2, 2, image::LumaA([10u8, 200])));
+        saturated.scale_alpha(2.).unwrap();
+        assert_eq!(saturated.as_luma_alpha8().unwrap().get_pixel(0, 0).0, [10, 255]);
+        saturated.scale_alpha(-1.).unwrap();
+        assert_eq!(saturated.as_luma_alpha8().unwrap().get_pixel(0, 0).0, [10, 0]);
+        assert!(matches!(saturated.scale_alpha(f64::NAN), Err(Error::InvalidArgument(_))));
+    }
+    #[test]
+    fn test_multiply_alpha() {
+        let half = DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, image::Rgba([200, 100, 50, 128])));
+        let half_16 = DynamicImage::ImageLumaA16(image::ImageBuffer::from_pixel(4, 4, image::LumaA([0u16, 32768])));
+        let mut intersected = half.clone();
+        intersected.multiply_alpha(&half_16).unwrap();
+        assert_eq!(intersected.as_rgba8().unwrap().get_pixel(0, 0).0, [200, 100, 50, 64]);
+
+        let mut no_alpha = DynamicImage::ImageRgb8(half.to_rgb8());
+        assert!(matches!(no_alpha.multiply_alpha(&half), Err(Error::NoAlphaChannel)));
+        let mut intersected = half.clone();
+        assert!(matches!(intersected.multiply_alpha(&no_alpha), Err(Error::NoAlphaChannel)));
+    }
+    #[test]
+    fn test_solid_color() {
+        let magenta = solid_color(8, 4, image::ColorType::Rgba16, &[1., 0., 1., 1.]).unwrap();
+        assert_eq!(magenta.color(), image::ColorType::Rgba16);
+        assert!(magenta.as_rgba16().unwrap().pixels().all(|px| px.0 == [65535, 0, 65535, 65535]));
+
+        let mut gray = solid_color(8, 4, image::ColorType::Rgb8, &[0.5, 0.5, 0.5]).unwrap();
+        gray.blend(&magenta, pixel_mult, true, false).unwrap();
+        assert_eq!(gray.as_rgb8().unwrap().get_pixel(0, 0).0, [128, 0, 128]);
+
+        assert!(matches!(
+            solid_color(8, 4, image::ColorType::Rgb8, &[0.5, 0.5]),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+    #[test]
+    fn test_blend_modes_deterministic() {
+        // The modes drawn by the contact_sheet example, on the same generated inputs
+        let base = gradient(48, 48, Direction::Horizontal, image::ColorType::Rgba8, &[0.1, 0.2, 0.8, 1.], &[1., 0.8, 0.1, 1.]).unwrap();
+        let top = gradient(48, 48, Direction::Vertical, image::ColorType::Rgba8, &[1., 0.4, 0.2, 1.], &[0.2, 0.9, 0.6, 0.25]).unwrap();
+        let modes = [
+            BlendMode::Normal, BlendMode::Add, BlendMode::Subtract, BlendMode::Divide, BlendMode::Darker, BlendMode::Lighter, BlendMode::Difference,
+            BlendMode::Multiply, BlendMode::Screen, BlendMode::Overlay, BlendMode::HardLight, BlendMode::SoftLight, BlendMode::GrainExtract,
+            BlendMode::GrainMerge, BlendMode::BipolarAdd, BlendMode::Phoenix, BlendMode::LinearBurn, BlendMode::LinearDodge, BlendMode::LinearLight,
+            BlendMode::VividLight, BlendMode::PinLight, BlendMode::HardMix, BlendMode::Average, BlendMode::Dissolve(7), BlendMode::Custom(pixel_normal),
+        ];
+        for mode in modes {
+            let blended = || {
+                let mut tile = base.clone();
+                tile.blend_mode(&top, mode, true, false).unwrap();
+                tile
+            };
+            assert_eq!(blended(), blended(), "{mode:?}");
+        }
+    }
+    #[test]
+    fn test_blend_mode_normal_fast_path() {
+        let img1 = DynamicImage::ImageRgba8(open("test_data/1.png").unwrap().into_rgba8());
+        let img2 = DynamicImage::ImageRgba8(open("test_data/2.png").unwrap().into_rgba8());
+        for (do_color, do_alpha) in [(true, false), (true, true), (false, true)] {
+            let mut fast = img1.clone();
+            fast.blend_mode(&img2, BlendMode::Normal, do_color, do_alpha).unwrap();
+            let mut float = img1.clone();
+            float.blend(&img2, pixel_normal, do_color, do_alpha).unwrap();
+            assert_eq!(fast.as_bytes(), float.as_bytes());
+        }
+        let mut mult = img1.clone();
+        mult.blend_mode(&img2, BlendMode::Multiply, true, false).unwrap();
+        let mut float = img1.clone();
+        float.blend(&img2, pixel_mult, true, false).unwrap();
+        assert_eq!(mult.as_bytes(), float.as_bytes());
+
+        // Out of range float input is clamped the same way on both paths
+        let bottom = DynamicImage::ImageRgba32F(image::Rgba32FImage::from_fn(2, 1, |x, _| {
+            image::Rgba([0.5, 1.5, -0.5, [1., 1.5][x as usize]])
+        }));
+        let top = DynamicImage::ImageRgba32F(image::Rgba32FImage::from_fn(2, 1, |x, _| {
+            image::Rgba([2., -1., 0.25, [1., 0.5][x as usize]])
+        }));
+        for (do_color, do_alpha) in [(true, false), (true, true), (false, true)] {
+            let mut fast = bottom.clone();
+            fast.blend_mode(&top, BlendMode::Normal, do_color, do_alpha).unwrap();
+            let mut float = bottom.clone();
+            float.blend(&top, pixel_normal, do_color, do_alpha).unwrap();
+            assert_eq!(fast, float);
+        }
+        let mut fast = bottom.clone();
+        fast.blend_mode(&top, BlendMode::Normal, true, true).unwrap();
+        assert_eq!(fast.as_rgba32f().unwrap().get_pixel(0, 0).0, [1., 0., 0.25, 1.]);
+    }
+    #[test]
+    fn test_map_pixels_positional() {
+        let width = 101;
+        let mut gradient = DynamicImage::ImageLuma16(image::ImageBuffer::new(width, 3));
+        gradient
+            .map_pixels_positional(|x, _, _| f64::from(x) / f64::from(width - 1), true, false)
+            .unwrap();
+        let buffer = gradient.as_luma16().unwrap();
+        assert_eq!(buffer.get_pixel(0, 1).0, [0]);
+        assert_eq!(buffer.get_pixel(50, 1).0, [32768]);
+        assert_eq!(buffer.get_pixel(width - 1, 2).0, [65535]);
+    }
+    #[test]
+    fn test_psnr() {
+        let img1 = open("test_data/1.png").unwrap();
+        assert_eq!(mse(&img1, &img1).unwrap(), 0.);
+        assert_eq!(psnr(&img1, &img1).unwrap(), f64::INFINITY);
+
+        let dark = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(16, 16, image::Luma([100])));
+        let light = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(16, 16, image::Luma([101])));
+        // One level of difference everywhere gives an mse of (1/255)^2, so psnr is 20 * log10(255)
+        assert!((mse(&dark, &light).unwrap() - (1. / 255_f64).powi(2)).abs() < 1e-9);
+        assert!((psnr(&dark, &light).unwrap() - 20. * 255_f64.log10()).abs() < 1e-3);
+        // Bit depth does not matter, only the channel layout
+        let light16 = DynamicImage::ImageLuma16(light.to_luma16());
+        assert!((psnr(&dark, &light16).unwrap() - 20. * 255_f64.log10()).abs() < 1e-3);
+
+        let small = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(8, 8, image::Luma([100])));
+        assert!(matches!(psnr(&dark, &small), Err(Error::DimensionMismatch)));
+        let rgb = DynamicImage::ImageRgb8(dark.to_rgb8());
+        assert!(matches!(psnr(&rgb, &dark), Err(Error::UnsupportedBlend(_, _))));
+    }
+    #[test]
+    fn test_get_alpha_or_opaque() {
+        let img1 = open("test_data/1.png").unwrap();
+        let rgb = DynamicImage::ImageRgb8(img1.to_rgb8());
+        assert!(rgb.get_alpha().is_none());
+        let mask = rgb.get_alpha_or_opaque();
+        assert_eq!(mask.color(), image::ColorType::Rgb8);
+        assert_eq!((mask.width(), mask.height()), (rgb.width(), rgb.height()));
+        assert!(mask.as_rgb8().unwrap().pixels().all(|px| px.0 == [255, 255, 255]));
+
+        let rgba = DynamicImage::ImageRgba8(img1.to_rgba8());
+        assert_eq!(rgba.get_alpha_or_opaque().as_bytes(), rgba.get_alpha().unwrap().as_bytes());
+    }
+    #[test]
+    fn test_phoenix() {
+        assert!((pixel_phoenix(0.2, 0.8) - 0.4).abs() < 1e-12);
+        assert_eq!(pixel_phoenix(0.8, 0.2), pixel_phoenix(0.2, 0.8));
+        assert_eq!(pixel_phoenix(0.5, 0.5), 1.);
+
+        let mut dark = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(4, 4, image::Rgb([0, 51, 255])));
+        let light = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(4, 4, image::Rgb([255, 204, 255])));
+        dark.blend(&light, pixel_phoenix, true, false).unwrap();
+        let [r, g, b] = dark.as_rgb8().unwrap().get_pixel(0, 0).0;
+        // 0.4 * 255 = 102, within one level of float error on write back
+        assert_eq!((r, b), (0, 255));
+        assert!((101..=102).contains(&g));
+    }
+    #[test]
+    fn test_blend_indexed() {
+        let img1 = open("test_data/1.png").unwrap();
+        let img2 = open("test_data/2.png").unwrap();
+        let boost_green = |ch: usize, a: f64, b: f64| if ch == 1 { (a + b) * 2. } else { pixel_mult(a, b) };
+
+        let mut indexed = img1.clone();
+        indexed.blend_indexed(&img2, boost_green, true, false).unwrap();
+        let mut mult = img1.clone();
+        mult.blend(&img2, pixel_mult, true, false).unwrap();
+        let mut boosted = img1.clone();
+        boosted.blend(&img2, |a, b| (a + b) * 2., true, false).unwrap();
+
+        let indexed = indexed.to_rgba8();
+        let (mult, boosted) = (mult.to_rgba8(), boosted.to_rgba8());
+        for ((px, px_mult), px_boosted) in indexed.pixels().zip(mult.pixels()).zip(boosted.pixels()) {
+            assert_eq!([px[0], px[2], px[3]], [px_mult[0], px_mult[2], px_mult[3]]);
+            assert_eq!(px[1], px_boosted[1]);
+        }
+    }
+    #[test]
+    fn test_convert_to() {
+        use image::ColorType;
+        let rgba8 = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, image::Rgba([255, 128, 0, 64])));
+        let cases = [
+            (ColorType::Rgba8, 4),
+            (ColorType::Rgb8, 3),
+            (ColorType::La8, 2),
+            (ColorType::L8, 1),
+            (ColorType::Rgba16, 4),
+            (ColorType::Rgb16, 3),
+            (ColorType::La16, 2),
+            (ColorType::L16, 1),
+            (ColorType::Rgba32F, 4),
+            (ColorType::Rgb32F, 3),
+        ];
+        for (target, channels) in cases {
+            let converted = rgba8.convert_to(target).unwrap();
+            assert_eq!(converted.color(), target);
+            assert_eq!(converted.color().channel_count(), channels);
+            // Converting back to the source type is lossless for the channels that were kept
+            let back = converted.convert_to(ColorType::Rgba8).unwrap();
+            let px = back.as_rgba8().unwrap().get_pixel(0, 0).0;
+            if target.has_alpha() {
+                assert_eq!(px[3], 64);
+            } else {
+                assert_eq!(px[3], 255);
+            }
+            if target.has_color() {
+                assert_eq!(&px[..3], &[255, 128, 0]);
+            }
+        }
+        // Bit depth is scaled to the new type
+        let rgba16 = rgba8.convert_to(ColorType::Rgba16).unwrap();
+        assert_eq!(rgba16.as_rgba16().unwrap().get_pixel(0, 0).0, [65535, 32896, 0, 16448]);
+        let rgb32f = rgba8.convert_to(ColorType::Rgb32F).unwrap();
+        assert_eq!(rgb32f.as_rgb32f().unwrap().get_pixel(0, 0).0[0], 1.);
+        // Adding alpha fills it with the maximum value
+        let opaque = DynamicImage::ImageRgb8(rgba8.to_rgb8()).convert_to(ColorType::La16).unwrap();
+        assert_eq!(opaque.as_luma_alpha16().unwrap().get_pixel(0, 0).0[1], 65535);
+    }
+    #[test]
+    fn test_blend_against_constant() {
+        let mut img = DynamicImage::ImageLuma8(image::GrayImage::from_fn(3, 1, |x, _| image::Luma([[40, 128, 215][x as usize]])));
+        // Overlay against 0.5 is neutral
+        let original = img.clone();
+        img.blend_against_constant(0.5, pixel_overlay, true, false).unwrap();
+        assert_eq!(img.as_bytes(), original.as_bytes());
+
+        // A contrast pivot at 0.5 leaves midtones alone and pushes the extremes apart
+        img.blend_against_constant(0.5, |a, pivot| pivot + (a - pivot) * 1.5, true, false).unwrap();
+        let [dark, mid, light] = [0, 1, 2].map(|x| img.as_luma8().unwrap().get_pixel(x, 0).0[0]);
+        assert_eq!(mid, 128);
+        assert!(dark < 40);
+        assert!(light > 215);
+
+
+        // Matches blending with a solid image of the same value
+        let img1 = DynamicImage::ImageRgb32F(open("test_data/1.png").unwrap().to_rgb32f());
+        let solid = solid_color(img1.width(), img1.height(), image::ColorType::Rgb32F, &[0.5, 0.5, 0.5]).unwrap();
+        let mut constant = img1.clone();
+        constant.blend_against_constant(0.5, pixel_mult, true, false).unwrap();
+        let mut blended = img1.clone();
+        blended.blend(&solid, pixel_mult, true, false).unwrap();
+        assert_eq!(constant.as_bytes(), blended.as_bytes());
+    }
+    #[test]
+    fn test_into_premultiplied() {
+        let white = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, image::Rgba([255, 255, 255, 128])));
+        let premultiplied = white.into_premultiplied();
+        assert_eq!(premultiplied.as_rgba8().unwrap().get_pixel(0, 0).0, [128, 128, 128, 128]);
+        // The original is untouched
+        assert_eq!(white.as_rgba8().unwrap().get_pixel(0, 0).0, [255, 255, 255, 128]);
+
+        let half = DynamicImage::ImageRgba32F(image::Rgba32FImage::from_pixel(2, 2, image::Rgba([1., 0.5, 0., 0.5])));
+        assert_eq!(half.into_premultiplied().as_rgba32f().unwrap().get_pixel(0, 0).0, [0.5, 0.25, 0., 0.5]);
+
+        let opaque = DynamicImage::ImageRgb8(white.to_rgb8());
+        assert_eq!(opaque.into_premultiplied().as_bytes(), opaque.as_bytes());
+        let mut no_alpha = opaque.clone();
+        assert!(matches!(no_alpha.premultiply(), Err(Error::NoAlphaChannel)));
+    }
+    #[test]
+    fn test_blend_slices() {
+        let img1 = open("test_data/1.png").unwrap().to_rgba8();
+        let img2 = open("test_data/2.png").unwrap().to_rgb8();
+        let (width, height) = img1.dimensions();
+
+        let mut expected = img1.clone();
+        expected.blend(&img2, pixel_screen, true, false).unwrap();
+
+        // Packed slices
+        let mut dst: Vec<u8> = img1.as_raw().clone();
+        let src: Vec<u8> = img2.as_raw().clone();
+        blend_slices(&mut dst, SliceLayout::packed(width, height, 4), &src, SliceLayout::packed(width, height, 3), pixel_screen, true, false).unwrap();
+        assert_eq!(&dst, expected.as_raw());
+
+        // Padded rows are blended the same, and the padding is left alone
+        let row_len = width as usize * 4;
+        let stride = row_len + 3;
+        let mut padded: Vec<u8> = vec![7; stride * height as usize];
+        for (row, chunk) in img1.as_raw().chunks(row_len).enumerate() {
+            padded[row * stride..row * stride + row_len].copy_from_slice(chunk);
+        }
+        let layout = SliceLayout { width, height, channels: 4, row_stride: stride };
+        blend_slices(&mut padded, layout, &src, SliceLayout::packed(width, height, 3), pixel_screen, true, false).unwrap();
+        for (row, chunk) in expected.as_raw().chunks(row_len).enumerate() {
+            assert_eq!(&padded[row * stride..row * stride + row_len], chunk);
+            assert_eq!(&padded[row * stride + row_len..(row + 1) * stride], &[7, 7, 7]);
+        }
+
+        assert!(matches!(
+            blend_slices(&mut dst, SliceLayout::packed(width, height, 4), &src[1..], SliceLayout::packed(width, height, 3), pixel_screen, true, false),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            blend_slices(&mut dst, SliceLayout::packed(width, height, 4), &src, SliceLayout::packed(width, height - 1, 3), pixel_screen, true, false),
+            Err(Error::DimensionMismatch)
+        ));
+        // A stride large enough to overflow the required length is rejected rather than wrapping past the check
+        let huge = SliceLayout { width: 2, height: 3, channels: 4, row_stride: usize::MAX / 2 };
+        let mut small = vec![0u8; 2 * 3 * 4];
+        assert!(matches!(
+            blend_slices(&mut small, huge, &src, SliceLayout::packed(2, 3, 3), pixel_screen, true, false),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+    #[test]
+    fn test_linear_burn() {
+        assert!((pixel_linear_burn(0.7, 0.7) - 0.4).abs() < 1e-12);
+        assert_eq!(BlendMode::LinearBurn.op()(0.7, 0.7), pixel_linear_burn(0.7, 0.7));
+        assert_eq!(BlendMode::LinearDodge.op()(0.2, 0.3), pixel_add(0.2, 0.3));
+
+        // Results below 0 are clamped
+        let mut dark = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([51])));
+        dark.blend_mode(&dark.clone(), BlendMode::LinearBurn, true, false).unwrap();
+        assert_eq!(dark.as_luma8().unwrap().get_pixel(0, 0).0, [0]);
+    }
+    #[test]
+    fn test_rounding() {
+        let base = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 1, image::Luma([0])));
+        // Both ops land exactly halfway between two u8 values, 126.5 and 127.5
+        let halfway_even: fn(f64, f64) -> f64 = |_, _| 126.5 / 255.;
+        let halfway_odd: fn(f64, f64) -> f64 = |_, _| 127.5 / 255.;
+        let cases = [
+            (Rounding::Truncate, 126, 127),
+            (Rounding::Round, 127, 128),
+            (Rounding::Banker, 126, 128),
+        ];
+        for (rounding, even, odd) in cases {
+            for (op, expected) in [(halfway_even, even), (halfway_odd, odd)] {
+                let mut img = base.clone();
+                let options = BlendOptions {
+                    rounding,
+                    ..BlendOptions::new(op)
+                };
+                img.blend_with_options(&base, &options).unwrap();
+                assert_eq!(img.as_luma8().unwrap().get_pixel(0, 0).0, [expected], "{rounding:?}");
+            }
+        }
+        assert_eq!(BlendOptions::new(pixel_add).rounding, Rounding::Round);
+
+        // Float images are never rounded
+        let base = DynamicImage::ImageRgb32F(image::Rgb32FImage::from_pixel(1, 1, image::Rgb([0., 0., 0.])));
+        let mut img = base.clone();
+        let options = BlendOptions {
+            rounding: Rounding::Truncate,
+            ..BlendOptions::new(halfway_odd)
+        };
+        img.blend_with_options(&base, &options).unwrap();
+        assert!((f64::from(img.as_rgb32f().unwrap().get_pixel(0, 0).0[0]) - 127.5 / 255.).abs() < 1e-6);
+    }
+    #[test]
+    fn test_blend_generated() {
+        let img1 = open("test_data/1.png").unwrap();
+        let (width, height) = (img1.width(), img1.height());
+        let (cx, cy) = (f64::from(width - 1) / 2., f64::from(height - 1) / 2.);
+        let max_dist = cx.hypot(cy);
+        // White in the center, fading to black in the corners
+        let radial = |x: u32, y: u32| {
+            let v = 1. - (f64::from(x) - cx).hypot(f64::from(y) - cy) / max_dist;
+            [v, v, v, 1.]
+        };
+        let mut vignette = img1.clone();
+        vignette.blend_generated(radial, pixel_mult, true, false).unwrap();
+        let (original, vignette) = (img1.to_rgba8(), vignette.to_rgba8());
+
+        for (x, y) in [(0, 0), (width - 1, 0), (0, height - 1), (width - 1, height - 1)] {
+            assert_eq!(vignette.get_pixel(x, y).0[..3], [0, 0, 0]);
+        }
+        for (px, px_original) in vignette.pixels().zip(original.pixels()) {
+            assert!(px.0[..3].iter().zip(&px_original.0[..3]).all(|(a, b)| a <= b));
+            assert_eq!(px.0[3], px_original.0[3]);
+        }
+
+        // A fully transparent generator changes nothing
+        let mut unchanged = img1.clone();
+        unchanged.blend_generated(|_, _| [1., 1., 1., 0.], pixel_normal, true, false).unwrap();
+        assert_eq!(unchanged.as_bytes(), img1.as_bytes());
+    }
+    #[test]
+    fn test_split_join_alpha() {
+        let img1 = DynamicImage::ImageRgba8(open("test_data/1.png").unwrap().into_rgba8());
+        let (color, alpha) = img1.split_alpha().unwrap();
+        assert_eq!(color.color(), image::ColorType::Rgb8);
+        assert_eq!(alpha.color(), image::ColorType::L8);
+        let joined = join_alpha(&color, &alpha).unwrap();
+        assert_eq!(joined.color(), image::ColorType::Rgba8);
+        assert_eq!(joined.as_bytes(), img1.as_bytes());
+
+        let img16 = DynamicImage::ImageLumaA16(img1.to_luma_alpha16());
+        let (color, alpha) = img16.split_alpha().unwrap();
+        assert_eq!((color.color(), alpha.color()), (image::ColorType::L16, image::ColorType::L16));
+        assert_eq!(join_alpha(&color, &alpha).unwrap().as_bytes(), img16.as_bytes());
+
+        assert!(matches!(color.split_alpha(), Err(Error::NoAlphaChannel)));
+    }
+    #[test]
+    fn test_to_luminance() {
+        let red = DynamicImage::ImageRgba32F(image::Rgba32FImage::from_pixel(2, 2, image::Rgba([1., 0., 0., 0.5])));
+        let rec_601 = red.to_luminance(LumaCoefficients::REC_601).as_rgba32f().unwrap().get_pixel(0, 0).0;
+        let rec_709 = red.to_luminance(LumaCoefficients::REC_709).as_rgba32f().unwrap().get_pixel(0, 0).0;
+        assert!((f64::from(rec_601[0]) - 0.299).abs() < 1e-6);
+        assert!((f64::from(rec_709[0]) - 0.2126).abs() < 1e-6);
+        assert!((f64::from(rec_601[0] - rec_709[0]) - (0.299 - 0.2126)).abs() < 1e-6);
+        assert_eq!(rec_601[0], rec_601[1]);
+        assert_eq!(rec_601[0], rec_601[2]);
+        assert_eq!(rec_601[3], 0.5);
+
+        let red8 = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(1, 1, image::Rgb([255, 0, 0])));
+        assert_eq!(red8.to_luminance(LumaCoefficients::default()).as_rgb8().unwrap().get_pixel(0, 0).0, [54, 54, 54]);
+        for coefficients in [LumaCoefficients::REC_601, LumaCoefficients::REC_709, LumaCoefficients::REC_2020] {
+            assert!((coefficients.luminance([1., 1., 1.]) - 1.).abs() < 1e-9);
+        }
+    }
+    #[test]
+    fn test_blend_padded() {
+        let base = DynamicImage::ImageRgba8(RgbaImage::from_pixel(40, 40, image::Rgba([10, 20, 30, 100])));
+        let overlay = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(20, 20, image::Rgb([200, 200, 200])));
+        let mut padded = base.clone();
+        padded.blend_padded(&overlay, pixel_normal, Anchor::Center, true, true).unwrap();
+        let padded = padded.as_rgba8().unwrap();
+        for (x, y, px) in padded.enumerate_pixels() {
+            if (10..30).contains(&x) && (10..30).contains(&y) {
+                assert_eq!(px.0, [200, 200, 200, 100]);
+            } else {
+                assert_eq!(px.0, [10, 20, 30, 100]);
+            }
+        }
+
+        // Parts of a larger image that fall outside are ignored
+        let mut small = overlay.clone();
+        small.blend_padded(&base, pixel_normal, Anchor::BottomRight, true, false).unwrap();
+        let mut cropped = overlay.clone();
+        cropped.blend(&base.crop_imm(20, 20, 20, 20), pixel_normal, true, false).unwrap();
+        assert_eq!(small.as_bytes(), cropped.as_bytes());
+    }
+    #[test]
+    fn test_hard_mix() {
+        assert_eq!(pixel_hard_mix(0.4, 0.7), 1.);
+        assert_eq!(pixel_hard_mix(0.3, 0.3), 0.);
+
+        // Partial alpha in other softens the threshold towards the original value
+        let base = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(2, 2, image::Rgb([102, 77, 0])));
+        let overlay = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, image::Rgba([179, 77, 0, 255])));
+        let mut opaque = base.clone();
+        opaque.blend(&overlay, pixel_hard_mix, true, false).unwrap();
+        assert_eq!(opaque.as_rgb8().unwrap().get_pixel(0, 0).0, [255, 0, 0]);
+
+        let half = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, image::Rgba([179, 77, 0, 128])));
+        let mut softened = base.clone();
+        softened.blend(&half, pixel_hard_mix, true, false).unwrap();
+        let [r, g, _] = softened.as_rgb8().unwrap().get_pixel(0, 0).0;
+        assert!(r > 102 && r < 255);
+        assert!(g > 0 && g < 77);
+    }
+    #[test]
+    fn test_blend_stream() {
+        let frame = |v: u8| DynamicImage::ImageLuma8(image::GrayImage::from_pixel(4, 4, image::Luma([v])));
+        let mut accumulated = frame(0);
+        let frames = vec![frame(200), frame(100), DynamicImage::ImageLuma8(image::GrayImage::new(2, 2)), frame(40)];
+        let blended = accumulated.blend_stream(frames.clone().into_iter(), pixel_average, MismatchPolicy::Skip, true, false).unwrap();
+        assert_eq!(blended, 3);
+        // ((0 + 200) / 2 + 100) / 2 = 100, then (100 + 40) / 2 = 70
+        assert!(accumulated.as_luma8().unwrap().pixels().all(|px| px.0 == [70]));
+
+        let mut strict = frame(0);
+        assert!(matches!(
+            strict.blend_stream(frames.into_iter(), pixel_average, MismatchPolicy::Error, true, false),
+            Err(Error::DimensionMismatch)
+        ));
+        assert!(strict.as_luma8().unwrap().pixels().all(|px| px.0 == [100]));
+    }
+    #[test]
+    fn test_unsupported_blend_message() {
+        let mut luma = DynamicImage::ImageLuma8(image::GrayImage::new(2, 2));
+        let rgb = DynamicImage::ImageRgb16(image::ImageBuffer::new(2, 2));
+        let err = luma.blend(&rgb, pixel_normal, true, false).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedBlend(_, _)));
+        let message = err.to_string();
+        assert!(message.contains("3-channel rgb source"), "{message}");
+        assert!(message.contains("1-channel luma destination"), "{message}");
+        assert!(message.contains("Convert 'a' to an rgb type"), "{message}");
+    }
+    #[test]
+    fn test_blend_files_premul_aware() {
+        let output = "tests_out/blend_files_premul_aware.png";
+        blend_files_premul_aware("test_data/1.png", AlphaMode::Straight, "test_data/2.png", AlphaMode::Premultiplied, output, &BlendOptions::new(pixel_mult)).unwrap();
+        let result = open(output).unwrap();
+
+        let mut expected = open("test_data/1.png").unwrap();
+        let mut top = open("test_data/2.png").unwrap().convert_to(image::ColorType::Rgba32F).unwrap();
+        top.unpremultiply().unwrap();
+        expected.blend(&top, pixel_mult, true, false).unwrap();
+        assert_eq!(result.as_bytes(), expected.as_bytes());
+
+        // Opaque, transparent and half transparent top pixels. The last is 0.5 gray once unpremultiplied
+        let bottom_path = "tests_out/blend_files_premul_aware_bottom.png";
+        let top_path = "tests_out/blend_files_premul_aware_top.png";
+        let bottom = image::RgbaImage::from_fn(3, 1, |x, _| image::Rgba([[200, 255, 255][x as usize], 100, 50, 255]));
+        let top = image::RgbaImage::from_fn(3, 1, |x, _| image::Rgba([[255, 0, 64][x as usize], [128, 0, 64][x as usize], 0, [255, 0, 128][x as usize]]));
+        bottom.save(bottom_path).unwrap();
+        top.save(top_path).unwrap();
+        blend_files_premul_aware(bottom_path, AlphaMode::Straight, top_path, AlphaMode::Premultiplied, output, &BlendOptions::new(pixel_mult)).unwrap();
+        let result = open(output).unwrap().into_rgba8();
+        assert_eq!(result.get_pixel(0, 0).0, [200, 50, 0, 255]);
+        assert_eq!(result.get_pixel(1, 0).0, [255, 100, 50, 255]);
+        // 1 * (1 - alpha) + 0.5 * alpha, where alpha is 128 / 255
+        assert_eq!(result.get_pixel(2, 0).0, [191, 75, 25, 255]);
+
+        // Luma inputs stay luma, so they can be blended with a luma bottom
+        let bottom = image::GrayAlphaImage::from_fn(2, 1, |x, _| image::LumaA([[200, 255][x as usize], 255]));
+        let top = image::GrayAlphaImage::from_fn(2, 1, |x, _| image::LumaA([[0, 64][x as usize], [0, 128][x as usize]]));
+        bottom.save(bottom_path).unwrap();
+        top.save(top_path).unwrap();
+        blend_files_premul_aware(bottom_path, AlphaMode::Straight, top_path, AlphaMode::Premultiplied, output, &BlendOptions::new(pixel_mult)).unwrap();
+        let result = open(output).unwrap();
+        assert_eq!(result.color(), image::ColorType::La8);
+        let result = result.into_luma_alpha8();
+        assert_eq!(result.get_pixel(0, 0).0, [200, 255]);
+        assert_eq!(result.get_pixel(1, 0).0, [191, 255]);
+
+        assert!(matches!(
+            blend_files_premul_aware("test_data/missing.png", AlphaMode::Straight, "test_data/2.png", AlphaMode::Straight, output, &BlendOptions::new(pixel_mult)),
+            Err(Error::Image(_))
+        ));
+    }
+    #[test]
+    fn test_overlay_hard_light_transpose() {
+        let steps = 64;
+        let grid = (0..=steps).map(|i| f64::from(i) / f64::from(steps));
+        for a in grid.clone() {
+            for b in grid.clone() {
+                assert_eq!(pixel_overlay(a, b), pixel_hard_light(b, a), "a={a} b={b}");
+            }
+        }
+        for v in [0.0, 0.25, 1.0] {
+            assert_eq!(pixel_overlay(0.5, v), v);
+            assert_eq!(pixel_hard_light(v, 0.5), v);
+        }
+    }
+    #[test]
+    fn test_blend_components_hsv() {
+        let red = DynamicImage::ImageRgb32F(image::Rgb32FImage::from_pixel(1, 1, image::Rgb([1., 0., 0.])));
+        let gray = DynamicImage::ImageRgb32F(image::Rgb32FImage::from_pixel(1, 1, image::Rgb([0.8, 0.8, 0.8])));
+        let close = |img: &DynamicImage, expected: [f32; 3]| {
+            let px = img.as_rgb32f().unwrap().get_pixel(0, 0).0;
+            assert!(iter::zip(px, expected).all(|(v, e)| (v - e).abs() < 1e-5), "{px:?} != {expected:?}");
+        };
+
+        // HSV keeps red fully saturated and darkens it to the gray's value
+        let mut hsv = red.clone();
+        hsv.blend_components(&gray, ComponentBlend::Value, ColorModel::Hsv).unwrap();
+        close(&hsv, [0.8, 0., 0.]);
+
+        // HSL lightens red towards white to reach the gray's lightness
+        let mut hsl = red.clone();
+        hsl.blend_components(&gray, ComponentBlend::Value, ColorModel::Hsl).unwrap();
+        close(&hsl, [1., 0.6, 0.6]);
+
+        let mut colored = gray.clone();
+        colored.blend_components(&red, ComponentBlend::Color, ColorModel::Hsv).unwrap();
+        close(&colored, [0.8, 0., 0.]);
+        let mut desaturated = red.clone();
+        desaturated.blend_components(&gray, ComponentBlend::Saturation, ColorModel::Hsv).unwrap();
+        close(&desaturated, [1., 1., 1.]);
+
+        // Out of range inputs are clamped like `blend`
+        let bright = DynamicImage::ImageRgb32F(image::Rgb32FImage::from_pixel(1, 1, image::Rgb([2., 0., 0.])));
+        let mut hsv = bright.clone();
+        hsv.blend_components(&gray, ComponentBlend::Value, ColorModel::Hsv).unwrap();
+        close(&hsv, [0.8, 0., 0.]);
+
+        let mut luma = DynamicImage::ImageLuma8(image::GrayImage::new(1, 1));
+        assert!(matches!(
+            luma.blend_components(&red, ComponentBlend::Hue, ColorModel::Hsv),
+            Err(Error::MissingChannel(Channel::Red, _))
+        ));
+    }
+    #[test]
+    fn test_skip_transparent() {
+        let base = DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, image::Rgba([128, 128, 128, 255])));
+        // Left half is a bright exposure, right half is masked out
+        let masked = DynamicImage::ImageRgba8(RgbaImage::from_fn(4, 4, |x, _| {
+            if x < 2 { image::Rgba([200, 90, 200, 255]) } else { image::Rgba([0, 0, 0, 0]) }
+        }));
+        let dark = DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, image::Rgba([100, 100, 100, 255])));
+
+        let mut naive = base.clone();
+        naive.blend(&masked, pixel_darker, true, true).unwrap();
+        assert_eq!(naive.as_rgba8().unwrap().get_pixel(3, 0).0, [128, 128, 128, 0]);
+
+        let options = BlendOptions {
+            apply_to_alpha: true,
+            skip_transparent: true,
+            ..BlendOptions::new(pixel_darker)
+        };
+        let mut stacked = base.clone();
+        stacked.blend_with_options(&masked, &options).unwrap();
+        assert_eq!(stacked.as_rgba8().unwrap().get_pixel(0, 0).0, [128, 90, 128, 255]);
+        assert_eq!(stacked.as_rgba8().unwrap().get_pixel(3, 0).0, [128, 128, 128, 255]);
+        stacked.blend_with_options(&dark, &options).unwrap();
+        assert_eq!(stacked.as_rgba8().unwrap().get_pixel(0, 0).0, [100, 90, 100, 255]);
+        assert_eq!(stacked.as_rgba8().unwrap().get_pixel(3, 0).0, [100, 100, 100, 255]);
+    }
+    #[test]
+    fn test_translucent_composite() {
+        // Reference W3C source-over of a blended source onto a backdrop, on straight (color, alpha) pairs
+        let src_over = |(cb, ab): (f64, f64), (cs, a_s): (f64, f64), op: fn(f64, f64) -> f64| {
+            let alpha = a_s + ab * (1. - a_s);
+            let source = (1. - ab) * cs + ab * op(cb, cs);
+            ((a_s * source + ab * cb * (1. - a_s)) / alpha, alpha)
+        };
+        let layer = |c: f32, a: f32| DynamicImage::ImageRgba32F(image::Rgba32FImage::from_pixel(2, 2, image::Rgba([c, c, c, a])));
+        for op in [pixel_normal, pixel_mult, pixel_screen] {
+            let mut stack = layer(0., 0.);
+            let mut expected = (0., 0.);
+            for (c, a) in [(0.2, 0.5), (0.8, 0.5), (0.4, 0.25)] {
+                stack.blend(&layer(c, a), op, true, false).unwrap();
+                stack.blend(&layer(c, a), pixel_screen, false, true).unwrap();
+                expected = src_over(expected, (f64::from(c), f64::from(a)), op);
+            }
+            let px = stack.as_rgba32f().unwrap().get_pixel(0, 0).0;
+            assert!((f64::from(px[0]) - expected.0).abs() < 1e-6, "{px:?} {expected:?}");
+            assert!((f64::from(px[3]) - expected.1).abs() < 1e-6, "{px:?} {expected:?}");
+        }
+
+        // Opaque destinations keep the simple weighting
+        let mut opaque = layer(0.2, 1.);
+        opaque.blend(&layer(0.8, 0.5), pixel_normal, true, false).unwrap();
+        assert!((opaque.as_rgba32f().unwrap().get_pixel(0, 0).0[0] - 0.5).abs() < 1e-6);
+    }
+    #[test]
+    fn test_dissolve() {
+        let base = DynamicImage::ImageRgba8(RgbaImage::from_pixel(100, 100, image::Rgba([0, 0, 0, 255])));
+        let top = DynamicImage::ImageRgba8(RgbaImage::from_pixel(100, 100, image::Rgba([255, 255, 255, 77])));
+        let mut first = base.clone();
+        first.dissolve(&top, 7).unwrap();
+        let mut second = base.clone();
+        second.blend_mode(&top, BlendMode::Dissolve(7), true, false).unwrap();
+        assert_eq!(first, second);
+        let mut reseeded = base.clone();
+        reseeded.dissolve(&top, 8).unwrap();
+        assert_ne!(first, reseeded);
+
+        let taken = first.as_rgba8().unwrap().pixels().filter(|px| px.0 == [255, 255, 255, 255]).count();
+        let kept = first.as_rgba8().unwrap().pixels().filter(|px| px.0 == [0, 0, 0, 255]).count();
+        assert_eq!(taken + kept, 10_000);
+        // 77 / 255 is about 30%
+        assert!((2_700..3_300).contains(&taken), "{taken}");
+        assert_eq!(taken, 2_991);
+    }
+    #[test]
+    fn test_blend_weighted() {
+        let gray = |v: f32| DynamicImage::ImageRgb32F(image::Rgb32FImage::from_pixel(3, 3, image::Rgb([v, v, v])));
+        let mut fused = gray(0.9);
+        let others = [(gray(0.2), 0.5), (gray(0.6), 0.3), (gray(1.0), 0.2)];
+        fused.blend_weighted(&others, pixel_normal).unwrap();
+        let expected = 0.2 * 0.5 + 0.6 * 0.3 + 1.0 * 0.2;
+        assert!(fused.as_rgb32f().unwrap().pixels().all(|px| (f64::from(px.0[0]) - expected).abs() < 1e-6));
+
+        // Weights are normalized, and op sees self's original value for every image
+        let mut scaled = gray(0.5);
+        scaled.blend_weighted(&[(gray(0.2), 5.), (gray(0.6), 3.), (gray(1.0), 2.)], pixel_mult).unwrap();
+        let expected = 0.5 * (0.2 * 0.5 + 0.6 * 0.3 + 1.0 * 0.2);
+        assert!(scaled.as_rgb32f().unwrap().pixels().all(|px| (f64::from(px.0[0]) - expected).abs() < 1e-6));
+
+        let mut luma = DynamicImage::ImageLuma8(image::GrayImage::new(3, 3));
+        assert!(matches!(luma.blend_weighted(&[], pixel_normal), Err(Error::NoImages)));
+        assert!(matches!(luma.blend_weighted(&[(gray(0.), 1.)], pixel_normal), Err(Error::UnsupportedBlend(_, _))));
+        assert!(matches!(scaled.blend_weighted(&[(gray(0.), -1.)], pixel_normal), Err(Error::InvalidArgument(_))));
+        assert!(matches!(
+            scaled.blend_weighted(&[(DynamicImage::ImageRgb8(image::RgbImage::new(2, 2)), 1.)], pixel_normal),
+            Err(Error::DimensionMismatch)
+        ));
+    }
+    #[test]
+    fn test_op_range() {
+        assert_eq!(op_range(pixel_add, 64), (0.0, 2.0));
+        assert_eq!(op_range(pixel_mult, 64), (0.0, 1.0));
+        assert_eq!(op_range(pixel_sub, 2), (-1.0, 1.0));
+        assert_eq!(op_range(|a, _| a, 0), (0.0, 1.0));
+    }
+    #[test]
+    fn test_blend_as() {
+        let a = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(2, 2, image::Rgb([200, 100, 50])));
+        let b = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(2, 2, image::Rgb([128, 255, 0])));
+        let blended = a.blend_as(&b, image::ColorType::Rgb16, pixel_mult, true, false).unwrap();
+        let mut in_place = a.clone();
+        in_place.blend(&b, pixel_mult, true, false).unwrap();
+        let [r, g, bl] = in_place.as_rgb8().unwrap().get_pixel(0, 0).0;
+        assert_eq!(blended.as_rgb16().unwrap().get_pixel(1, 1).0, [r, g, bl].map(|v| u16::from(v) * 257));
+        assert_eq!(blended.as_rgb16().unwrap().get_pixel(1, 1).0, [100 * 257, 100 * 257, 0]);
+        assert_eq!(a.as_rgb8().unwrap().get_pixel(0, 0).0, [200, 100, 50]);
+    }
+    #[test]
+    fn test_flatten_onto_color() {
+        let white = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, image::Rgba([255, 255, 255, 128])));
+        let flattened = white.flatten_onto_color([0., 0., 0.]);
+        assert_eq!(flattened.color(), image::ColorType::Rgb8);
+        assert_eq!(flattened.as_rgb8().unwrap().get_pixel(0, 0).0, [128, 128, 128]);
+
+        let half = DynamicImage::ImageRgba32F(image::Rgba32FImage::from_pixel(2, 2, image::Rgba([1., 1., 1., 0.5])));
+        let flattened = half.flatten_onto_color([0., 0., 0.]);
+        assert_eq!(flattened.as_rgb32f().unwrap().get_pixel(0, 0).0, [0.5, 0.5, 0.5]);
+        let tinted = half.flatten_onto_color([1., 0., 0.]);
+        assert_eq!(tinted.as_rgb32f().unwrap().get_pixel(0, 0).0, [1., 0.5, 0.5]);
+
+        let luma16 = DynamicImage::ImageLumaA16(image::ImageBuffer::from_pixel(1, 1, image::LumaA([0u16, 0])));
+        assert_eq!(luma16.flatten_onto_color([1., 1., 1.]).as_rgb16().unwrap().get_pixel(0, 0).0, [65535; 3]);
+    }
+    #[test]
+    fn test_blend_generic() {
+        let mut base = RgbaImage::from_pixel(8, 8, image::Rgba([200, 100, 50, 255]));
+        let other = image::RgbImage::from_pixel(4, 4, image::Rgb([128, 128, 128]));
+        let mut corner = image::imageops::crop(&mut base, 4, 4, 4, 4);
+        blend_generic(&mut *corner, &other, pixel_mult, true, false).unwrap();
+
+        let mut expected = RgbaImage::from_pixel(4, 4, image::Rgba([200, 100, 50, 255]));
+        expected.blend(&other, pixel_mult, true, false).unwrap();
+        for (x, y, px) in base.enumerate_pixels() {
+            if x >= 4 && y >= 4 {
+                assert_eq!(px, expected.get_pixel(x - 4, y - 4));
+            } else {
+                assert_eq!(px.0, [200, 100, 50, 255]);
+            }
+        }
+        let mut corner = image::imageops::crop(&mut base, 4, 4, 4, 4);
+        assert!(matches!(
+            blend_generic(&mut *corner, &image::RgbImage::new(2, 2), pixel_mult, true, false),
+            Err(Error::DimensionMismatch)
+        ));
+    }
+    #[test]
+    fn test_threshold() {
+        let mut gradient = DynamicImage::ImageRgba8(RgbaImage::from_fn(256, 1, |x, _| {
+            let v = u8::try_from(x).unwrap();
+            image::Rgba([v, 255 - v, v / 2, v])
+        }));
+        gradient.threshold(0.5, true, false).unwrap();
+        let buffer = gradient.as_rgba8().unwrap();
+        for ch in 0..3 {
+            let mut values: Vec<u8> = buffer.pixels().map(|px| px.0[ch]).collect();
+            values.sort_unstable();
+            values.dedup();
+            assert!(values.iter().all(|v| [0, 255].contains(v)), "{values:?}");
+        }
+        // 128 / 255 is the first value above the cutoff
+        assert_eq!(buffer.get_pixel(127, 0).0, [0, 255, 0, 127]);
+        assert_eq!(buffer.get_pixel(128, 0).0, [255, 0, 0, 128]);
+        assert_eq!(buffer.get_pixel(255, 0).0, [255, 0, 0, 255]);
+    }
+    #[test]
+    fn test_blend_tile() {
+        let base = DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 64, |x, y| image::Rgb([u8::try_from(x).unwrap(), u8::try_from(y).unwrap(), 200])));
+        let other = DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 64, |x, y| image::Rgb([u8::try_from(x + y).unwrap(), 128, 64])));
+        let tile = Rect { x: 32, y: 32, width: 32, height: 32 };
+        let mut tiled = base.clone();
+        tiled.blend_tile(&other, pixel_mult, tile, true, false).unwrap();
+        let mut full = base.clone();
+        full.blend(&other, pixel_mult, true, false).unwrap();
+        for (x, y, px) in tiled.as_rgb8().unwrap().enumerate_pixels() {
+            if x >= 32 && y >= 32 {
+                assert_eq!(px, full.as_rgb8().unwrap().get_pixel(x, y));
+            } else {
+                assert_eq!(px, base.as_rgb8().unwrap().get_pixel(x, y));
+            }
+        }
+        let outside = Rect { x: 40, y: 0, width: 32, height: 32 };
+        assert!(matches!(tiled.blend_tile(&other, pixel_mult, outside, true, false), Err(Error::InvalidArgument(_))));
+    }
+    #[test]
+    fn test_match_types() {
+        let mut rgb = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(2, 2, image::Rgb([255, 128, 0])));
+        let rgba16 = DynamicImage::ImageRgba16(image::ImageBuffer::from_pixel(2, 2, image::Rgba([0u16, 0, 0, 1000])));
+        match_types(&mut rgb, &rgba16).unwrap();
+        assert_eq!(rgb.color(), image::ColorType::Rgba16);
+        assert_eq!(rgb.as_rgba16().unwrap().get_pixel(0, 0).0, [65535, 128 * 257, 0, 65535]);
+
+        rgb.transplant_alpha(&rgba16).unwrap();
+        assert_eq!(rgb.as_rgba16().unwrap().get_pixel(1, 1).0[3], 1000);
+    }
+    #[test]
+    fn test_pixel_div_policy() {
+        assert_eq!(pixel_div_policy(DivideZero::White)(0.25, 0.), 1.);
+        assert_eq!(pixel_div_policy(DivideZero::Black)(0.25, 0.), 0.);
+        assert_eq!(pixel_div_policy(DivideZero::PassThrough)(0.25, 0.), 0.25);
+        for policy in [DivideZero::White, DivideZero::Black, DivideZero::PassThrough] {
+            assert_eq!(pixel_div_policy(policy)(0.25, 0.5), pixel_div(0.25, 0.5));
+        }
+
+        let mut img = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([100])));
+        let zero = DynamicImage::ImageLuma8(image::GrayImage::new(2, 2));
+        img.blend(&zero, pixel_div_policy(DivideZero::PassThrough), true, false).unwrap();
+        assert_eq!(img.as_luma8().unwrap().get_pixel(0, 0).0, [100]);
+        img.blend_mode(&zero, BlendMode::Custom(pixel_div_policy(DivideZero::Black)), true, false).unwrap();
+        assert_eq!(img.as_luma8().unwrap().get_pixel(0, 0).0, [0]);
+    }
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_blend_log() {
+        use std::sync::Mutex;
+        static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        struct Capture;
+        impl log::Log for Capture {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+            fn log(&self, record: &log::Record) {
+                RECORDS.lock().unwrap().push(record.args().to_string());
+            }
+            fn flush(&self) {}
+        }
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        let mut img = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(4, 4, image::Rgb([200, 100, 0])));
+        let other = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(4, 4, image::Rgb([200, 100, 0])));
+        img.blend(&other, pixel_add, true, false).unwrap();
+        // Red overflows, blue stays at 0 and green lands in range
+        assert!(RECORDS.lock().unwrap().contains(&"blend: 16 of 48 subpixels hit 0 and 16 hit 1".to_string()));
+    }
+    #[test]
+    fn test_blend_selection() {
+        let base = DynamicImage::ImageRgba16(image::ImageBuffer::from_pixel(32, 32, image::Rgba([40000u16, 20000, 10000, 65535])));
+        let other = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(32, 32, image::Rgb([128, 128, 128])));
+        let circle = DynamicImage::ImageLuma8(image::GrayImage::from_fn(32, 32, |x, y| {
+            let (dx, dy) = (f64::from(x) - 15.5, f64::from(y) - 15.5);
+            if dx.hypot(dy) < 14. { image::Luma([255]) } else { image::Luma([0]) }
+        }));
+        let mut selected = base.clone();
+        selected.blend_selection(&other, pixel_mult, &circle, true, false).unwrap();
+        let mut full = base.clone();
+        full.blend(&other, pixel_mult, true, false).unwrap();
+        let (selected, full, base) = (selected.as_rgba16().unwrap(), full.as_rgba16().unwrap(), base.as_rgba16().unwrap());
+        for (x, y) in [(0, 0), (31, 0), (0, 31), (31, 31), (2, 3)] {
+            assert_eq!(selected.get_pixel(x, y), base.get_pixel(x, y));
+        }
+        for (x, y) in [(16, 16), (5, 16), (16, 28)] {
+            assert_eq!(selected.get_pixel(x, y), full.get_pixel(x, y));
+        }
+        // `op` only runs inside the selection
+        let calls = std::cell::Cell::new(0);
+        let mut counted = base.clone();
+        let counting_mult = |a, b| {
+            calls.set(calls.get() + 1);
+            pixel_mult(a, b)
+        };
+        counted.blend_selection(other.as_rgb8().unwrap(), counting_mult, circle.as_luma8().unwrap(), true, false).unwrap();
+        assert_eq!(&counted, selected);
+        assert_eq!(calls.get(), 3 * circle.as_luma8().unwrap().pixels().filter(|px| px.0[0] == 255).count());
+
+        let mut base = DynamicImage::ImageRgba16(base.clone());
+        let small = DynamicImage::ImageLuma8(image::GrayImage::new(8, 8));
+        assert!(matches!(base.blend_selection(&other, pixel_mult, &small, true, false), Err(Error::DimensionMismatch)));
+    }
+    #[test]
+    fn test_blend_options_presets() {
+        let img1 = open("test_data/1.png").unwrap();
+        let img2 = open("test_data/2.png").unwrap();
+        for (options, op) in [
+            (BlendOptions::multiply(), pixel_mult as fn(f64, f64) -> f64),
+            (BlendOptions::screen(), pixel_screen),
+            (BlendOptions::default(), pixel_normal),
+        ] {
+            let mut with_options = img1.clone();
+            with_options.blend_with_options(&img2, &options).unwrap();
+            let mut positional = img1.clone();
+            positional.blend(&img2, op, true, false).unwrap();
+            assert_eq!(with_options, positional);
+        }
+        let default = BlendOptions::default();
+        assert!(default.apply_to_color && !default.apply_to_alpha);
+        assert_eq!(default.opacity, 1.);
+
+        let mut half = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([0])));
+        let white = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([255])));
+        half.blend_with_options(&white, &BlendOptions { opacity: 0.5, ..BlendOptions::normal() }).unwrap();
+        assert_eq!(half.as_luma8().unwrap().get_pixel(0, 0).0, [128]);
+    }
+    #[test]
+    fn test_blend_trimmed() {
+        let base = DynamicImage::ImageRgba8(RgbaImage::from_pixel(64, 64, image::Rgba([10, 20, 30, 255])));
+        // A 3x3 dot in the middle of a transparent 32x32 sprite
+        let sprite = DynamicImage::ImageRgba8(RgbaImage::from_fn(32, 32, |x, y| {
+            if (15..18).contains(&x) && (15..18).contains(&y) { image::Rgba([250, 250, 250, 255]) } else { image::Rgba([0, 0, 0, 0]) }
+        }));
+        let mut stamped = base.clone();
+        stamped.blend_trimmed(&sprite, pixel_normal, 20, -5, true, false).unwrap();
+        for (x, y, px) in stamped.as_rgba8().unwrap().enumerate_pixels() {
+            if (35..38).contains(&x) && (10..13).contains(&y) {
+                assert_eq!(px.0, [250, 250, 250, 255], "({x}, {y})");
+            } else {
+                assert_eq!(px.0, [10, 20, 30, 255], "({x}, {y})");
+            }
+        }
+        // Off the edge, or fully transparent, nothing changes
+        let mut untouched = base.clone();
+        untouched.blend_trimmed(&sprite, pixel_normal, -18, 0, true, false).unwrap();
+        let empty = DynamicImage::ImageRgba8(RgbaImage::new(8, 8));
+        untouched.blend_trimmed(&empty, pixel_normal, 0, 0, true, true).unwrap();
+        assert_eq!(untouched, base);
+    }
+    #[test]
+    fn test_clip_color() {
+        let rgb = [1.2, 0.5, -0.1];
+        let clipped = clip_color(rgb);
+        assert!(clipped.iter().all(|v| (0.0..=1.0).contains(v)));
+        let luma = LumaCoefficients::default();
+        assert!((luma.luminance(clipped) - luma.luminance(rgb)).abs() < 1e-9);
+        assert!(clipped[0] > clipped[1] && clipped[1] > clipped[2]);
+        assert_eq!(clip_color([0.2, 0.4, 0.6]), [0.2, 0.4, 0.6]);
+    }
+    #[test]
+    fn test_blend_options_clone_debug() {
+        let options = BlendOptions { opacity: 0.5, ..BlendOptions::multiply() };
+        #[allow(clippy::clone_on_copy)]
+        let cloned = options.clone();
+        assert_eq!(cloned, options);
+        assert_ne!(cloned, BlendOptions::multiply());
+        let debug = format!("{options:?}");
+        assert!(debug.starts_with("BlendOptions {"));
+        assert!(debug.contains("opacity: 0.5"));
+        assert_eq!(BlendMode::Dissolve(3), BlendMode::Dissolve(3));
+        assert_ne!(BlendMode::Multiply, BlendMode::Screen);
+    }
+    #[test]
+    fn test_blend_threshold_op() {
+        // Alpha ramps from 0 to 255 across the image
+        let other = RgbaImage::from_fn(256, 1, |x, _| image::Rgba([128, 128, 128, u8::try_from(x).unwrap()]));
+        let base = RgbaImage::from_pixel(256, 1, image::Rgba([200, 100, 50, 255]));
+        let mut img = base.clone();
+        img.blend_threshold_op(&other, pixel_mult, pixel_screen, 0.5, true, false).unwrap();
+        let mut multiplied = base.clone();
+        multiplied.blend(&other, pixel_mult, true, false).unwrap();
+        let mut screened = base.clone();
+        screened.blend(&other, pixel_screen, true, false).unwrap();
+        for x in 0..256 {
+            let expected = if x >= 128 { multiplied.get_pixel(x, 0) } else { screened.get_pixel(x, 0) };
+            assert_eq!(img.get_pixel(x, 0), expected, "x = {x}");
+        }
+        assert_ne!(multiplied.get_pixel(200, 0), screened.get_pixel(200, 0));
+        // Each pixel runs only the op it uses. The fully transparent pixel runs neither
+        let (opaque_calls, transparent_calls) = (std::cell::Cell::new(0), std::cell::Cell::new(0));
+        let mut counted = base.clone();
+        counted.blend_threshold_op(&other, |a, b| {
+            opaque_calls.set(opaque_calls.get() + 1);
+            pixel_mult(a, b)
+        }, |a, b| {
+            transparent_calls.set(transparent_calls.get() + 1);
+            pixel_screen(a, b)
+        }, 0.5, true, false).unwrap();
+        assert_eq!(counted, img);
+        assert_eq!((opaque_calls.get(), transparent_calls.get()), (128 * 3, 127 * 3));
+
+        let mut dynamic = DynamicImage::ImageRgba8(base);
+        dynamic.blend_threshold_op(&DynamicImage::ImageRgba8(other), pixel_mult, pixel_screen, 0.5, true, false).unwrap();
+        assert_eq!(dynamic.as_rgba8().unwrap(), &img);
+    }
+    #[test]
+    fn test_blend_f64_subpixels() {
+        let mut img: ImageBuffer<Rgba<f64>, Vec<f64>> = ImageBuffer::from_pixel(2, 2, Rgba([0.25, 0.5, 0.75, 1.0]));
+        let other: ImageBuffer<Rgba<f64>, Vec<f64>> = ImageBuffer::from_pixel(2, 2, Rgba([0.25, 0.25, 0.5, 1.0]));
+        img.blend(&other, pixel_add, true, false).unwrap();
+        assert_eq!(img.get_pixel(1, 1).0, [0.5, 0.75, 1.0, 1.0]);
+    }
+    #[test]
+    fn test_blend_with_progress() {
+        let base = RgbaImage::from_pixel(3, 5, image::Rgba([200, 100, 50, 255]));
+        let other = RgbaImage::from_pixel(3, 5, image::Rgba([128, 128, 128, 200]));
+        let mut expected = base.clone();
+        expected.blend(&other, pixel_mult, true, true).unwrap();
+
+        let mut reported = vec![];
+        let mut img = base.clone();
+        img.blend_with_progress(&other, pixel_mult, |done| reported.push(done), true, true).unwrap();
+        assert_eq!(img, expected);
+        assert_eq!(reported.len(), 5);
+        assert!(reported.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(reported.last(), Some(&1.0));
+
+        let mut calls = 0;
+        let mut last = 0.;
+        let mut dynamic = DynamicImage::ImageRgba8(base);
+        dynamic.blend_with_progress(&DynamicImage::ImageRgba8(other), pixel_mult, |done| { calls += 1; last = done; }, true, true).unwrap();
+        assert_eq!(dynamic.as_rgba8().unwrap(), &expected);
+        assert_eq!((calls, last), (5, 1.0));
+    }
+    #[test]
+    fn test_reverse_operands() {
+        let mut img = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([50])));
+        let other = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([200])));
+        let options = BlendOptions { reverse_operands: true, ..BlendOptions::subtract() };
+        img.blend_with_options(&other, &options).unwrap();
+        assert_eq!(img.as_luma8().unwrap().get_pixel(0, 0).0, [150]);
+
+        let mut forward = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([50])));
+        forward.blend_with_options(&other, &BlendOptions::subtract()).unwrap();
+        assert_eq!(forward.as_luma8().unwrap().get_pixel(0, 0).0, [0]);
+    }
+    #[test]
+    fn test_generators() {
+        let checker = checkerboard(10, 7, 3, image::ColorType::L8, &[0.], &[1.]).unwrap();
+        let checker = checker.as_luma8().unwrap();
+        for (x, y, px) in checker.enumerate_pixels() {
+            let expected = if (x / 3 + y / 3).is_multiple_of(2) { 0 } else { 255 };
+            assert_eq!(px.0, [expected], "({x}, {y})");
+            // Periodic with twice the cell size in both directions
+            if x + 6 < 10 {
+                assert_eq!(checker.get_pixel(x + 6, y), px);
+            }
+            if y + 6 < 7 {
+                assert_eq!(checker.get_pixel(x, y + 6), px);
+            }
+        }
+        assert!(matches!(checkerboard(4, 4, 0, image::ColorType::L8, &[0.], &[1.]), Err(Error::InvalidArgument(_))));
+
+        let ramp = gradient(5, 9, Direction::Vertical, image::ColorType::Rgba16, &[0., 1., 0., 1.], &[1., 0., 0., 0.]).unwrap();
+        let ramp = ramp.as_rgba16().unwrap();
+        assert_eq!(ramp.get_pixel(0, 0).0, [0, 65535, 0, 65535]);
+        assert_eq!(ramp.get_pixel(0, 8).0, [65535, 0, 0, 0]);
+        for y in 1..9 {
+            assert!(ramp.get_pixel(0, y).0[0] > ramp.get_pixel(0, y - 1).0[0]);
+            assert!(ramp.get_pixel(0, y).0[1] < ramp.get_pixel(0, y - 1).0[1]);
+            assert!((1..5).all(|x| ramp.get_pixel(x, y) == ramp.get_pixel(0, y)));
+        }
+        assert!(matches!(gradient(4, 4, Direction::Horizontal, image::ColorType::Rgb8, &[0.], &[1.]), Err(Error::InvalidArgument(_))));
+    }
+    #[test]
+    fn test_blend_alpha_only() {
+        let base = RgbaImage::from_fn(4, 4, |x, y| image::Rgba([u8::try_from(x * 60).unwrap(), u8::try_from(y * 60).unwrap(), 7, 200]));
+        let mask = image::GrayAlphaImage::from_fn(4, 4, |x, _| image::LumaA([0, u8::try_from(x * 85).unwrap()]));
+        let mut img = base.clone();
+        img.blend_alpha_only(&mask, pixel_mult).unwrap();
+        for (x, y, px) in img.enumerate_pixels() {
+            let original = base.get_pixel(x, y);
+            assert_eq!(px.0[..3], original.0[..3]);
+            let expected = (200. * f64::from(mask.get_pixel(x, y).0[1]) / 255.).round();
+            assert_eq!(f64::from(px.0[3]), expected);
+        }
+
+        // The color layouts do not need to be compatible
+        let mut luma = DynamicImage::ImageLumaA8(mask.clone());
+        luma.blend_alpha_only(&DynamicImage::ImageRgba8(base), pixel_mult).unwrap();
+        assert_eq!(luma.as_luma_alpha8().unwrap().get_pixel(3, 0).0, [0, 200]);
+        let mut no_alpha = DynamicImage::ImageRgb8(image::RgbImage::new(4, 4));
+        assert!(matches!(no_alpha.blend_alpha_only(&DynamicImage::ImageLumaA8(mask), pixel_mult), Err(Error::NoAlphaChannel)));
+    }
+    #[test]
+    fn test_clamp_inputs() {
+        let negative = DynamicImage::ImageRgb32F(image::Rgb32FImage::from_pixel(2, 2, image::Rgb([-0.5, 0.2, 0.9])));
+        let other = DynamicImage::ImageRgb32F(image::Rgb32FImage::from_pixel(2, 2, image::Rgb([0.75, 0.75, 0.75])));
+        let mut img = negative.clone();
+        img.blend(&other, pixel_soft_light, true, false).unwrap();
+        let px = img.as_rgb32f().unwrap().get_pixel(0, 0).0;
+        assert!(px.iter().all(|v| !v.is_nan()));
+        assert_eq!(px[0], 0.);
+
+        let sqrt: fn(f64, f64) -> f64 = |a, _| a.sqrt();
+        let mut clamped = negative.clone();
+        clamped.blend_with_options(&other, &BlendOptions::new(sqrt)).unwrap();
+        assert_eq!(clamped.as_rgb32f().unwrap().get_pixel(0, 0).0[0], 0.);
+        let mut hdr = negative;
+        hdr.blend_with_options(&other, &BlendOptions { clamp_inputs: false, ..BlendOptions::new(sqrt) }).unwrap();
+        assert!(hdr.as_rgb32f().unwrap().get_pixel(0, 0).0[0].is_nan());
+    }
+    #[test]
+    fn test_rgb_into_luma() {
+        let mut luma = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([255])));
+        let rgb = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(2, 2, image::Rgb([255, 0, 0])));
+        let options = BlendOptions { rgb_into_luma: Some(LumaCoefficients::REC_709), ..BlendOptions::multiply() };
+        assert!(matches!(luma.clone().blend_with_options(&rgb, &BlendOptions::multiply()), Err(Error::UnsupportedBlend(..))));
+        luma.blend_with_options(&rgb, &options).unwrap();
+        assert_eq!(luma.as_luma8().unwrap().get_pixel(0, 0).0, [54]);
+
+        // other's alpha still weights the blend
+        let mut luma_alpha = DynamicImage::ImageLumaA16(image::ImageBuffer::from_pixel(2, 2, image::LumaA([65535, 65535])));
+        let rgba = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 0, 128])));
+        luma_alpha.blend_with_options(&rgba, &options).unwrap();
+        assert_eq!(luma_alpha.as_luma_alpha16().unwrap().get_pixel(0, 0).0, [32639, 65535]);
+    }
+    #[test]
+    fn test_blend_saturating_int() {
+        let mut img = RgbaImage::from_pixel(2, 2, image::Rgba([200, 50, 255, 255]));
+        let other = RgbaImage::from_pixel(2, 2, image::Rgba([100, 100, 128, 0]));
+        let mut sum = img.clone();
+        sum.blend_saturating_int(&other, IntOp::Add, true, false).unwrap();
+        assert_eq!(sum.get_pixel(0, 0).0, [200u8.saturating_add(100), 150, 255, 255]);
+        let mut difference = img.clone();
+        difference.blend_saturating_int(&other, IntOp::Sub, true, true).unwrap();
+        assert_eq!(difference.get_pixel(0, 0).0, [100, 0, 127, 255]);
+        img.blend_saturating_int(&other, IntOp::Mult, true, false).unwrap();
+        assert_eq!(img.get_pixel(0, 0).0, [78, 20, 128, 255]);
+
+        let mut dynamic = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, image::Rgba([200, 50, 255, 255])));
+        dynamic.blend_saturating_int(&DynamicImage::ImageRgba8(other.clone()), IntOp::Add, true, false).unwrap();
+        assert_eq!(dynamic.as_rgba8().unwrap(), &sum);
+        let sixteen = DynamicImage::ImageRgba8(other).into_rgba16().into();
+        assert!(matches!(dynamic.blend_saturating_int(&sixteen, IntOp::Add, true, false), Err(Error::UnsupportedType)));
+        let mut float = dynamic.to_rgba32f().into();
+        assert!(matches!(DynamicChops::blend_saturating_int(&mut float, &dynamic, IntOp::Add, true, false), Err(Error::UnsupportedType)));
+    }
+    #[test]
+    fn test_fixed_point() {
+        // Every pair of 8 bit values, self along x and other along y
+        let base = GrayImage::from_fn(256, 256, |x, _| image::Luma([u8::try_from(x).unwrap()]));
+        let other = GrayImage::from_fn(256, 256, |_, y| image::Luma([u8::try_from(y).unwrap()]));
+        let options = BlendOptions {
+            arithmetic: Arithmetic::FixedPoint(IntOp::Mult),
+            ..BlendOptions::default()
+        };
+        let mut mult = base.clone();
+        mult.blend_with_options(&other, &options).unwrap();
+        for (x, y, px) in mult.enumerate_pixels() {
+            assert_eq!(u32::from(px.0[0]), (x * y + 127) / 255, "{x} * {y}");
+        }
+        let mut screen = base.clone();
+        screen.blend_with_options(&other, &BlendOptions { arithmetic: Arithmetic::FixedPoint(IntOp::Screen), ..options }).unwrap();
+        for (x, y, px) in screen.enumerate_pixels() {
+            assert_eq!(u32::from(px.0[0]), 255 - ((255 - x) * (255 - y) + 127) / 255, "screen {x} {y}");
+        }
+
+        // Translucent pixels are weighted by both alphas like a float blend of the exact result
+        let mut rgba = RgbaImage::from_pixel(2, 2, image::Rgba([200, 50, 255, 255]));
+        let half = RgbaImage::from_pixel(2, 2, image::Rgba([100, 100, 0, 128]));
+        rgba.blend_with_options(&half, &options).unwrap();
+        assert_eq!(rgba.get_pixel(0, 0).0, [139, 35, 127, 255]);
+        let mut translucent = RgbaImage::from_pixel(2, 2, image::Rgba([200, 50, 255, 64]));
+        translucent.blend_with_options(&half, &options).unwrap();
+        let mut expected = RgbaImage::from_pixel(2, 2, image::Rgba([200, 50, 255, 64]));
+        let int_mult = |a: f64, b: f64| (((a * 255.).round() * (b * 255.).round() + 127.) / 255.).floor() / 255.;
+        expected.blend(&half, int_mult, true, false).unwrap();
+        assert_eq!(translucent, expected);
+
+        let mut float = DynamicImage::ImageRgba32F(Rgba32FImage::new(2, 2));
+        assert!(matches!(float.blend_with_options(&DynamicImage::ImageRgba32F(Rgba32FImage::new(2, 2)), &options), Err(Error::UnsupportedType)));
+        // Options that can't be honoured in fixed point are refused rather than ignored
+        let mut dynamic = DynamicImage::ImageRgba8(half.clone());
+        let half = DynamicImage::ImageRgba8(half);
+        for unsupported in [
+            BlendOptions { min_delta: 0.1, ..options },
+            BlendOptions { skip_transparent: true, ..options },
+            BlendOptions { opacity: 0.5, ..options },
+            BlendOptions { alpha_opacity: 0.5, ..options },
+            BlendOptions { linear_alpha_weighting: true, ..options },
+        ] {
+            assert!(matches!(dynamic.blend_with_options(&half, &unsupported), Err(Error::InvalidArgument(..))));
+        }
+        assert_eq!(dynamic, half);
+    }
+    #[test]
+    fn test_linear_alpha_weighting() {
+        // Midgray at 50% alpha over black and over white
+        let overlay = RgbaImage::from_pixel(2, 1, Rgba([128, 128, 128, 128]));
+        let base = image::RgbImage::from_fn(2, 1, |x, _| if x == 0 { image::Rgb([0, 0, 0]) } else { image::Rgb([255, 255, 255]) });
+        let linear = BlendOptions {
+            linear_alpha_weighting: true,
+            ..BlendOptions::normal()
+        };
+
+        let mut gamma_weighted = base.clone();
+        gamma_weighted.blend_with_options(&overlay, &BlendOptions::normal()).unwrap();
+        let mut linear_weighted = base.clone();
+        linear_weighted.blend_with_options(&overlay, &linear).unwrap();
+        // Mixing encoded values darkens the partial coverage: the linear mix is perceptually half way between the two
+        assert_eq!(gamma_weighted.get_pixel(0, 0).0, [64; 3]);
+        assert_eq!(linear_weighted.get_pixel(0, 0).0, [93; 3]);
+        assert_eq!(gamma_weighted.get_pixel(1, 0).0, [191; 3]);
+        assert_eq!(linear_weighted.get_pixel(1, 0).0, [204; 3]);
+
+        // op itself still works on the encoded values, so opaque pixels are unaffected
+        let opaque = RgbaImage::from_pixel(2, 1, Rgba([128, 128, 128, 255]));
+        let mut multiplied = base.clone();
+        multiplied.blend_with_options(&opaque, &BlendOptions { op: pixel_mult, ..linear }).unwrap();
+        assert_eq!(multiplied.get_pixel(1, 0).0, [128; 3]);
+    }
+    #[test]
+    fn test_premultiplied_wrapper() {
+        let bottom = Straight::new(DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]))));
+        let top = Straight::new(DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([200, 100, 50, 128]))));
+
+        // Blending a premultiplied image with straight alpha math takes an explicit unpremultiply (see the compile_fail doctest on `Straight`)
+        let premultiplied = top.clone().premultiply();
+        assert_eq!(premultiplied.image().as_rgba8().unwrap().get_pixel(0, 0).0, [100, 50, 25, 128]);
+        let mut straight = bottom.clone();
+        straight.blend(&premultiplied.clone().unpremultiply(), pixel_normal, true, false).unwrap();
+        assert_eq!(straight.image().as_rgba8().unwrap().get_pixel(0, 0).0, [227, 50, 25, 255]);
+
+        // Source-over in premultiplied space matches the straight alpha normal blend
+        let mut composited = bottom.premultiply();
+        composited.composite_over(&premultiplied).unwrap();
+        assert_eq!(composited.image().color(), image::ColorType::Rgba8);
+        assert_eq!(composited.unpremultiply(), straight);
+
+        let mut luma = Premultiplied::new(DynamicImage::ImageLuma8(GrayImage::new(2, 2)));
+        assert!(matches!(luma.composite_over(&premultiplied), Err(Error::UnsupportedBlend(..))));
+        assert!(matches!(luma.composite_over(&Premultiplied::new(DynamicImage::ImageLuma8(GrayImage::new(3, 2)))), Err(Error::DimensionMismatch)));
+    }
+    #[test]
+    fn test_blend_color_keyed() {
+        let base = RgbaImage::from_pixel(2, 2, Rgba([10, 20, 30, 255]));
+        let mut sprite = image::RgbImage::from_pixel(2, 2, image::Rgb([255, 0, 255]));
+        sprite.put_pixel(0, 0, image::Rgb([255, 255, 255]));
+        // One level off the key is not keyed
+        sprite.put_pixel(1, 0, image::Rgb([254, 0, 255]));
+
+        let mut img = base.clone();
+        img.blend_color_keyed(&sprite, [1., 0., 1.], pixel_normal, true, true).unwrap();
+        assert_eq!(img.get_pixel(0, 0).0, [255, 255, 255, 255]);
+        assert_eq!(img.get_pixel(1, 0).0, [254, 0, 255, 255]);
+        assert_eq!(img.get_pixel(0, 1), base.get_pixel(0, 1));
+        assert_eq!(img.get_pixel(1, 1), base.get_pixel(1, 1));
+
+        // The key still matches once the sprite is converted to 16 bit
+        let mut dynamic = DynamicImage::ImageRgba8(base.clone());
+        let sprite16 = DynamicImage::ImageRgb8(sprite).into_rgb16().into();
+        dynamic.blend_color_keyed(&sprite16, [1., 0., 1.], pixel_normal, true, false).unwrap();
+        assert_eq!(dynamic.as_rgba8().unwrap(), &img);
+    }
+    #[cfg(feature = "registry")]
+    #[test]
+    fn test_blend_named() {
+        use crate::{blend_named, named_blend_mode, register_blend_mode};
+        register_blend_mode("invert_mult", |a, b| a * (1. - b));
+        assert_eq!(named_blend_mode("invert_mult").unwrap()(1., 0.25), 0.75);
+
+        let mut img = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([200])));
+        let other = DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, image::Luma([51])));
+        blend_named(&mut img, &other, "invert_mult", true, false).unwrap();
+        assert_eq!(img.as_luma8().unwrap().get_pixel(0, 0).0, [160]);
+        assert!(matches!(blend_named(&mut img, &other, "not registered", true, false), Err(Error::UnknownBlendMode(name)) if name == "not registered"));
+    }
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_blend_mapped_files() {
+        use crate::blend_mapped_files;
+        let img1 = open("test_data/1.png").unwrap().to_rgba8();
+        let img2 = open("test_data/2.png").unwrap().to_rgb8();
+        let (width, height) = img1.dimensions();
+        // Pad the rows of the destination file to check its stride is honoured
+        let stride = width as usize * 4 + 12;
+        let mut padded = vec![7u8; stride * height as usize];
+        for (row, src) in padded.chunks_mut(stride).zip(img1.as_raw().chunks(width as usize * 4)) {
+            row[..src.len()].copy_from_slice(src);
+        }
+        let (dst_path, src_path) = ("tests_out/mapped_dst.raw", "tests_out/mapped_src.raw");
+        std::fs::write(dst_path, &padded).unwrap();
+        std::fs::write(src_path, img2.as_raw()).unwrap();
+
+        let dst_layout = SliceLayout { width, height, channels: 4, row_stride: stride };
+        let src_layout = SliceLayout::packed(width, height, 3);
+        blend_mapped_files(dst_path, dst_layout, src_path, src_layout, pixel_screen, true, false).unwrap();
+
+        let mut expected = img1.clone();
+        expected.blend(&img2, pixel_screen, true, false).unwrap();
+        let on_disk = std::fs::read(dst_path).unwrap();
+        for (row, expected) in on_disk.chunks(stride).zip(expected.as_raw().chunks(width as usize * 4)) {
+            assert_eq!(&row[..expected.len()], expected);
+            assert!(row[expected.len()..].iter().all(|&v| v == 7));
+        }
+
+        // The same file can't be mapped as both, however it is named
+        let same = blend_mapped_files(dst_path, dst_layout, "tests_out/../tests_out/mapped_dst.raw", dst_layout, pixel_screen, true, false);
+        assert!(matches!(same, Err(Error::InvalidArgument(_))));
+
+        let short_layout = SliceLayout::packed(width, height + 1, 3);
+        assert!(matches!(blend_mapped_files(dst_path, dst_layout, src_path, short_layout, pixel_screen, true, false), Err(Error::DimensionMismatch)));
+        let tall_layout = SliceLayout { height: height + 1, ..dst_layout };
+        let tall_src = SliceLayout::packed(width, height + 1, 3);
+        assert!(matches!(blend_mapped_files(dst_path, tall_layout, src_path, tall_src, pixel_screen, true, false), Err(Error::InvalidArgument(_))));
+        assert!(matches!(blend_mapped_files("tests_out/does_not_exist.raw", dst_layout, src_path, src_layout, pixel_screen, true, false), Err(Error::Io(_))));
+    }
+    #[test]
+    fn test_blend_alpha_coverage() {
+        let base = RgbaImage::from_pixel(4, 4, image::Rgba([100, 100, 100, 255]));
+        let half = RgbaImage::from_pixel(4, 4, image::Rgba([200, 200, 200, 128]));
+        let mut img = base.clone();
+        let coverage = img.blend_alpha_coverage(&half, pixel_mult, true, true).unwrap();
+        assert_eq!(coverage, AlphaCoverage { transparent: 0, opaque: 0 });
+
+        // Holes in other knock out self's alpha where the alpha is multiplied
+        let holes = RgbaImage::from_fn(4, 4, |x, _| image::Rgba([200, 200, 200, if x == 0 { 0 } else { 255 }]));
+        let mut img = DynamicImage::ImageRgba8(base);
+        let coverage = img.blend_alpha_coverage(&DynamicImage::ImageRgba8(holes), pixel_mult, true, true).unwrap();
+        assert_eq!(coverage, AlphaCoverage { transparent: 4, opaque: 12 });
+
+        let mut rgb = DynamicImage::ImageRgb8(image::RgbImage::new(4, 4));
+        let coverage = rgb.blend_alpha_coverage(&DynamicImage::ImageRgba8(half), pixel_mult, true, true).unwrap();
+        assert_eq!(coverage, AlphaCoverage { transparent: 0, opaque: 16 });
+    }
+    #[test]
+    fn test_blend_chunk() {
+        let base = RgbaImage::from_fn(8, 4, |x, y| image::Rgba([u8::try_from(x * 30).unwrap(), u8::try_from(y * 60).unwrap(), 90, 200]));
+        let other = RgbaImage::from_fn(8, 4, |x, y| image::Rgba([u8::try_from(y * 50).unwrap(), 120, u8::try_from(x * 20).unwrap(), 150]));
+        let mut whole = base.clone();
+        whole.blend(&other, pixel_overlay, true, true).unwrap();
+
+        // Pixels 5..13, crossing a row boundary
+        let range = 5 * 4..13 * 4;
+        let mut chunk = base.as_raw()[range.clone()].to_vec();
+        blend_chunk(&mut chunk, &other.as_raw()[range.clone()], 4, pixel_overlay, true, true).unwrap();
+        assert_eq!(chunk, whole.as_raw()[range]);
+
+        assert!(matches!(blend_chunk(&mut chunk[..6], &other.as_raw()[..6], 4, pixel_overlay, true, true), Err(Error::InvalidArgument(_))));
+        assert!(matches!(blend_chunk(&mut chunk[..8], &other.as_raw()[..4], 4, pixel_overlay, true, true), Err(Error::DimensionMismatch)));
+    }
+    #[test]
+    fn test_verify_blend() {
+        let a: ImageBuffer<Rgba<u16>, Vec<u16>> = ImageBuffer::from_fn(3, 3, |x, y| Rgba([u16::try_from(x * 30000).unwrap(), u16::try_from(y * 25000).unwrap(), 12345, 65535 - u16::try_from(x * 20000).unwrap()]));
+        let b: ImageBuffer<Rgba<u16>, Vec<u16>> = ImageBuffer::from_fn(3, 3, |x, y| Rgba([u16::try_from(y * 32767).unwrap(), 40000, u16::try_from(x * 10000).unwrap(), u16::try_from(y * 30000).unwrap()]));
+        verify_blend(&a, &b, pixel_overlay, 1e-12);
+        verify_blend(&a, &b, |a, b| (a - b).abs().sqrt(), 1e-12);
+        let rgb: ImageBuffer<image::Rgb<f32>, Vec<f32>> = ImageBuffer::from_fn(3, 3, |x, y| image::Rgb([[0.1, 0.45, 0.8][x as usize], [0., 0.3, 0.6][y as usize], 1.5]));
+        verify_blend(&rgb, &rgb, pixel_soft_light, 1e-6);
+    }
+    #[test]
+    fn test_flatten_onto() {
+        let foreground = gradient(5, 1, Direction::Horizontal, image::ColorType::Rgba8, &[1., 1., 1., 0.], &[1., 1., 1., 1.]).unwrap();
+        let background = solid_color(5, 1, image::ColorType::Rgb8, &[0., 0., 0.]).unwrap();
+        let flattened = foreground.flatten_onto(&background).unwrap();
+        let flattened = flattened.as_rgb8().unwrap();
+        assert_eq!(flattened.get_pixel(0, 0).0, [0, 0, 0]);
+        assert_eq!(flattened.get_pixel(2, 0).0, [128, 128, 128]);
+        assert_eq!(flattened.get_pixel(4, 0).0, [255, 255, 255]);
+
+        let solid = foreground.flatten_onto_color([0., 0., 0.]);
+        assert_eq!(solid.as_rgb8().unwrap(), flattened);
+        let small = solid_color(4, 1, image::ColorType::Rgb8, &[0., 0., 0.]).unwrap();
+        assert!(matches!(foreground.flatten_onto(&small), Err(Error::DimensionMismatch)));
+    }
+    #[test]
+    fn test_blend_normal_maps() {
+        // Flat on the left half, tilted towards +x on the right half
+        let detail = DynamicImage::ImageRgb8(image::ImageBuffer::from_fn(8, 8, |x, _| if x < 4 { image::Rgb([128, 128, 255]) } else { image::Rgb([218, 128, 218]) }));
+        for method in [NormalBlend::Reoriented, NormalBlend::PartialDerivative] {
+            let base = solid_color(8, 8, image::ColorType::Rgb8, &[0.5, 0.5, 1.]).unwrap();
+            let mut combined = base.clone();
+            combined.blend_normal_maps(&detail, method).unwrap();
+            let (base, combined) = (base.as_rgb8().unwrap(), combined.as_rgb8().unwrap());
+            for (x, y, px) in combined.enumerate_pixels() {
+                if x < 4 {
+                    assert_eq!(px, base.get_pixel(x, y), "{method:?}");
+                } else {
+                    // On a flat base, the result is the detail
+                    let expected = detail.as_rgb8().unwrap().get_pixel(x, y);
+                    assert!(px.0.iter().zip(expected.0).all(|(a, b)| a.abs_diff(b) <= 1), "{method:?}: {px:?}");
+                }
+            }
+
+            // A tilted base is kept where the detail is flat, at any bit depth
+            let tilted = solid_color(8, 8, image::ColorType::Rgba16, &[0.2, 0.5, 0.9, 1.]).unwrap();
+            let mut combined = tilted.clone();
+            combined.blend_normal_maps(&detail, method).unwrap();
+            let (tilted, combined) = (tilted.as_rgba16().unwrap(), combined.as_rgba16().unwrap());
+            for (before, after) in tilted.pixels().zip(combined.pixels()).step_by(8) {
+                let decoded = after.0.map(|v| <f64 as From<u16>>::from(v) / 65535. * 2. - 1.);
+                let length = (decoded[0].powi(2) + decoded[1].powi(2) + decoded[2].powi(2)).sqrt();
+                assert!((length - 1.).abs() < 1e-3, "{method:?}: {length}");
+                assert_eq!(before.0[3], after.0[3]);
+            }
+            // 0.5 isn't exact in 8 bits, so the flat detail is flat to within one 8 bit step
+            assert!(tilted.get_pixel(0, 0).0.iter().zip(combined.get_pixel(0, 0).0).all(|(a, b)| a.abs_diff(b) <= 257), "{method:?}");
+            assert_ne!(tilted.get_pixel(7, 0), combined.get_pixel(7, 0));
+        }
+
+        let mut luma = DynamicImage::ImageLuma8(image::GrayImage::new(8, 8));
+        assert!(matches!(luma.blend_normal_maps(&detail, NormalBlend::Reoriented), Err(Error::MissingChannel(Channel::Red, _))));
+        let mut base = solid_color(8, 8, image::ColorType::Rgb8, &[0.5, 0.5, 1.]).unwrap();
+        assert!(matches!(base.blend_normal_maps(&luma, NormalBlend::Reoriented), Err(Error::MissingChannel(Channel::Red, _))));
+        let small = solid_color(4, 4, image::ColorType::Rgb8, &[0.5, 0.5, 1.]).unwrap();
+        assert!(matches!(base.blend_normal_maps(&small, NormalBlend::Reoriented), Err(Error::DimensionMismatch)));
+    }
+    #[test]
+    fn test_blend_opacity_map() {
+        // A 16 bit map ramping slowly enough that an 8 bit copy of it would skip values
+        let width = 4096;
+        let opacity_map = gradient(width, 1, Direction::Horizontal, image::ColorType::L16, &[0.], &[1.]).unwrap();
+        let mut black = solid_color(width, 1, image::ColorType::Rgb8, &[0., 0., 0.]).unwrap();
+        let white = solid_color(width, 1, image::ColorType::Rgb8, &[1., 1., 1.]).unwrap();
+        black.blend_opacity_map(&white, pixel_normal, &opacity_map, true, false).unwrap();
+
+        let map = opacity_map.as_luma16().unwrap();
+        let faded = black.as_rgb8().unwrap();
+        for (px, opacity) in faded.pixels().zip(map.pixels()) {
+            // Exactly the 16 bit opacity, rounded once to 8 bits
+            let expected = (<f64 as From<u16>>::from(opacity.0[0]) / 65535. * 255.).round();
+            assert_eq!(<f64 as From<u8>>::from(px.0[0]), expected);
+        }
+        let values: Vec<u8> = faded.pixels().map(|px| px.0[0]).collect();
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+        assert!(values.windows(2).all(|w| w[1] - w[0] <= 1), "the falloff has bands");
+        assert_eq!((values[0], values[values.len() - 1]), (0, 255));
+
+        // Any type of map works, and a float map gives the same result
+        let mut black_f = solid_color(width, 1, image::ColorType::Rgb8, &[0., 0., 0.]).unwrap();
+        black_f.blend_opacity_map(&white, pixel_normal, &DynamicImage::ImageRgba32F(opacity_map.to_rgba32f()), true, false).unwrap();
+        assert_eq!(black_f, black);
+
+        let small = solid_color(4, 1, image::ColorType::L8, &[1.]).unwrap();
+        assert!(matches!(black.blend_opacity_map(&white, pixel_normal, &small, true, false), Err(Error::DimensionMismatch)));
+    }
+    #[test]
+    fn test_normalize_round_trip() {
+        use crate::normalize::{clamp01, from_f64_normalized, max_value, to_f64_normalized};
+        use image::{Luma, Rgb};
+
+        let max_8 = max_value::<Rgba<u8>>();
+        let max_16 = max_value::<Luma<u16>>();
+        let max_32 = max_value::<Rgb<f32>>();
+        assert_eq!((max_8, max_16, max_32), (255., 65535., 1.));
+        for v in 0..=u8::MAX {
+            assert_eq!(from_f64_normalized::<Rgba<u8>>(to_f64_normalized::<Rgba<u8>>(v, max_8), max_8), v);
+            // Widening is exact, and narrowing back gives the original value
+            let wide = from_f64_normalized::<Luma<u16>>(to_f64_normalized::<Rgba<u8>>(v, max_8), max_16);
+            assert_eq!(wide, u16::from(v) * 257);
+            assert_eq!(from_f64_normalized::<Rgba<u8>>(to_f64_normalized::<Luma<u16>>(wide, max_16), max_8), v);
+        }
+        for v in 0..=u16::MAX {
+            assert_eq!(from_f64_normalized::<Luma<u16>>(to_f64_normalized::<Luma<u16>>(v, max_16), max_16), v);
+            let float = from_f64_normalized::<Rgb<f32>>(to_f64_normalized::<Luma<u16>>(v, max_16), max_32);
+            assert_eq!(from_f64_normalized::<Luma<u16>>(to_f64_normalized::<Rgb<f32>>(float, max_32), max_16), v);
+        }
+        for v in [0f32, 0.1, 0.25, 0.5, 0.999, 1.] {
+            assert_eq!(from_f64_normalized::<Rgb<f32>>(to_f64_normalized::<Rgb<f32>>(v, max_32), max_32), v);
+        }
+        // Matches the quantization of a blend
+        let mut img = ImageBuffer::from_pixel(1, 1, Luma([100u8]));
+        img.blend(&ImageBuffer::from_pixel(1, 1, Luma([200u8])), pixel_average, true, false).unwrap();
+        let expected = pixel_average(to_f64_normalized::<Luma<u8>>(100, max_8), to_f64_normalized::<Luma<u8>>(200, max_8));
+        assert_eq!(img.get_pixel(0, 0).0[0], from_f64_normalized::<Luma<u8>>(expected, max_8));
+
+        assert_eq!(from_f64_normalized::<Rgba<u8>>(-0.5, max_8), 0);
+        assert_eq!(from_f64_normalized::<Rgb<f32>>(1.5, max_32), 1.);
+        assert_eq!(to_f64_normalized::<Rgb<f32>>(1.5, max_32), 1.5);
+        assert_eq!((clamp01(-1.), clamp01(0.3), clamp01(2.)), (0., 0.3, 1.));
+    }
+    #[test]
+    fn test_dimension_policy() {
+        let base = solid_color(8, 6, image::ColorType::Rgba8, &[0., 0., 0., 1.]).unwrap();
+        let white = solid_color(4, 4, image::ColorType::Rgba8, &[1., 1., 1., 1.]).unwrap();
+        let blended = |other: &DynamicImage, dimension_policy| {
+            let mut img = base.clone();
+            img.blend_with_options(other, &BlendOptions { dimension_policy, ..BlendOptions::normal() }).map(|()| img)
+        };
+        let white_at = |img: &DynamicImage| -> Vec<(u32, u32)> {
+            img.as_rgba8().unwrap().enumerate_pixels().filter(|(_, _, px)| px.0 == [255, 255, 255, 255]).map(|(x, y, _)| (x, y)).collect()
+        };
+        let region = |x0, y0, width, height| -> Vec<(u32, u32)> { (y0..y0 + height).flat_map(|y| (x0..x0 + width).map(move |x| (x, y))).collect() };
+
+        assert!(matches!(blended(&white, DimensionPolicy::Strict), Err(Error::DimensionMismatch)));
+        assert_eq!(BlendOptions::normal().dimension_policy, DimensionPolicy::Strict);
+        assert_eq!(white_at(&blended(&white, DimensionPolicy::Crop).unwrap()), region(0, 0, 4, 4));
+        assert_eq!(white_at(&blended(&white, DimensionPolicy::Pad).unwrap()), region(2, 1, 4, 4));
+        assert_eq!(white_at(&blended(&white, DimensionPolicy::Scale).unwrap()), region(0, 0, 8, 6));
+
+        // A larger other is cropped to the top left or center
+        let big = gradient(12, 10, Direction::Horizontal, image::ColorType::Rgba8, &[0., 0., 0., 1.], &[1., 1., 1., 1.]).unwrap();
+        let cropped = blended(&big, DimensionPolicy::Crop).unwrap();
+        assert_eq!(cropped.as_rgba8().unwrap().get_pixel(7, 5), big.as_rgba8().unwrap().get_pixel(7, 5));
+        let padded = blended(&big, DimensionPolicy::Pad).unwrap();
+        assert_eq!(padded.as_rgba8().unwrap().get_pixel(0, 0), big.as_rgba8().unwrap().get_pixel(2, 2));
+
+        // Scaling a gradient keeps it smooth and ordered
+        let scaled = blended(&big, DimensionPolicy::Scale).unwrap();
+        let row: Vec<u8> = (0..8).map(|x| scaled.as_rgba8().unwrap().get_pixel(x, 0).0[0]).collect();
+        assert!(row.windows(2).all(|w| w[0] < w[1]), "{row:?}");
+
+        // Other options still apply
+        let mut img = base.clone();
+        let options = BlendOptions { dimension_policy: DimensionPolicy::Crop, opacity: 0.5, ..BlendOptions::normal() };
+        img.blend_with_options(&white, &options).unwrap();
+        assert_eq!(img.as_rgba8().unwrap().get_pixel(0, 0).0, [128, 128, 128, 255]);
+        assert_eq!(img.as_rgba8().unwrap().get_pixel(7, 5).0, [0, 0, 0, 255]);
+
+        let empty = DynamicImage::new_rgba8(0, 0);
+        assert!(matches!(blended(&empty, DimensionPolicy::Scale), Err(Error::DimensionMismatch)));
+        assert_eq!(blended(&empty, DimensionPolicy::Pad).unwrap(), base);
+    }
+    #[test]
+    fn test_apply_lut_3d_identity() {
+        let img = open("test_data/1.png").unwrap();
+        for size in [2, 17] {
+            let identity = Lut3D::identity(size);
+            as_all_types(&img).for_each(|a| {
+                let mut mapped = a.clone();
+                mapped.apply_lut_3d(&identity).unwrap();
+                // Trilinear interpolation of an identity table is exact up to float error and rounding
+                a.to_rgba32f().pixels().zip(mapped.to_rgba32f().pixels()).for_each(|(before, after)| {
+                    assert!(before.0.iter().zip(after.0).all(|(b, a)| (b - a).abs() < 1e-5), "{size} {}: {before:?} {after:?}", a.color().color_str());
+                });
+            });
+        }
+    }
+    #[test]
+    fn test_apply_lut_3d_channel_swap() {
+        // Rotate the channels: (r, g, b) -> (g, b, r)
+        let mut table = Lut3D::identity(5).table().to_vec();
+        for color in &mut table {
+            *color = [color[1], color[2], color[0]];
+        }
+        let rotate = Lut3D::new(5, table).unwrap();
+        let img = open("test_data/1.png").unwrap();
+        let mut rotated = img.clone();
+        rotated.apply_lut_3d(&rotate).unwrap();
+        img.to_rgba8().pixels().zip(rotated.to_rgba8().pixels()).for_each(|(before, after)| {
+            assert_eq!(after.0, [before.0[1], before.0[2], before.0[0], before.0[3]]);
+        });
+        // Three rotations bring every color back
+        rotated.apply_lut_3d(&rotate).unwrap();
+        rotated.apply_lut_3d(&rotate).unwrap();
+        assert_eq!(rotated, img);
+
+        assert!(matches!(Lut3D::new(1, vec![[0.; 3]]), Err(Error::InvalidArgument(_))));
+        assert!(matches!(Lut3D::new(3, vec![[0.; 3]; 26]), Err(Error::InvalidArgument(_))));
+        assert_eq!(Lut3D::identity(4).size(), 4);
+    }
+    #[test]
+    fn test_apply_lut_1d() {
+        let img = open("test_data/1.png").unwrap();
+        let identity: Vec<[f64; 3]> = (0..=4).map(|i| [<f64 as From<u32>>::from(i) / 4.; 3]).collect();
+        let mut mapped = img.clone();
+        mapped.apply_lut_1d(&identity).unwrap();
+        assert_eq!(mapped, img);
+
+        // Invert red only, with a two entry table
+        let invert_red = [[1., 0., 0.], [0., 1., 1.]];
+        let mut inverted = img.clone();
+        inverted.apply_lut_1d(&invert_red).unwrap();
+        img.to_rgba8().pixels().zip(inverted.to_rgba8().pixels()).for_each(|(before, after)| {
+            assert_eq!(after.0, [255 - before.0[0], before.0[1], before.0[2], before.0[3]]);
+        });
+
+        // Luma takes the luminance of the looked up gray
+        let mut gray = DynamicImage::ImageLumaA8(image::ImageBuffer::from_pixel(2, 2, image::LumaA([100u8, 50])));
+        gray.apply_lut_1d(&[[0., 0., 0.], [1., 0., 0.]]).unwrap();
+        assert_eq!(gray.as_luma_alpha8().unwrap().get_pixel(0, 0).0, [21, 50]);
+        assert!(matches!(gray.apply_lut_1d(&[]), Err(Error::InvalidArgument(_))));
+    }
+    #[test]
+    fn test_composite_sprites() {
+        let sprite = |color: [f64; 4]| solid_color(4, 4, image::ColorType::Rgba8, &color).unwrap();
+        let (red, green, blue) = (sprite([1., 0., 0., 1.]), sprite([0., 1., 0., 1.]), sprite([0., 0., 1., 1.]));
+        let mut canvas = DynamicImage::new_rgba8(10, 10);
+        canvas.composite_sprites(&[(red, -1, -1), (green, 1, 1), (blue, 2, 2)]).unwrap();
+        let canvas = canvas.as_rgba8().unwrap();
+        // Red is clipped at the top left, and each later sprite covers the earlier ones where they overlap
+        assert_eq!(canvas.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(canvas.get_pixel(1, 1).0, [0, 255, 0, 255]);
+        assert_eq!(canvas.get_pixel(2, 1).0, [0, 255, 0, 255]);
+        assert_eq!(canvas.get_pixel(2, 2).0, [0, 0, 255, 255]);
+        assert_eq!(canvas.get_pixel(5, 5).0, [0, 0, 255, 255]);
+        assert_eq!(canvas.get_pixel(6, 6).0, [0, 0, 0, 0]);
+        assert_eq!(canvas.get_pixel(3, 0).0, [0, 0, 0, 0]);
+
+        // A translucent top sprite mixes with what is under it, and alpha composites source-over
+        let mut canvas = DynamicImage::new_rgba8(4, 4);
+        canvas.composite_sprites(&[(sprite([1., 0., 0., 0.5]), 0, 0), (sprite([0., 0., 1., 0.5]), 2, 0)]).unwrap();
+        let canvas = canvas.as_rgba8().unwrap();
+        assert_eq!(canvas.get_pixel(0, 0).0, [255, 0, 0, 128]);
+        let overlap = canvas.get_pixel(2, 0).0;
+        assert!(overlap[2] > overlap[0] && overlap[0] > 0, "{overlap:?}");
+        assert_eq!(overlap[3], 192);
+
+        let mut luma = DynamicImage::new_luma8(4, 4);
+        assert!(matches!(luma.composite_sprites(&[(sprite([1., 0., 0., 1.]), 0, 0)]), Err(Error::UnsupportedBlend(..))));
+    }
+    #[test]
+    fn test_soft_light_variants() {
+        let steps = (0..=20).map(|i| f64::from(i) / 20.0);
+        for a in steps.clone() {
+            for b in steps.clone() {
+                assert_eq!(pixel_soft_light(a, b), pixel_soft_light_w3c(a, b));
+                if b <= 0.5 {
+                    assert!((pixel_soft_light_w3c(a, b) - pixel_soft_light_pegtop(a, b)).abs() < 1e-12);
+                }
+            }
+        }
+        // Above 0.5 white gives sqrt(a) for W3C and 2a - a² for Pegtop
+        assert!((pixel_soft_light_w3c(0.49, 1.0) - 0.7).abs() < 1e-12);
+        assert!((pixel_soft_light_pegtop(0.49, 1.0) - 0.7399).abs() < 1e-12);
+        assert!((pixel_soft_light_w3c(0.5, 0.75) - (0.5 + 0.5 * (0.5f64.sqrt() - 0.5))).abs() < 1e-12);
+        assert!((pixel_soft_light_pegtop(0.5, 0.75) - 0.625).abs() < 1e-12);
+        assert_eq!(BlendMode::SoftLight.op()(0.5, 0.75), pixel_soft_light_w3c(0.5, 0.75));
+        assert_eq!(BlendMode::SoftLightPegtop.op()(0.5, 0.75), pixel_soft_light_pegtop(0.5, 0.75));
+    }
+    #[test]
+    fn test_identity_preserves_bytes() {
+        let img = open("test_data/1.png").unwrap();
+        for rounding in [Rounding::Truncate, Rounding::Round, Rounding::Banker] {
+            let original = img.to_rgba16();
+            let mut blended = original.clone();
+            let options = BlendOptions {
+                apply_to_alpha: true,
+                rounding,
+                ..BlendOptions::new(pixel_normal)
+            };
+            blended.blend_with_options(&original, &options).unwrap();
+            assert_eq!(blended.as_raw(), original.as_raw());
+
+            let original = img.to_rgb8();
+            let mut blended = original.clone();
+            blended.blend_with_options(&original, &BlendOptions { rounding, ..BlendOptions::new(pixel_darker) }).unwrap();
+            assert_eq!(blended.as_raw(), original.as_raw());
+        }
+    }
+    #[test]
+    fn test_blend_with_max() {
+        type Gray12 = ImageBuffer<Luma<u16>, Vec<u16>>;
+        let mut img: Gray12 = ImageBuffer::from_fn(8, 1, |x, _| Luma([[0, 1, 1024, 2047, 2048, 3000, 4094, 4095][x as usize]]));
+        let original = img.clone();
+        let half: Gray12 = ImageBuffer::from_pixel(8, 1, Luma([2048]));
+        img.blend_with_max(&half, pixel_screen, 4095., 4095., true, false).unwrap();
+        for (new, old) in img.pixels().zip(original.pixels()) {
+            let a = f64::from(old.0[0]) / 4095.;
+            let expected = (pixel_screen(a, 2048. / 4095.) * 4095.).round();
+            assert_eq!(f64::from(new.0[0]), expected);
+            assert!(new.0[0] <= 4095);
+        }
+        // Out of range samples are clamped to the given maximum
+        let mut img: Gray12 = ImageBuffer::from_pixel(1, 1, Luma([0]));
+        let over: Gray12 = ImageBuffer::from_pixel(1, 1, Luma([5000]));
+        img.blend_with_max(&over, pixel_add, 4095., 4095., true, false).unwrap();
+        assert_eq!(img.get_pixel(0, 0).0, [4095]);
+        // Mixed ranges, a 12 bit image blended into a full range one
+        let mut full: ImageBuffer<Luma<u16>, Vec<u16>> = ImageBuffer::from_pixel(1, 1, Luma([0]));
+        full.blend_with_max(&over, pixel_normal, 65535., 4095., true, false).unwrap();
+        assert_eq!(full.get_pixel(0, 0).0, [65535]);
+        let twelve: Gray12 = ImageBuffer::from_pixel(1, 1, Luma([2048]));
+        full.blend_with_max(&twelve, pixel_normal, 65535., 4095., true, false).unwrap();
+        assert_eq!(full.get_pixel(0, 0).0, [32776]);
+        assert!(matches!(full.blend_with_max(&twelve, pixel_normal, 0., 4095., true, false), Err(Error::InvalidArgument(_))));
+        assert!(matches!(full.blend_with_max(&twelve, pixel_normal, 65535., f64::NAN, true, false), Err(Error::InvalidArgument(_))));
+        // A maximum of 1.0 on an integer type still rounds, rather than truncating like a cast
+        let mut bit: GrayImage = ImageBuffer::from_pixel(1, 1, Luma([0]));
+        let set: GrayImage = ImageBuffer::from_pixel(1, 1, Luma([1]));
+        bit.blend_with_max(&set, f64::midpoint, 1., 1., true, false).unwrap();
+        assert_eq!(bit.get_pixel(0, 0).0, [1]);
+    }
+    #[test]
+    fn test_alpha_from_edges() {
+        // Black on the left, white on the right, split between x = 7 and x = 8
+        let img = RgbaImage::from_fn(16, 16, |x, _| if x < 8 { Rgba([0, 0, 0, 255]) } else { Rgba([255, 255, 255, 255]) });
+        let mut buffer = img.clone();
+        buffer.alpha_from_edges(0.5).unwrap();
+        for (x, _, px) in buffer.enumerate_pixels() {
+            let expected = if x == 7 || x == 8 { 255 } else { 0 };
+            assert_eq!(px.0[3], expected, "x = {x}");
+            assert_eq!(px.0[..3], img.get_pixel(x, 0).0[..3]);
+        }
+        // Weaker edges fade with the threshold
+        let soft = RgbaImage::from_fn(16, 16, |x, _| if x < 8 { Rgba([0, 0, 0, 255]) } else { Rgba([51, 51, 51, 255]) });
+        let mut buffer = soft.clone();
+        buffer.alpha_from_edges(0.4).unwrap();
+        assert_eq!(buffer.get_pixel(7, 5).0[3], 128);
+        assert_eq!(buffer.get_pixel(2, 5).0[3], 0);
+        assert!(matches!(buffer.alpha_from_edges(0.), Err(Error::InvalidArgument(_))));
+        assert!(matches!(buffer.alpha_from_edges(f64::NAN), Err(Error::InvalidArgument(_))));
+        // Dynamic images gain an alpha channel if they lack one
+        let mut dynamic = DynamicImage::ImageRgba8(img).to_luma16().into();
+        let DynamicImage::ImageLuma16(_) = &dynamic else { panic!() };
+        dynamic.alpha_from_edges(0.5).unwrap();
+        let DynamicImage::ImageLumaA16(gray) = &dynamic else { panic!("expected La16, got {:?}", dynamic.color()) };
+        assert_eq!(gray.get_pixel(8, 3).0, [65535, 65535]);
+        assert_eq!(gray.get_pixel(0, 3).0, [0, 0]);
+        assert_eq!(gray.get_pixel(15, 3).0, [65535, 0]);
+    }
+    #[test]
+    fn test_color_and_alpha_opacity() {
+        let img1 = open("test_data/1.png").unwrap().to_rgba8();
+        let img2 = open("test_data/2.png").unwrap().to_rgba8();
+        let mut blended = img1.clone();
+        let options = BlendOptions {
+            apply_to_alpha: true,
+            color_opacity: 1.,
+            alpha_opacity: 0.,
+            ..BlendOptions::new(pixel_mult)
+        };
+        blended.blend_with_options(&img2, &options).unwrap();
+        let mut color_only = img1.clone();
+        color_only.blend(&img2, pixel_mult, true, false).unwrap();
+        assert_eq!(blended.as_raw(), color_only.as_raw());
+        assert!(blended.pixels().zip(img1.pixels()).all(|(new, old)| new.0[3] == old.0[3]));
+        assert_ne!(blended.as_raw(), img1.as_raw());
+
+        // The opposite leaves color untouched, and color_opacity combines with opacity
+        let mut blended = img1.clone();
+        blended.blend_with_options(&img2, &BlendOptions { color_opacity: 0., ..options }).unwrap();
+        assert!(blended.pixels().zip(img1.pixels()).all(|(new, old)| new.0[..3] == old.0[..3]));
+        let mut halves = img1.clone();
+        halves.blend_with_options(&img2, &BlendOptions { opacity: 0.5, color_opacity: 0.5, ..BlendOptions::multiply() }).unwrap();
+        let mut quarter = img1.clone();
+        quarter.blend_with_options(&img2, &BlendOptions { opacity: 0.25, ..BlendOptions::multiply() }).unwrap();
+        assert_eq!(halves.as_raw(), quarter.as_raw());
+    }
+    #[test]
+    fn test_fourth_channel_as_color() {
+        // CMYK-ish data, where a channel 3 of 0 would read as fully transparent
+        let mut cmyk: RgbaImage = ImageBuffer::from_pixel(4, 4, Rgba([255, 128, 0, 0]));
+        let other: RgbaImage = ImageBuffer::from_pixel(4, 4, Rgba([128, 128, 128, 255]));
+        let options = BlendOptions { fourth_channel_is_alpha: false, ..BlendOptions::add() };
+        cmyk.blend_with_options(&other, &options).unwrap();
+        assert!(cmyk.pixels().all(|px| px.0 == [255, 255, 128, 255]));
+
+        // The other way around, other's channel 3 does not weight the blend
+        let mut cmyk: RgbaImage = ImageBuffer::from_pixel(4, 4, Rgba([0, 0, 0, 64]));
+        let other: RgbaImage = ImageBuffer::from_pixel(4, 4, Rgba([128, 128, 128, 0]));
+        cmyk.blend_with_options(&other, &options).unwrap();
+        assert!(cmyk.pixels().all(|px| px.0 == [128, 128, 128, 64]));
+
+        let rgb: ImageBuffer<image::Rgb<u8>, Vec<u8>> = ImageBuffer::new(4, 4);
+        assert!(matches!(cmyk.blend_with_options(&rgb, &options), Err(Error::UnsupportedBlend(..))));
+    }
+    #[test]
+    fn test_compatibility() {
+        let img1 = open("test_data/1.png").unwrap();
+        let img2 = open("test_data/2.png").unwrap();
+
+        // A compatible pair agrees with blend
+        let report = img1.compatibility_report(&img2);
+        assert!(report.is_compatible() && report.dimensions_match() && report.color_blendable && report.alpha_blendable);
+        assert_eq!((report.self_layout, report.other_layout), (Some("Rgba"), Some("Rgba")));
+        img1.clone().blend(&img2, pixel_mult, true, false).unwrap();
+
+        // Dimension mismatch
+        let small = DynamicImage::new_rgba8(2, 2);
+        let report = img1.compatibility_report(&small);
+        assert!(!report.dimensions_match() && report.color_blendable);
+        assert!(matches!(img1.compatibility(&small), Err(Error::DimensionMismatch)));
+        assert!(matches!(img1.clone().blend(&small, pixel_mult, true, false), Err(Error::DimensionMismatch)));
+
+        // Luma can be blended into rgb, but rgb cannot be blended into luma
+        let luma = img1.to_luma8();
+        let rgb = img2.to_rgb8();
+        let report = rgb.compatibility_report(&luma);
+        assert!(report.is_compatible() && !report.alpha_blendable);
+        let report = luma.compatibility_report(&rgb);
+        assert!(report.dimensions_match() && !report.color_blendable);
+        assert!(matches!(report.result(), Err(Error::UnsupportedBlend("L", "Rgb"))));
+        assert!(matches!(luma.clone().blend(&rgb, pixel_mult, true, false), Err(Error::UnsupportedBlend(..))));
+    }
+    #[test]
+    fn test_blend_tonal() {
+        // Black on the left, white on the right
+        let base = gradient(64, 4, Direction::Horizontal, image::ColorType::Rgb8, &[0., 0., 0.], &[1., 1., 1.]).unwrap();
+        let red = solid_color(64, 4, image::ColorType::Rgb8, &[1., 0., 0.]).unwrap();
+
+        let mut shadows = base.clone();
+        shadows.blend_tonal(&red, pixel_normal, TonalRange::Shadows, true, false).unwrap();
+        let shadows = shadows.to_rgb8();
+        let base = base.to_rgb8();
+        // The darkest pixels are fully replaced, and the brightest barely change
+        assert_eq!(shadows.get_pixel(0, 0).0, [255, 0, 0]);
+        let (bright, bright_base) = (shadows.get_pixel(63, 0).0, base.get_pixel(63, 0).0);
+        assert!(iter::zip(bright, bright_base).all(|(new, old)| new.abs_diff(old) <= 1));
+
+        let mut highlights = DynamicImage::ImageRgb8(base.clone());
+        highlights.blend_tonal(&red, pixel_normal, TonalRange::Highlights, true, false).unwrap();
+        let highlights = highlights.to_rgb8();
+        assert_eq!(highlights.get_pixel(63, 0).0, [255, 0, 0]);
+        assert!(iter::zip(highlights.get_pixel(0, 0).0, base.get_pixel(0, 0).0).all(|(new, old)| new.abs_diff(old) <= 1));
+
+        // Midtones leave both ends almost alone
+        let mut midtones = DynamicImage::ImageRgb8(base.clone());
+        midtones.blend_tonal(&red, pixel_normal, TonalRange::Midtones, true, false).unwrap();
+        let midtones = midtones.to_rgb8();
+        assert!(midtones.get_pixel(0, 0).0[0] < 40 && midtones.get_pixel(63, 0).0[1] > 215);
+    }
+    #[test]
+    fn test_transplant_alpha_curve() {
+        let color: RgbaImage = ImageBuffer::from_pixel(4, 1, Rgba([10, 20, 30, 255]));
+        let mut mask: ImageBuffer<image::LumaA<u16>, Vec<u16>> = ImageBuffer::new(4, 1);
+        for (px, alpha) in mask.pixels_mut().zip([0, 16384, 32768, 65535]) {
+            px.0[1] = alpha;
+        }
+
+        let mut plain = color.clone();
+        plain.transplant_alpha(&mask).unwrap();
+        let mut curved = color.clone();
+        curved.transplant_alpha_curve(&mask, 2.).unwrap();
+        let alphas = |img: &RgbaImage| img.pixels().map(|px| px.0[3]).collect::<Vec<_>>();
+        assert_eq!(alphas(&plain), [0, 63, 127, 255]);
+        // 0.25^2 and 0.5^2, with the ends unchanged
+        assert_eq!(alphas(&curved), [0, 16, 64, 255]);
+        assert!(curved.pixels().all(|px| px.0[..3] == [10, 20, 30]));
+
+        let mut gamma_one = color.clone();
+        gamma_one.transplant_alpha_curve(&mask, 1.).unwrap();
+        assert!(iter::zip(alphas(&gamma_one), alphas(&plain)).all(|(a, b)| a.abs_diff(b) <= 1));
+
+        assert!(matches!(curved.transplant_alpha_curve(&mask, 0.), Err(Error::InvalidArgument(..))));
+        assert!(matches!(curved.transplant_alpha_curve(&ImageBuffer::<Luma<u8>, Vec<u8>>::new(4, 1), 2.), Err(Error::NoAlphaChannel)));
+    }
+    #[test]
+    fn test_transplant_alpha_scaled() {
+        // A half resolution mask fading in from left to right
+        let mask: ImageBuffer<image::LumaA<u8>, Vec<u8>> = ImageBuffer::from_fn(4, 4, |x, _| image::LumaA([0, [0, 85, 170, 255][x as usize]]));
+        let mut img: RgbaImage = ImageBuffer::from_pixel(8, 8, Rgba([10, 20, 30, 255]));
+        img.transplant_alpha_scaled(&mask, FilterType::Triangle).unwrap();
+        assert!(img.pixels().all(|px| px.0[..3] == [10, 20, 30]));
+
+        // Every row rises smoothly, in steps finer than the mask's
+        for y in 0..8 {
+            let alphas: Vec<u8> = (0..8).map(|x| img.get_pixel(x, y).0[3]).collect();
+            assert_eq!(alphas.first(), Some(&0), "{alphas:?}");
+            assert_eq!(alphas.last(), Some(&255), "{alphas:?}");
+            assert!(alphas.windows(2).all(|w| w[0] <= w[1] && w[1] - w[0] <= 64), "{alphas:?}");
+        }
+
+        // The same size is a plain transplant, resizing or not
+        let mut dynamic = DynamicImage::ImageRgba8(ImageBuffer::from_pixel(8, 8, Rgba([10, 20, 30, 255])));
+        dynamic.transplant_alpha_scaled(&DynamicImage::ImageLumaA8(mask.clone()), FilterType::Triangle).unwrap();
+        assert_eq!(dynamic.as_rgba8().unwrap(), &img);
+        let mut same = img.clone();
+        same.transplant_alpha_scaled(&img, FilterType::Nearest).unwrap();
+        assert_eq!(same, img);
+
+        assert!(matches!(img.transplant_alpha_scaled(&GrayImage::new(4, 4), FilterType::Triangle), Err(Error::NoAlphaChannel)));
+        assert!(matches!(img.transplant_alpha_scaled(&RgbaImage::new(0, 0), FilterType::Triangle), Err(Error::DimensionMismatch)));
+    }
+    #[test]
+    fn test_motion_mask() {
+        let previous = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(8, 8, image::Rgb([100, 100, 100])));
+        let moved = |x: u32, y: u32| (2..5).contains(&x) && (3..5).contains(&y);
+        let current = DynamicImage::ImageRgb8(image::RgbImage::from_fn(8, 8, |x, y| {
+            if moved(x, y) {
+                image::Rgb([200, 180, 160])
+            } else if x == 7 {
+                // Noise below the threshold
+                image::Rgb([102, 101, 100])
+            } else {
+                image::Rgb([100, 100, 100])
+            }
+        }));
+
+        let mask = current.motion_mask(&previous, 0.05).unwrap();
+        assert_eq!(mask.color(), image::ColorType::L8);
+        for (x, y, px) in mask.as_luma8().unwrap().enumerate_pixels() {
+            assert_eq!(px.0[0], if moved(x, y) { 255 } else { 0 }, "{x}, {y}");
+        }
+        // Color types don't need to match
+        assert_eq!(current.motion_mask(&DynamicImage::ImageRgba16(previous.to_rgba16()), 0.05).unwrap(), mask);
+        assert!(matches!(current.motion_mask(&previous.crop_imm(0, 0, 4, 4), 0.05), Err(Error::DimensionMismatch)));
+    }
+    #[test]
+    fn test_supported_combinations() {
+        let combinations = supported_combinations();
+        assert_eq!(combinations.len(), 100);
+        for (a, b, supported) in combinations {
+            assert_eq!(supported, can_blend(a, b));
+            let mut img_a = DynamicImage::new(4, 4, a);
+            let img_b = DynamicImage::new(4, 4, b);
+            assert_eq!(supported, img_a.compatibility(&img_b).is_ok(), "{a:?} <- {b:?}");
+            let result = img_a.blend(&img_b, pixel_mult, true, true);
+            assert_eq!(supported, result.is_ok(), "{a:?} <- {b:?}: {result:?}");
+        }
+        assert!(!can_blend(image::ColorType::L8, image::ColorType::Rgba32F));
+        assert!(can_blend(image::ColorType::Rgb16, image::ColorType::La8));
+    }
+    #[test]
+    fn test_blend_under() {
+        // Opaque red on the left, transparent on the right, and a translucent pixel in between
+        let mut layer: RgbaImage = ImageBuffer::from_fn(4, 1, |x, _| match x {
+            0 | 1 => Rgba([255, 0, 0, 255]),
+            2 => Rgba([255, 0, 0, 128]),
+            _ => Rgba([0, 0, 0, 0]),
+        });
+        let background: ImageBuffer<image::Rgb<u16>, Vec<u16>> = ImageBuffer::from_pixel(4, 1, image::Rgb([0, 0, 65535]));
+        let original = layer.clone();
+        layer.blend_under(&background, pixel_normal, true, false).unwrap();
+        assert_eq!(layer.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(layer.get_pixel(1, 0).0, [255, 0, 0, 255]);
+        assert_eq!(layer.get_pixel(2, 0).0, [128, 0, 127, 128]);
+        assert_eq!(layer.get_pixel(3, 0).0, [0, 0, 255, 0]);
+
+        // The same as blending the layer into a copy of the background
+        let img1 = open("test_data/1.png").unwrap();
+        let img2 = open("test_data/2.png").unwrap();
+        let mut under = img1.clone();
+        under.blend_under(&img2, pixel_mult, true, true).unwrap();
+        let mut over = img2.clone();
+        over.blend(&img1, pixel_mult, true, true).unwrap();
+        assert_eq!(under.as_bytes(), over.as_bytes());
+
+        assert!(matches!(original.clone().blend_under(&DynamicImage::new_luma8(4, 1).into_luma8(), pixel_normal, true, false), Ok(())));
+        assert!(matches!(DynamicImage::new_luma8(4, 1).into_luma8().blend_under(&original, pixel_normal, true, false), Err(Error::UnsupportedBlend(..))));
+    }
+    #[test]
+    fn test_blend_accumulator() {
+        let start: RgbaImage = ImageBuffer::from_fn(16, 16, |x, y| Rgba([u8::try_from(x * 16).unwrap(), u8::try_from(y * 16).unwrap(), 100, 255]));
+        let tiny: ImageBuffer<image::Rgba<f32>, Vec<f32>> = ImageBuffer::from_pixel(16, 16, image::Rgba([0.001, 0.002, 0.0007, 1.]));
+
+        let mut in_place = start.clone();
+        for _ in 0..50 {
+            in_place.blend(&tiny, pixel_add, true, false).unwrap();
+        }
+        let mut accumulated = start.clone();
+        let mut accumulator = BlendAccumulator::new(&mut accumulated);
+        for _ in 0..50 {
+            accumulator.blend(&tiny, pixel_add, true, false).unwrap();
+        }
+        accumulator.finish();
+
+        // Compare against the exact result, 50 additions of the tiny values
+        let error = |img: &RgbaImage| -> f64 {
+            iter::zip(img.pixels(), start.pixels()).map(|(px, px_start)| {
+                (0..3).map(|ch| {
+                    let exact = (f64::from(px_start.0[ch]) / 255. + 50. * f64::from(tiny.get_pixel(0, 0).0[ch])).min(1.) * 255.;
+                    (f64::from(px.0[ch]) - exact).abs()
+                }).sum::<f64>()
+            }).sum()
+        };
+        let (error_in_place, error_accumulated) = (error(&in_place), error(&accumulated));
+        assert!(error_accumulated * 20. < error_in_place, "{error_accumulated} vs {error_in_place}");
+        // Every subpixel is within rounding of the exact result
+        assert!(error_accumulated <= 0.5 * 3. * 256.);
+        assert!(accumulated.pixels().all(|px| px.0[3] == 255));
+
+        // Blends that do nothing leave the image exactly as it was
+        let mut untouched = start.clone();
+        let mut accumulator = BlendAccumulator::new(&mut untouched);
+        accumulator.blend(&tiny, pixel_mult, false, false).unwrap();
+        accumulator.finish();
+        assert_eq!(untouched, start);
+
+        let mut accumulator = BlendAccumulator::new(&mut untouched);
+        assert!(matches!(accumulator.blend(&RgbaImage::new(2, 2), pixel_add, true, false), Err(Error::DimensionMismatch)));
+    }
+    #[test]
+    fn test_blend_type_aliases() {
+        // `BufferBlend` across the `image` type aliases. Subpixel type never matters, so each row and column covers both the 8 bit and float aliases.
+        //
+        // | `self` \ `other` | luma | luma alpha | rgb                | rgba               |
+        // |------------------|------|------------|--------------------|--------------------|
+        // | luma             | ok   | ok         | `UnsupportedBlend` | `UnsupportedBlend` |
+        // | luma alpha       | ok   | ok         | `UnsupportedBlend` | `UnsupportedBlend` |
+        // | rgb              | ok   | ok         | ok                 | ok                 |
+        // | rgba             | ok   | ok         | ok                 | ok                 |
+        use image::{GrayAlphaImage, Rgb32FImage, RgbImage};
+        macro_rules! blend_alias {
+            ($dst:ty, $src:ty) => {{
+                let mut dst = <$dst>::new(2, 2);
+                let src = <$src>::new(2, 2);
+                dst.blend(&src, pixel_mult, true, true)
+            }};
+        }
+        macro_rules! assert_supported {
+            ($dst:ty: $($src:ty),+) => {
+                $(assert!(blend_alias!($dst, $src).is_ok(), "{} into {}", stringify!($src), stringify!($dst));)+
+            };
+        }
+        macro_rules! assert_unsupported {
+            ($dst:ty: $($src:ty),+) => {
+                $(assert!(matches!(blend_alias!($dst, $src), Err(Error::UnsupportedBlend(..))), "{} into {}", stringify!($src), stringify!($dst));)+
+            };
+        }
+        assert_supported!(GrayImage: GrayImage, GrayAlphaImage);
+        assert_supported!(GrayAlphaImage: GrayImage, GrayAlphaImage);
+        assert_unsupported!(GrayImage: RgbImage, RgbaImage, Rgb32FImage, Rgba32FImage);
+        assert_unsupported!(GrayAlphaImage: RgbImage, RgbaImage, Rgb32FImage, Rgba32FImage);
+        assert_supported!(RgbImage: GrayImage, GrayAlphaImage, RgbImage, RgbaImage, Rgb32FImage, Rgba32FImage);
+        assert_supported!(RgbaImage: GrayImage, GrayAlphaImage, RgbImage, RgbaImage, Rgb32FImage, Rgba32FImage);
+        assert_supported!(Rgb32FImage: GrayImage, GrayAlphaImage, RgbImage, RgbaImage, Rgb32FImage, Rgba32FImage);
+        assert_supported!(Rgba32FImage: GrayImage, GrayAlphaImage, RgbImage, RgbaImage, Rgb32FImage, Rgba32FImage);
+
+        // A luma `other` is blended into every color channel
+        let mut rgb = RgbImage::from_pixel(1, 1, image::Rgb([200, 100, 50]));
+        rgb.blend(&GrayImage::from_pixel(1, 1, Luma([255])), pixel_mult, true, false).unwrap();
+        assert_eq!(rgb.get_pixel(0, 0).0, [200, 100, 50]);
+        let mut gray = GrayImage::new(1, 1);
+        assert!(matches!(gray.blend(&RgbImage::new(1, 1), pixel_mult, true, false), Err(Error::UnsupportedBlend(..))));
+    }
+    #[test]
+    fn test_light_family() {
+        // `pixel_linear_light` and `pixel_vivid_light` go well outside of 0.0..1.0 near the extremes, where clamping would hide a wrong formula,
+        // so the raw results are checked as well as the clamped results of a blend
+        let close = |got: f64, expected: f64| assert!((got - expected).abs() < 1e-12, "got {got}, expected {expected}");
+        let blend_u8 = |op: fn(f64, f64) -> f64, a: u8, b: u8| {
+            let mut img = GrayImage::from_pixel(1, 1, Luma([a]));
+            img.blend(&GrayImage::from_pixel(1, 1, Luma([b])), op, true, false).unwrap();
+            img.get_pixel(0, 0).0[0]
+        };
+
+        close(pixel_linear_light(0.9, 0.9), 1.7);
+        close(pixel_linear_light(0.1, 0.1), -0.7);
+        close(pixel_linear_light(0.4, 0.5), 0.4);
+        close(pixel_linear_light(0.25, 0.625), 0.5);
+        assert_eq!(blend_u8(pixel_linear_light, 230, 230), 255);
+        assert_eq!(blend_u8(pixel_linear_light, 25, 25), 0);
+
+        // Dodge half: a / (2 * (1 - b))
+        close(pixel_vivid_light(0.9, 0.9), 4.5);
+        close(pixel_vivid_light(0.2, 0.75), 0.4);
+        // Burn half: 1 - (1 - a) / (2 * b)
+        close(pixel_vivid_light(0.1, 0.1), -3.5);
+        close(pixel_vivid_light(0.8, 0.25), 0.6);
+        // Division by zero at the extremes of b
+        assert_eq!(pixel_vivid_light(0.5, 0.), 0.);
+        assert_eq!(pixel_vivid_light(1., 0.), 1.);
+        assert_eq!(pixel_vivid_light(0.5, 1.), 1.);
+        assert_eq!(pixel_vivid_light(0., 1.), 0.);
+        assert_eq!(blend_u8(pixel_vivid_light, 230, 230), 255);
+        assert_eq!(blend_u8(pixel_vivid_light, 25, 25), 0);
+
+        close(pixel_pin_light(0.9, 0.9), 0.9);
+        close(pixel_pin_light(0.1, 0.1), 0.1);
+        close(pixel_pin_light(0.9, 0.25), 0.5);
+        close(pixel_pin_light(0.1, 0.75), 0.5);
+        assert_eq!(blend_u8(pixel_pin_light, 230, 230), 230);
+        assert_eq!(blend_u8(pixel_pin_light, 200, 51), 102);
+
+        let samples = (0..=20).map(|i| f64::from(i) / 20.);
+        for a in samples.clone() {
+            for b in samples.clone() {
+                assert!((0.0..=1.0).contains(&pixel_pin_light(a, b)));
+                assert!((-1.0..=2.0).contains(&pixel_linear_light(a, b)));
+                assert!(!pixel_vivid_light(a, b).is_nan());
+            }
+        }
+    }
+    #[test]
+    fn test_pinned_outputs() {
+        // Pinned outputs of the built in modes whose formulas go beyond addition and multiplication. Every built in op only uses `+`, `-`, `*`,
+        // `/` and `sqrt`, which IEEE 754 requires to be correctly rounded, so the quantized results must be bit identical everywhere. A
+        // failure here means a formula changed, not that a platform drifted.
+        type Gray16 = ImageBuffer<Luma<u16>, Vec<u16>>;
+        /// A mode's name, op, its 8 bit spot values and its checksum.
+        type Pinned = (&'static str, fn(f64, f64) -> f64, [u8; 3], u64);
+        // Every value of `a` against every value of `b` on a coarse 16 bit grid, including both ends and either side of 0.25 and 0.5
+        let levels: Vec<u16> = (0..=64).map(|i: u32| u16::try_from((i * 65535).div_ceil(64)).unwrap()).chain([16383, 16384, 32767, 32768]).collect();
+        let size = u32::try_from(levels.len()).unwrap();
+        let a: Gray16 = ImageBuffer::from_fn(size, size, |x, _| Luma([levels[x as usize]]));
+        let b: Gray16 = ImageBuffer::from_fn(size, size, |_, y| Luma([levels[y as usize]]));
+        // FNV-1a of the blended subpixels
+        let checksum = |op: fn(f64, f64) -> f64| {
+            let mut a = a.clone();
+            a.blend(&b, op, true, false).unwrap();
+            a.as_raw().iter().flat_map(|v| v.to_le_bytes()).fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+        };
+
+        // Blends of 100 with 30, 128 and 200 in 8 bits, to make failures readable, then the checksum of the full 16 bit grid
+        let pinned: [Pinned; 11] = [
+            ("soft_light", pixel_soft_light, [54, 100, 134], 0xb6a3_2e2a_7134_eccb),
+            ("overlay", pixel_overlay, [24, 100, 157], 0xc556_77f9_9aa4_b740),
+            ("hard_light", pixel_hard_light, [24, 101, 188], 0xd506_33b1_d3d5_3368),
+            ("screen", pixel_screen, [118, 178, 222], 0x10ca_24d7_d287_668a),
+            ("div", pixel_div, [255, 199, 128], 0xe4f2_02d7_18c2_e260),
+            ("phoenix", pixel_phoenix, [185, 227, 155], 0xf5cd_9a76_677e_8eaf),
+            ("linear_burn", pixel_linear_burn, [0, 0, 45], 0x75e1_21f6_fd20_b112),
+            ("linear_light", pixel_linear_light, [0, 101, 245], 0xba3b_fa06_ccc5_5d10),
+            ("vivid_light", pixel_vivid_light, [0, 100, 232], 0x51b2_2839_08c5_138d),
+            ("pin_light", pixel_pin_light, [60, 100, 145], 0x3ea5_e078_ca85_93a2),
+            ("hard_mix", pixel_hard_mix, [0, 0, 255], 0xb749_d001_4ab8_39d5),
+        ];
+        for (name, op, spots, expected) in pinned {
+            let blended = [30u8, 128, 200].map(|b| {
+                let mut img = ImageBuffer::from_pixel(1, 1, Luma([100u8]));
+                img.blend(&ImageBuffer::from_pixel(1, 1, Luma([b])), op, true, false).unwrap();
+                img.get_pixel(0, 0).0[0]
+            });
+            assert_eq!(blended, spots, "{name}");
+            assert_eq!(checksum(op), expected, "{name}: {:#018x}", checksum(op));
+        }
+    }
 }